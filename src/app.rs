@@ -1,28 +1,105 @@
 //! Application data for the RSS reader.
 
 use anyhow::Result;
-use chrono::{DateTime, Duration, Utc};
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use chrono::{DateTime, Duration, Local, Utc};
+use crossterm::event::{KeyCode, KeyEvent};
 use directories::ProjectDirs;
 use html2text::from_read;
 use ratatui::layout::Rect;
 use std::char;
 use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
-use tokio::sync::mpsc;
+use std::sync::Arc;
+use tokio::sync::{Semaphore, mpsc};
+use tokio::task::JoinSet;
+use tokio_util::sync::CancellationToken;
 
-use crate::local_storage::LocalStorage;
-use crate::tui::{PopupState, Row, SPINNER_CHARS, ViewState};
+use crate::discovery::{self, DiscoveredFeed, Discovery};
+use crate::extract::extract_article;
+use crate::keymap::{Action, KeyPress, Keymap, Mode};
+use crate::local_storage::{LocalStorage, RetentionPolicy, SearchResult};
+use crate::opml;
+use crate::refresh::{self, MIN_REFRESH_INTERVAL};
+use crate::render::{RenderedContent, text_to_html};
+use crate::theme::Theme;
+use crate::tui::{PopupState, Row, SPINNER_CHARS, ViewState, copy_to_clipboard, get_rows};
+
+/// A feed that lists entries from every RSS feed, rather than entries
+/// of its own.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum VirtualFeed {
+    AllUnread,
+    Today,
+    Starred,
+}
+
+impl VirtualFeed {
+    /// Every virtual feed, in the order they're listed.
+    pub const ALL: [VirtualFeed; 3] = [
+        VirtualFeed::AllUnread,
+        VirtualFeed::Today,
+        VirtualFeed::Starred,
+    ];
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            VirtualFeed::AllUnread => "All Unread",
+            VirtualFeed::Today => "Today",
+            VirtualFeed::Starred => "Starred",
+        }
+    }
+
+    /// Whether the virtual feed lists an entry.
+    pub fn contains(&self, rss_entry: &RssEntry) -> bool {
+        match self {
+            VirtualFeed::AllUnread => !rss_entry.read,
+            VirtualFeed::Today => {
+                rss_entry.published.with_timezone(&Local).date_naive() == Local::now().date_naive()
+            }
+            VirtualFeed::Starred => rss_entry.starred,
+        }
+    }
+}
+
+/// A folder that RSS feeds can be filed under.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Folder {
+    pub id: i64,
+    pub name: String,
+    pub expanded: bool,
+}
 
 /// An RSS feed, a web feed that provides updates in the form of
 /// human-readable entries.
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub struct RssFeed {
     pub id: String,
     pub title: String,
     pub link: String,
     pub rss_entries: Vec<RssEntry>,
     pub expanded: bool,
+    /// The `ETag` header of the feed's last successful response.
+    pub etag: Option<String>,
+    /// The `Last-Modified` header of the feed's last successful response.
+    pub last_modified: Option<String>,
+    /// When the feed was last fetched successfully.
+    pub last_fetched: Option<DateTime<Utc>>,
+    /// Overrides of the global retention policy for this feed's entries.
+    /// Unset rules fall back to the global policy.
+    pub retention_override: RetentionPolicy,
+    /// The folder the feed is filed under, if any.
+    pub folder: Option<String>,
+    /// The URL of the website the feed belongs to, if known.
+    pub html_url: Option<String>,
+    /// How often the feed asks to be refreshed, if it says.
+    pub refresh_interval: Option<Duration>,
+    /// When syncing the feed was last started, successful or not. Not
+    /// saved.
+    pub last_sync_attempt: Option<DateTime<Utc>>,
+    /// Whether the full content of new entries is fetched from their web
+    /// pages while syncing.
+    pub fetch_full_content: bool,
 }
 
 impl From<feed_rs::model::Feed> for RssFeed {
@@ -39,8 +116,23 @@ impl From<feed_rs::model::Feed> for RssFeed {
                 .first()
                 .map(|l| l.href.clone())
                 .unwrap_or_default(),
+            html_url: feed
+                .links
+                .iter()
+                .find(|l| l.rel.as_deref() != Some("self"))
+                .map(|l| l.href.clone()),
+            // A feed's first category seeds its folder.
+            folder: feed
+                .categories
+                .first()
+                .map(|c| c.label.clone().unwrap_or_else(|| c.term.clone()))
+                .map(|c| c.trim().to_string())
+                .filter(|c| !c.is_empty()),
             rss_entries,
             expanded: false,
+            // A feed is converted as soon as it's fetched.
+            last_fetched: Some(Utc::now()),
+            ..Default::default()
         };
         new_rss_feed
             .rss_entries
@@ -52,48 +144,96 @@ impl From<feed_rs::model::Feed> for RssFeed {
 /// An RSS entry, belonging to an RSS feed and containing
 /// human-readable data. An example of an RSS feed entry is
 /// a web article.
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub struct RssEntry {
     pub id: String,
     pub title: String,
     pub authors: Vec<String>,
+    /// The entry's content as plain text, which is searched.
     pub content: String,
+    /// The entry's content as HTML, which is displayed.
+    pub content_html: String,
     pub content_total_lines: usize,
     pub link: String,
     pub published: DateTime<Utc>,
     pub read: bool,
+    /// Whether the entry has been starred. Starred entries never expire.
+    pub starred: bool,
+    /// A hash of the entry's title and content as provided by its feed.
+    /// Used to match entries across syncs and to detect edited entries.
+    pub content_hash: String,
+    /// The content scraped from the entry's web page, if it was fetched.
+    pub full_content: Option<FullContent>,
+}
+
+/// An entry's content as scraped from its web page, kept apart from the
+/// content its feed provides.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FullContent {
+    /// The page's main article, or the whole page if no article was
+    /// found, as HTML.
+    pub html: String,
+    /// When the page was fetched.
+    pub fetched: DateTime<Utc>,
+}
+
+impl RssEntry {
+    /// The HTML the entry is displayed with: its full content if it was
+    /// fetched and `show_full_content` is set, and its feed content
+    /// otherwise.
+    pub fn displayed_html(&self, show_full_content: bool) -> &str {
+        match &self.full_content {
+            Some(full_content) if show_full_content => &full_content.html,
+            _ => &self.content_html,
+        }
+    }
 }
 
 impl From<feed_rs::model::Entry> for RssEntry {
     fn from(entry: feed_rs::model::Entry) -> Self {
         let authors = entry.authors.into_iter().map(|a| a.name).collect();
+        let content_html = entry
+            .content
+            .as_ref()
+            .and_then(|c| c.body.clone())
+            .or_else(|| {
+                entry.summary.as_ref().map(|s| {
+                    if s.content_type.essence().to_string() == "text/plain" {
+                        text_to_html(&s.content)
+                    } else {
+                        s.content.clone()
+                    }
+                })
+            })
+            .unwrap_or_default();
         let content = entry
             .content
             .and_then(|c| {
                 let parsed_html =
                     from_read(c.body?.clone().as_bytes(), usize::MAX).unwrap_or_default();
-                return Some(parsed_html);
+                Some(parsed_html)
             })
             .or_else(|| {
-                entry.summary.map(|s| {
-                    let parsed_html =
-                        from_read(s.content.as_bytes(), usize::MAX).unwrap_or_default();
-                    return parsed_html;
-                })
+                entry
+                    .summary
+                    .map(|s| from_read(s.content.as_bytes(), usize::MAX).unwrap_or_default())
             })
             .unwrap_or_default();
 
-        let published = entry.published.unwrap_or(Utc::now());
+        let published = entry.published.or(entry.updated).unwrap_or(Utc::now());
+        let title = entry
+            .title
+            .map(|t| t.content)
+            .unwrap_or_else(|| "Untitled".into());
+        let content_hash = content_hash(&title, &content);
 
         RssEntry {
             id: entry.id,
-            title: entry
-                .title
-                .map(|t| t.content)
-                .unwrap_or_else(|| "Untitled".into()),
+            title,
             authors,
-            content: content,
-            content_total_lines: 0, // All text is currently on a single line.
+            content,
+            content_html,
+            content_total_lines: 0, // Counted when the content is rendered.
             link: entry
                 .links
                 .first()
@@ -101,19 +241,227 @@ impl From<feed_rs::model::Entry> for RssEntry {
                 .unwrap_or_default(),
             published,
             read: false,
+            starred: false,
+            content_hash,
+            full_content: None,
+        }
+    }
+}
+
+/// Hashes an entry's title and content with 64-bit FNV-1a. The hash is
+/// stored, so it must stay stable across releases.
+pub fn content_hash(title: &str, content: &str) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in title.bytes().chain([0]).chain(content.bytes()) {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{:016x}", hash)
+}
+
+impl RssFeed {
+    /// Merges entries fetched while syncing into the feed. A fetched entry
+    /// is matched to an existing entry by ID, then by link, then by content
+    /// hash. Matched entries whose content changed are refreshed, keeping
+    /// their read and starred flags. Unmatched entries are added, unless
+    /// the retention policy would expire them straight away. Returns the
+    /// number of added entries and the number of refreshed entries.
+    pub fn merge_rss_entries(
+        &mut self,
+        fetched_rss_entries: Vec<RssEntry>,
+        retention_policy: RetentionPolicy,
+    ) -> (usize, usize) {
+        let mut new_rss_entries: Vec<RssEntry> = Vec::new();
+        let mut num_updated = 0;
+        for fetched_rss_entry in fetched_rss_entries {
+            let existing = self
+                .rss_entries
+                .iter()
+                .position(|e| e.id == fetched_rss_entry.id)
+                .or_else(|| {
+                    self.rss_entries.iter().position(|e| {
+                        !fetched_rss_entry.link.is_empty() && e.link == fetched_rss_entry.link
+                    })
+                })
+                .or_else(|| {
+                    self.rss_entries
+                        .iter()
+                        .position(|e| e.content_hash == fetched_rss_entry.content_hash)
+                });
+            match existing {
+                Some(index) => {
+                    let rss_entry = &mut self.rss_entries[index];
+                    if rss_entry.content_hash != fetched_rss_entry.content_hash {
+                        rss_entry.title = fetched_rss_entry.title;
+                        rss_entry.authors = fetched_rss_entry.authors;
+                        rss_entry.content = fetched_rss_entry.content;
+                        rss_entry.content_html = fetched_rss_entry.content_html;
+                        rss_entry.link = fetched_rss_entry.link;
+                        rss_entry.content_hash = fetched_rss_entry.content_hash;
+                        num_updated += 1;
+                    }
+                }
+                None => {
+                    // The same entry can appear more than once in a feed.
+                    if !new_rss_entries.iter().any(|e| e.id == fetched_rss_entry.id) {
+                        new_rss_entries.push(fetched_rss_entry);
+                    }
+                }
+            }
+        }
+
+        // Entries that would expire as soon as they were added are skipped,
+        // so that expired entries don't reappear as unread after each sync.
+        if let Some(unread_ttl) = retention_policy.unread_ttl {
+            let cutoff = Utc::now() - unread_ttl;
+            new_rss_entries.retain(|e| e.published >= cutoff);
+        }
+        if let Some(max_rss_entries) = retention_policy.max_rss_entries {
+            let mut published: Vec<DateTime<Utc>> = self
+                .rss_entries
+                .iter()
+                .filter(|e| !e.starred)
+                .chain(new_rss_entries.iter())
+                .map(|e| e.published)
+                .collect();
+            published.sort_by_key(|p| Reverse(*p));
+            if published.len() > max_rss_entries {
+                match max_rss_entries.checked_sub(1).map(|i| published[i]) {
+                    Some(oldest_kept) => new_rss_entries.retain(|e| e.published >= oldest_kept),
+                    None => new_rss_entries.clear(),
+                }
+            }
+        }
+
+        let num_new = new_rss_entries.len();
+        self.rss_entries.extend(new_rss_entries);
+        self.rss_entries.sort_by_key(|e| Reverse(e.published));
+        (num_new, num_updated)
+    }
+
+    /// Whether the feed is due to be synced in the background. Feeds are
+    /// synced at the interval they ask for, or `default_refresh_interval`
+    /// if they don't say, but never more often than
+    /// `MIN_REFRESH_INTERVAL`.
+    pub fn is_sync_due(&self, default_refresh_interval: Duration, now: DateTime<Utc>) -> bool {
+        let refresh_interval = self
+            .refresh_interval
+            .unwrap_or(default_refresh_interval)
+            .max(MIN_REFRESH_INTERVAL);
+        match self.last_fetched.max(self.last_sync_attempt) {
+            Some(last_synced) => now - last_synced >= refresh_interval,
+            None => true,
+        }
+    }
+}
+
+/// An RSS feed's contents as fetched while syncing, before they are
+/// merged into the feed.
+pub struct FetchedRssFeed {
+    /// The fetched entries. Empty if the feed hasn't changed since it
+    /// was last fetched.
+    pub rss_entries: Vec<RssEntry>,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub last_fetched: DateTime<Utc>,
+    /// How often the feed asks to be refreshed, if it says.
+    pub refresh_interval: Option<Duration>,
+}
+
+/// The outcome of syncing a single RSS feed.
+#[derive(Clone)]
+pub struct RssFeedSyncResult {
+    pub rss_feed_id: String,
+    pub rss_feed_title: String,
+    /// The HTTP status code of the feed's response, if a response
+    /// was received.
+    pub status: Option<u16>,
+    /// The number of entries added to the feed.
+    pub new_rss_entries: usize,
+    /// The number of existing entries refreshed because their content
+    /// changed.
+    pub updated_rss_entries: usize,
+    /// Why the feed failed to sync, if it failed.
+    pub error: Option<String>,
+}
+
+impl RssFeedSyncResult {
+    /// Creates the result of a feed that hasn't finished syncing.
+    fn new(rss_feed: &RssFeed) -> Self {
+        RssFeedSyncResult {
+            rss_feed_id: rss_feed.id.clone(),
+            rss_feed_title: rss_feed.title.clone(),
+            status: None,
+            new_rss_entries: 0,
+            updated_rss_entries: 0,
+            error: None,
+        }
+    }
+}
+
+/// The most results a search shows.
+const MAX_SEARCH_RESULTS: usize = 200;
+
+/// The `strftime` format dates are displayed in by default.
+pub const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d %I:%M%P";
+
+/// Options for syncing feeds.
+#[derive(Clone)]
+pub struct SyncOptions {
+    /// The maximum number of feeds fetched at once.
+    pub max_concurrent_feeds: usize,
+    /// The maximum number of feeds fetched at once from a single host.
+    pub max_concurrent_feeds_per_host: usize,
+    /// How often feeds that don't say how often to refresh them are
+    /// synced in the background. `None` disables background syncing.
+    pub refresh_interval: Option<Duration>,
+    /// The `User-Agent` header requests are sent with. `None` sends
+    /// reqwest's default.
+    pub user_agent: Option<String>,
+}
+
+impl Default for SyncOptions {
+    fn default() -> Self {
+        SyncOptions {
+            max_concurrent_feeds: 8,
+            max_concurrent_feeds_per_host: 2,
+            refresh_interval: Some(Duration::hours(1)),
+            user_agent: None,
+        }
+    }
+}
+
+impl SyncOptions {
+    /// Builds the HTTP client feeds and web pages are fetched with.
+    pub fn http_client(&self) -> reqwest::Client {
+        let mut builder = reqwest::Client::builder();
+        if let Some(user_agent) = &self.user_agent {
+            builder = builder.user_agent(user_agent);
         }
+        builder.build().unwrap_or_default()
     }
 }
 
 /// An app event representing the result of an asynchronous operation.
 pub enum AppEvent {
-    FeedFetched(Result<feed_rs::model::Feed, String>, String),
+    FeedFetched(Result<Box<feed_rs::model::Feed>, String>, String),
+    /// Several feeds were found on a website, for the user to pick from.
+    RssFeedsDiscovered(Vec<DiscoveredFeed>),
+    /// The full content of an entry was fetched from its web page.
     ScrapedEntry {
-        rss_feed_index: usize,
-        rss_entry_index: usize,
+        rss_feed_id: String,
+        rss_entry_id: String,
         result: Result<String, String>,
+        /// Whether the user asked for the content, rather than it being
+        /// fetched while syncing. Only failures the user asked for are
+        /// reported.
+        requested: bool,
     },
-    SyncFinished(Result<Vec<RssFeed>, anyhow::Error>),
+    /// A single RSS feed started being fetched while syncing.
+    RssFeedSyncStarted(String),
+    /// A single RSS feed finished syncing. There are no fetched contents
+    /// if the feed failed to sync.
+    RssFeedSynced(Option<Box<FetchedRssFeed>>, RssFeedSyncResult),
 }
 
 /// Application data. For example, RSS feeds, error messages, view
@@ -124,28 +472,78 @@ pub struct App {
     pub sender: mpsc::UnboundedSender<AppEvent>,
     /// The current error message to display.
     pub error_message: Option<String>,
+    /// The current informational message to display.
+    pub info_message: Option<String>,
     /// The position of the cursor in the input field.
     pub character_index: usize,
-    /// The last key that was pressed.
-    pub last_key: Option<KeyCode>,
+    /// Keys pressed that are the start of a key sequence, like the first
+    /// `g` of `gg`.
+    pub pending_keys: Vec<KeyPress>,
+    /// The keys that perform each action.
+    pub keymap: Keymap,
     /// Which screen to display.
     pub view_state: ViewState,
     /// Which popup to display.
     pub popup: PopupState,
     /// User input. For example, when adding a new feed.
     pub input: String,
+    /// Feeds found on a website, for the user to pick from.
+    pub discovered_rss_feeds: Vec<DiscoveredFeed>,
+    /// The position of the cursor in the list of discovered feeds.
+    pub discovered_rss_feed_cursor: usize,
     /// The position of the cursor in the feeds list.
     pub cursor: usize,
+    /// The virtual feeds that are expanded, listing their entries.
+    pub expanded_virtual_feeds: HashSet<VirtualFeed>,
+    /// Folders that feeds can be filed under, ordered by name.
+    pub folders: Vec<Folder>,
+    /// The position of the cursor in the list of folders to move a feed
+    /// to, where 0 is no folder.
+    pub folder_cursor: usize,
+    /// The position of the cursor in the list of the open entry's links.
+    pub link_cursor: usize,
+    /// The current search, if the user is searching.
+    pub search_query: Option<String>,
+    /// Entries matching the current search, best matches first.
+    pub search_results: Vec<SearchResult>,
+    /// The position of the cursor in the search results.
+    pub search_cursor: usize,
     /// Feeds, which contain entries.
     pub rss_feeds: Vec<RssFeed>,
     /// The current visual line for the current article.
     pub rss_entry_scroll: u16,
     /// Previous frame area. Used for visual navigation.
     pub last_frame_area: Rect,
-    /// Whether the app is currently syncing.
-    pub syncing: bool,
+    /// The IDs of the feeds currently syncing.
+    pub syncing_rss_feed_ids: HashSet<String>,
+    /// The IDs of the feeds currently being fetched, in the order they
+    /// started.
+    pub fetching_rss_feed_ids: Vec<String>,
+    /// Cancels the feeds currently syncing.
+    pub sync_cancellation: CancellationToken,
+    /// Whether the sync report is shown once syncing finishes. Only syncs
+    /// the user asked for are reported.
+    pub report_sync: bool,
+    /// Whether a finished sync's report is waiting for the popup open when
+    /// it finished to close.
+    pub sync_report_pending: bool,
     /// The index used to draw the current frame of the spinner.
     pub spinner_index: usize,
+    /// Per-feed results of the current or last sync.
+    pub sync_results: Vec<RssFeedSyncResult>,
+    /// The number of feeds being synced.
+    pub sync_total: usize,
+    /// Options for syncing feeds.
+    pub sync_options: SyncOptions,
+    /// The colors the TUI is drawn with.
+    pub theme: Theme,
+    /// The `strftime` format dates are displayed in.
+    pub date_format: String,
+    /// The lines the open entry's content was rendered as.
+    pub rendered_content: RenderedContent,
+    /// Whether entries are shown with their full content, where it was
+    /// fetched, rather than the content their feeds provide.
+    pub show_full_content: bool,
     pub storage: LocalStorage,
 }
 
@@ -153,38 +551,57 @@ impl App {
     pub fn new(
         sender: mpsc::UnboundedSender<AppEvent>,
         db_path: Option<PathBuf>,
-        max_ttl: Option<Duration>,
+        retention_policy: RetentionPolicy,
+        sync_options: SyncOptions,
     ) -> anyhow::Result<Self> {
-        let db_path = match db_path {
-            Some(path) => path.join("rss.db"),
-            None => get_default_db_path()?,
-        };
-
-        let max_ttl = match max_ttl {
-            Some(max_ttl) => max_ttl,
-            None => Duration::days(5),
-        };
+        let mut storage = LocalStorage::new(get_db_path(db_path)?, retention_policy)?;
+        let expire_result = storage.expire_old_entries();
+        let rss_feeds = storage.load_rss_feeds()?;
+        let folders = storage.load_folders()?;
 
-        let storage = LocalStorage::new(db_path, max_ttl)?;
-        let _ = storage.expire_old_entries();
-        let rss_feeds = storage.load_rss_feeds().unwrap();
-
-        Ok(App {
-            sender: sender,
+        let mut app = App {
+            sender,
             error_message: None,
+            info_message: None,
             character_index: 0,
-            last_key: None,
+            pending_keys: Vec::new(),
+            keymap: Keymap::default(),
             view_state: ViewState::RssFeeds,
             popup: PopupState::None,
             input: String::new(),
+            discovered_rss_feeds: Vec::new(),
+            discovered_rss_feed_cursor: 0,
             cursor: 0,
-            rss_feeds: rss_feeds,
+            expanded_virtual_feeds: HashSet::new(),
+            folders,
+            folder_cursor: 0,
+            link_cursor: 0,
+            search_query: None,
+            search_results: Vec::new(),
+            search_cursor: 0,
+            rss_feeds,
             rss_entry_scroll: 0,
             last_frame_area: Rect::default(),
-            syncing: false,
+            syncing_rss_feed_ids: HashSet::new(),
+            fetching_rss_feed_ids: Vec::new(),
+            sync_cancellation: CancellationToken::new(),
+            report_sync: false,
+            sync_report_pending: false,
             spinner_index: 0,
+            sync_results: Vec::new(),
+            sync_total: 0,
+            sync_options,
+            theme: Theme::default(),
+            date_format: DEFAULT_DATE_FORMAT.to_string(),
+            rendered_content: RenderedContent::default(),
+            show_full_content: true,
             storage,
-        })
+        };
+        if let Err(err) = expire_result {
+            app.error_message = Some(format!("Failed to expire old entries: {}", err));
+            app.popup = PopupState::Error;
+        }
+        Ok(app)
     }
 
     /// Calculates the maximum RSS entry scroll position possible when
@@ -197,39 +614,550 @@ impl App {
     ) -> u16 {
         let content_total_lines = (self.rss_feeds[rss_feed_index].rss_entries[rss_entry_index]
             .content_total_lines) as u16;
-        if content_total_lines < area_height {
-            0
+        content_total_lines.saturating_sub(area_height)
+    }
+
+    /// Finds the index of the folder a feed is in, if it's in one.
+    pub fn folder_index(&self, rss_feed: &RssFeed) -> Option<usize> {
+        let folder = rss_feed.folder.as_ref()?;
+        self.folders.iter().position(|f| f.name == *folder)
+    }
+
+    /// Reloads the folders, which saving feeds may have created.
+    fn reload_folders(&mut self) {
+        match self.storage.load_folders() {
+            Ok(folders) => self.folders = folders,
+            Err(err) => {
+                self.error_message = Some(err.to_string());
+                self.popup = PopupState::Error;
+            }
+        }
+    }
+
+    /// Creates a folder named after the user's input.
+    fn create_folder(&mut self) {
+        let name = self.input.trim().to_string();
+        if name.is_empty() {
+            return;
+        }
+        if let Err(err) = self.storage.create_folder(&name) {
+            self.error_message = Some(format!("Failed to create folder: {}", err));
+            self.popup = PopupState::Error;
+            return;
+        }
+        self.reload_folders();
+        self.move_cursor_to_folder(&name);
+    }
+
+    /// Moves the cursor to the row of the folder with a name.
+    fn move_cursor_to_folder(&mut self, name: &str) {
+        if let Some(folder_index) = self.folders.iter().position(|f| f.name == name)
+            && let Some(row) = get_rows(self)
+                .iter()
+                .position(|r| *r == Row::Folder(folder_index))
+        {
+            self.cursor = row;
+        }
+    }
+
+    /// Renames a folder after the user's input, keeping its feeds in it.
+    fn rename_folder(&mut self, folder_index: usize) {
+        let name = self.input.trim().to_string();
+        let old_name = self.folders[folder_index].name.clone();
+        if name.is_empty() || name == old_name {
+            return;
+        }
+        if self.folders.iter().any(|f| f.name == name) {
+            self.error_message = Some(format!(
+                "Failed to rename folder: a folder named {} already exists",
+                name
+            ));
+            self.popup = PopupState::Error;
+            return;
+        }
+        self.folders[folder_index].name = name.clone();
+        if let Err(err) = self.storage.save_folder(&self.folders[folder_index]) {
+            self.folders[folder_index].name = old_name;
+            self.error_message = Some(format!("Failed to rename folder: {}", err));
+            self.popup = PopupState::Error;
+            return;
+        }
+        for rss_feed in self
+            .rss_feeds
+            .iter_mut()
+            .filter(|f| f.folder.as_ref() == Some(&old_name))
+        {
+            rss_feed.folder = Some(name.clone());
+        }
+        self.folders.sort_by(|a, b| a.name.cmp(&b.name));
+        self.move_cursor_to_folder(&name);
+    }
+
+    /// Deletes a folder, moving its feeds out of it.
+    fn delete_folder(&mut self, folder_index: usize) {
+        let folder = self.folders.remove(folder_index);
+        if let Err(err) = self.storage.delete_folder(folder.id) {
+            self.error_message = Some(err.to_string());
+            self.popup = PopupState::Error;
+        }
+        for rss_feed in self
+            .rss_feeds
+            .iter_mut()
+            .filter(|f| f.folder.as_ref() == Some(&folder.name))
+        {
+            rss_feed.folder = None;
+        }
+    }
+
+    /// Sets whether a folder is expanded, saving it.
+    fn set_folder_expanded(&mut self, folder_index: usize, expanded: bool) {
+        self.folders[folder_index].expanded = expanded;
+        if let Err(err) = self.storage.save_folder(&self.folders[folder_index]) {
+            self.error_message = Some(err.to_string());
+            self.popup = PopupState::Error;
+        }
+    }
+
+    /// Moves a feed to the folder under the folder cursor, expanding the
+    /// folder and moving the cursor to the feed.
+    fn move_rss_feed(&mut self, rss_feed_index: usize) {
+        let folder_index = self.folder_cursor.checked_sub(1);
+        self.rss_feeds[rss_feed_index].folder = folder_index.map(|i| self.folders[i].name.clone());
+        if let Err(err) = self.storage.save_rss_feed(&self.rss_feeds[rss_feed_index]) {
+            self.error_message = Some(format!("Failed to move feed: {}", err));
+            self.popup = PopupState::Error;
+            return;
+        }
+        if let Some(folder_index) = folder_index {
+            self.set_folder_expanded(folder_index, true);
+        }
+        if let Some(row) = get_rows(self)
+            .iter()
+            .position(|r| *r == Row::RssFeed(rss_feed_index))
+        {
+            self.cursor = row;
+        }
+    }
+
+    /// Lists feed indices in the order the feeds are listed in: the feeds
+    /// of each folder, then the feeds that aren't in a folder.
+    pub fn rss_feed_order(&self) -> Vec<usize> {
+        let mut order: Vec<usize> = Vec::new();
+        for folder_index in 0..self.folders.len() {
+            order.extend(
+                (0..self.rss_feeds.len())
+                    .filter(|i| self.folder_index(&self.rss_feeds[*i]) == Some(folder_index)),
+            );
+        }
+        order.extend(
+            (0..self.rss_feeds.len()).filter(|i| self.folder_index(&self.rss_feeds[*i]).is_none()),
+        );
+        order
+    }
+
+    /// Moves the cursor to the next feed with unread entries after the
+    /// row under the cursor, or the previous one if `forward` is false.
+    /// The folder of the feed is expanded to show it.
+    fn move_cursor_to_unread_rss_feed(&mut self, rows: &[Row], forward: bool) {
+        let order = self.rss_feed_order();
+        // The feeds after the cursor start at `next`, while the feeds
+        // before it end at `previous`.
+        let (next, previous) = match rows.get(self.cursor) {
+            Some(Row::RssFeed(rss_feed_index) | Row::RssEntry(rss_feed_index, _)) => {
+                let position = order.iter().position(|i| i == rss_feed_index).unwrap_or(0);
+                (position + 1, position)
+            }
+            Some(Row::Folder(folder_index)) => {
+                let position = order
+                    .iter()
+                    .position(|i| {
+                        self.folder_index(&self.rss_feeds[*i])
+                            .is_none_or(|f| f >= *folder_index)
+                    })
+                    .unwrap_or(order.len());
+                (position, position)
+            }
+            _ => (0, 0),
+        };
+        let has_unread = |i: &&usize| self.rss_feeds[**i].rss_entries.iter().any(|e| !e.read);
+        let rss_feed_index = if forward {
+            order[next.min(order.len())..].iter().find(has_unread)
+        } else {
+            order[..previous].iter().rev().find(has_unread)
+        };
+        let Some(&rss_feed_index) = rss_feed_index else {
+            return;
+        };
+        if let Some(folder_index) = self.folder_index(&self.rss_feeds[rss_feed_index])
+            && !self.folders[folder_index].expanded
+        {
+            self.set_folder_expanded(folder_index, true);
+        }
+        if let Some(row) = get_rows(self)
+            .iter()
+            .position(|r| *r == Row::RssFeed(rss_feed_index))
+        {
+            self.cursor = row;
+        }
+    }
+
+    /// Opens the next unread entry after an entry, or the previous one if
+    /// `forward` is false, going through the feeds in the order they're
+    /// listed in.
+    fn open_unread_rss_entry(
+        &mut self,
+        rss_feed_index: usize,
+        rss_entry_index: usize,
+        forward: bool,
+    ) {
+        let rss_entries: Vec<(usize, usize)> = self
+            .rss_feed_order()
+            .into_iter()
+            .flat_map(|fi| (0..self.rss_feeds[fi].rss_entries.len()).map(move |ei| (fi, ei)))
+            .collect();
+        let Some(position) = rss_entries
+            .iter()
+            .position(|e| *e == (rss_feed_index, rss_entry_index))
+        else {
+            return;
+        };
+        let is_unread = |(fi, ei): &&(usize, usize)| !self.rss_feeds[*fi].rss_entries[*ei].read;
+        let unread = if forward {
+            rss_entries[position + 1..].iter().find(is_unread)
         } else {
-            content_total_lines - area_height
+            rss_entries[..position].iter().rev().find(is_unread)
+        };
+        match unread {
+            Some(&(fi, ei)) => self.open_rss_entry(fi, ei),
+            None => {
+                self.info_message = Some("No more unread entries".to_string());
+                self.popup = PopupState::Info;
+            }
         }
     }
 
-    /// Adds a new RSS feed.
+    /// Adds a new RSS feed. If the URL entered is a website rather than a
+    /// feed, the feeds the website offers are looked for instead. A single
+    /// feed found is added, while several are offered to pick from.
     pub fn add_rss_feed(&mut self) {
-        let rss_feed_url: String = self.input.clone();
+        let url: String = self.input.trim().to_string();
         self.input.clear();
         self.reset_cursor();
         let sender = self.sender.clone();
+        let client = self.sync_options.http_client();
 
         // Use a background thread to retrieve the new feed.
         tokio::spawn(async move {
-            let result = async {
-                let rss_body = reqwest::get(&rss_feed_url)
-                    .await
-                    .map_err(|e| format!("Failed to add feed: {}", e.to_string()))?
-                    .text()
-                    .await
-                    .map_err(|e| format!("Failed to add feed: {}", e.to_string()))?;
-
-                let feed = feed_rs::parser::parse(rss_body.as_bytes())
-                    .map_err(|e| format!("Failed to add feed: {}", e.to_string()))?;
-                Ok(feed)
-            }
-            .await;
+            let app_event = match discovery::discover_feeds(&client, &url).await {
+                Ok(Discovery::Feed(feed, rss_feed_url)) => {
+                    AppEvent::FeedFetched(Ok(feed), rss_feed_url)
+                }
+                Ok(Discovery::Candidates(mut discovered_rss_feeds))
+                    if discovered_rss_feeds.len() == 1 =>
+                {
+                    let rss_feed_url = discovered_rss_feeds.remove(0).url;
+                    let result = discovery::fetch_feed(&client, &rss_feed_url).await;
+                    AppEvent::FeedFetched(result, rss_feed_url)
+                }
+                Ok(Discovery::Candidates(discovered_rss_feeds)) => {
+                    AppEvent::RssFeedsDiscovered(discovered_rss_feeds)
+                }
+                Err(err) => AppEvent::FeedFetched(Err(err), url),
+            };
+            let _ = sender.send(app_event);
+        });
+    }
+
+    /// Adds the discovered feed under the cursor.
+    fn add_discovered_rss_feed(&mut self) {
+        let Some(discovered_rss_feed) = self
+            .discovered_rss_feeds
+            .get(self.discovered_rss_feed_cursor)
+        else {
+            return;
+        };
+        let rss_feed_url = discovered_rss_feed.url.clone();
+        let sender = self.sender.clone();
+        self.discovered_rss_feeds.clear();
+        self.discovered_rss_feed_cursor = 0;
+        let client = self.sync_options.http_client();
+
+        tokio::spawn(async move {
+            let result = discovery::fetch_feed(&client, &rss_feed_url).await;
             let _ = sender.send(AppEvent::FeedFetched(result, rss_feed_url));
         });
     }
 
+    /// Imports the feeds listed in the OPML file at the path entered by
+    /// the user.
+    pub fn import_opml(&mut self) {
+        let path = PathBuf::from(self.input.trim());
+        let result = std::fs::read_to_string(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|opml| opml::import_opml(&mut self.storage, &opml));
+        match result {
+            Ok(imported_rss_feeds) => {
+                self.info_message = Some(format!("Imported {} feeds", imported_rss_feeds.len()));
+                self.popup = PopupState::Info;
+                self.rss_feeds.extend(imported_rss_feeds);
+                self.rss_feeds.sort_by_key(|e| e.title.to_string());
+                self.reload_folders();
+            }
+            Err(err) => {
+                self.error_message = Some(format!("Failed to import OPML: {}", err));
+                self.popup = PopupState::Error;
+            }
+        }
+    }
+
+    /// Exports every feed to an OPML file at the path entered by the user.
+    pub fn export_opml(&mut self) {
+        let path = PathBuf::from(self.input.trim());
+        match std::fs::write(&path, opml::write_opml(&self.rss_feeds)) {
+            Ok(_) => {
+                self.info_message = Some(format!(
+                    "Exported {} feeds to {}",
+                    self.rss_feeds.len(),
+                    path.display()
+                ));
+                self.popup = PopupState::Info;
+            }
+            Err(err) => {
+                self.error_message = Some(format!("Failed to export OPML: {}", err));
+                self.popup = PopupState::Error;
+            }
+        }
+    }
+
+    /// Searches every stored entry for the search entered by the user,
+    /// showing the results.
+    pub fn search(&mut self) {
+        let search_query = self.input.trim().to_string();
+        if search_query.is_empty() {
+            return;
+        }
+        match self
+            .storage
+            .search_rss_entries(&search_query, MAX_SEARCH_RESULTS)
+        {
+            Ok(search_results) => {
+                self.search_query = Some(search_query);
+                self.search_results = search_results;
+                self.search_cursor = 0;
+                self.view_state = ViewState::SearchResults;
+            }
+            Err(err) => {
+                self.error_message = Some(format!("Failed to search: {}", err));
+                self.popup = PopupState::Error;
+            }
+        }
+    }
+
+    /// Opens an RSS entry, marking it as read.
+    fn open_rss_entry(&mut self, rss_feed_index: usize, rss_entry_index: usize) {
+        self.rss_entry_scroll = 0;
+        self.rss_feeds[rss_feed_index].rss_entries[rss_entry_index].read = true;
+        match self.storage.save_rss_entry(
+            &self.rss_feeds[rss_feed_index].id,
+            &self.rss_feeds[rss_feed_index].rss_entries[rss_entry_index],
+        ) {
+            Ok(_) => {}
+            Err(err) => {
+                self.error_message = Some(err.to_string());
+                self.popup = PopupState::Error;
+            }
+        }
+        self.view_state = ViewState::RssEntry {
+            rss_feed_id: self.rss_feeds[rss_feed_index].id.clone(),
+            rss_entry_id: self.rss_feeds[rss_feed_index].rss_entries[rss_entry_index]
+                .id
+                .clone(),
+        };
+    }
+
+    /// Leaves the entry being read. Entries opened from search results
+    /// return to them.
+    pub fn close_rss_entry(&mut self) {
+        self.view_state = if self.search_query.is_some() {
+            ViewState::SearchResults
+        } else {
+            ViewState::RssFeeds
+        };
+    }
+
+    /// Finds an entry by its feed's ID and its own ID, returning the
+    /// feed's index and the entry's index within it. Returns `None` if
+    /// the feed or the entry no longer exists.
+    pub fn rss_entry_indices(
+        &self,
+        rss_feed_id: &str,
+        rss_entry_id: &str,
+    ) -> Option<(usize, usize)> {
+        let rss_feed_index = self.rss_feeds.iter().position(|f| f.id == rss_feed_id)?;
+        let rss_entry_index = self.rss_feeds[rss_feed_index]
+            .rss_entries
+            .iter()
+            .position(|e| e.id == rss_entry_id)?;
+        Some((rss_feed_index, rss_entry_index))
+    }
+
+    /// The indices of the entry being read. Returns `None` if no entry is
+    /// being read, or if the entry no longer exists.
+    pub fn open_rss_entry_indices(&self) -> Option<(usize, usize)> {
+        match &self.view_state {
+            ViewState::RssEntry {
+                rss_feed_id,
+                rss_entry_id,
+            } => self.rss_entry_indices(rss_feed_id, rss_entry_id),
+            _ => None,
+        }
+    }
+
+    /// Switches between showing entries' full content and the content
+    /// their feeds provide. The full content of an entry that has none is
+    /// fetched instead.
+    fn toggle_full_content(&mut self, rss_feed_index: usize, rss_entry_index: usize) {
+        let rss_entry = &self.rss_feeds[rss_feed_index].rss_entries[rss_entry_index];
+        if rss_entry.full_content.is_none() {
+            self.fetch_full_rss_entries_content(rss_feed_index, vec![rss_entry_index], true);
+            return;
+        }
+        self.show_full_content = !self.show_full_content;
+        self.rss_entry_scroll = 0;
+    }
+
+    /// Sets whether the full content of a feed's new entries is fetched
+    /// while syncing, or unsets it if it's already set.
+    fn toggle_fetch_full_content(&mut self, rss_feed_index: usize) {
+        let rss_feed = &mut self.rss_feeds[rss_feed_index];
+        rss_feed.fetch_full_content = !rss_feed.fetch_full_content;
+        if let Err(err) = self.storage.save_rss_feed(rss_feed) {
+            self.error_message = Some(format!("Failed to save feed: {}", err));
+            self.popup = PopupState::Error;
+            return;
+        }
+        self.info_message = Some(if rss_feed.fetch_full_content {
+            format!("New entries of {} will be fetched in full", rss_feed.title)
+        } else {
+            format!("New entries of {} won't be fetched in full", rss_feed.title)
+        });
+        self.popup = PopupState::Info;
+    }
+
+    /// Stars an RSS entry, or unstars it if it's already starred.
+    /// Starred entries are kept however old they get.
+    pub fn toggle_starred(&mut self, rss_feed_index: usize, rss_entry_index: usize) {
+        let rss_feed = &mut self.rss_feeds[rss_feed_index];
+        let rss_entry = &mut rss_feed.rss_entries[rss_entry_index];
+        rss_entry.starred = !rss_entry.starred;
+        if let Err(err) = self.storage.save_rss_entry(&rss_feed.id, rss_entry) {
+            self.error_message = Some(err.to_string());
+            self.popup = PopupState::Error;
+        }
+    }
+
+    /// Sets whether entries are read, saving them in a single
+    /// transaction. Each entry is a feed index and an entry index.
+    fn set_rss_entries_read(&mut self, rss_entries: Vec<(usize, usize)>, read: bool) {
+        let rss_entries: Vec<(usize, usize)> = rss_entries
+            .into_iter()
+            .filter(|(fi, ei)| self.rss_feeds[*fi].rss_entries[*ei].read != read)
+            .collect();
+        let ids: Vec<(String, String)> = rss_entries
+            .iter()
+            .map(|(fi, ei)| {
+                let rss_feed = &self.rss_feeds[*fi];
+                (rss_feed.id.clone(), rss_feed.rss_entries[*ei].id.clone())
+            })
+            .collect();
+        match self.storage.set_rss_entries_read(&ids, read) {
+            Ok(_) => {
+                for (fi, ei) in rss_entries {
+                    self.rss_feeds[fi].rss_entries[ei].read = read;
+                }
+            }
+            Err(err) => {
+                self.error_message = Some(format!("Failed to mark entries: {}", err));
+                self.popup = PopupState::Error;
+            }
+        }
+    }
+
+    /// Marks an RSS entry as unread if it's read, and as read otherwise.
+    pub fn toggle_read(&mut self, rss_feed_index: usize, rss_entry_index: usize) {
+        let read = !self.rss_feeds[rss_feed_index].rss_entries[rss_entry_index].read;
+        self.set_rss_entries_read(vec![(rss_feed_index, rss_entry_index)], read);
+    }
+
+    /// Marks the entries listed under a row as read: a virtual feed's
+    /// entries, every feed in a folder, or the feed of a feed or entry
+    /// row.
+    pub fn mark_row_read(&mut self, row: &Row) {
+        let rss_entries: Vec<(usize, usize)> = self
+            .rss_feeds
+            .iter()
+            .enumerate()
+            .flat_map(|(fi, rss_feed)| {
+                let in_row = match row {
+                    Row::VirtualFeed(_) => true,
+                    Row::Folder(folder_index) => self.folder_index(rss_feed) == Some(*folder_index),
+                    Row::VirtualFeedEntry(_, rss_feed_index, _)
+                    | Row::RssFeed(rss_feed_index)
+                    | Row::RssEntry(rss_feed_index, _) => fi == *rss_feed_index,
+                };
+                rss_feed
+                    .rss_entries
+                    .iter()
+                    .enumerate()
+                    .filter(move |(_, rss_entry)| match row {
+                        Row::VirtualFeed(virtual_feed) => virtual_feed.contains(rss_entry),
+                        _ => in_row,
+                    })
+                    .map(move |(ei, _)| (fi, ei))
+            })
+            .collect();
+        self.set_rss_entries_read(rss_entries, true);
+    }
+
+    /// Marks every entry as read.
+    pub fn mark_all_read(&mut self) {
+        let rss_entries: Vec<(usize, usize)> = self
+            .rss_feeds
+            .iter()
+            .enumerate()
+            .flat_map(|(fi, rss_feed)| (0..rss_feed.rss_entries.len()).map(move |ei| (fi, ei)))
+            .collect();
+        self.set_rss_entries_read(rss_entries, true);
+    }
+
+    /// Marks entries published more than the number of days the user
+    /// entered ago as read.
+    fn mark_older_read(&mut self) {
+        let days: i64 = match self.input.trim().parse() {
+            Ok(days) if days >= 0 => days,
+            _ => {
+                self.error_message = Some(format!("Not a number of days: {}", self.input.trim()));
+                self.popup = PopupState::Error;
+                return;
+            }
+        };
+        let cutoff = Utc::now() - Duration::days(days);
+        let rss_entries: Vec<(usize, usize)> = self
+            .rss_feeds
+            .iter()
+            .enumerate()
+            .flat_map(|(fi, rss_feed)| {
+                rss_feed
+                    .rss_entries
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, rss_entry)| rss_entry.published < cutoff)
+                    .map(move |(ei, _)| (fi, ei))
+            })
+            .collect();
+        self.set_rss_entries_read(rss_entries, true);
+    }
+
     /// Deletes an RSS feed.
     pub fn delete_rss_feed(&mut self, rss_feed_index: usize) {
         match self
@@ -313,50 +1241,132 @@ impl App {
         self.character_index = 0;
     }
 
-    /// Updates all RSS feeds, adding new entries.
-    fn sync(&mut self) {
+    /// Whether any feeds are currently syncing.
+    pub fn is_syncing(&self) -> bool {
+        !self.syncing_rss_feed_ids.is_empty()
+    }
+
+    /// Updates the RSS feeds at the given indices, adding new entries.
+    /// Feeds that are already syncing are skipped. Results accumulate
+    /// until every syncing feed has finished.
+    fn sync(&mut self, rss_feed_indices: Vec<usize>) {
+        if !self.is_syncing() {
+            self.sync_results.clear();
+            self.sync_total = 0;
+        }
+        if self.sync_cancellation.is_cancelled() {
+            self.sync_cancellation = CancellationToken::new();
+        }
+        let now = Utc::now();
+        let mut rss_feeds = Vec::new();
+        for rss_feed_index in rss_feed_indices {
+            let rss_feed = &mut self.rss_feeds[rss_feed_index];
+            if self.syncing_rss_feed_ids.insert(rss_feed.id.clone()) {
+                rss_feed.last_sync_attempt = Some(now);
+                rss_feeds.push(rss_feed.clone());
+            }
+        }
+        if rss_feeds.is_empty() {
+            if !self.is_syncing() {
+                self.finish_sync();
+            }
+            return;
+        }
+        self.sync_total += rss_feeds.len();
         let sender = self.sender.clone();
-        let rss_feeds = self.rss_feeds.clone();
+        let sync_options = self.sync_options.clone();
+        let cancellation = self.sync_cancellation.clone();
         tokio::spawn(async move {
-            let result = sync_feeds(rss_feeds).await;
-            let _ = sender.send(AppEvent::SyncFinished(result));
+            sync_feeds(rss_feeds, sync_options, &sender, cancellation).await;
         });
     }
 
-    /// Updates spinner appearance.
-    pub fn on_tick(&mut self) {
-        if self.syncing {
-            self.spinner_index = (self.spinner_index + 1) % SPINNER_CHARS.len();
+    /// Cancels the feeds currently syncing. Feeds that already finished
+    /// keep their new entries, and no sync report is shown.
+    fn cancel_sync(&mut self) {
+        self.report_sync = false;
+        self.sync_cancellation.cancel();
+    }
+
+    /// Ends a sync, showing the sync report if the user asked for it.
+    fn finish_sync(&mut self) {
+        if self.report_sync {
+            self.report_sync = false;
+            self.sync_report_pending = true;
+            self.show_pending_sync_report();
         }
     }
 
-    /// Uses an entry's URL to scrape web contents.
-    fn fetch_full_rss_entry_content(&mut self, rss_feed_index: usize, rss_entry_index: usize) {
-        let sender = self.sender.clone();
-        let link = self.rss_feeds[rss_feed_index].rss_entries[rss_entry_index]
-            .link
-            .clone();
+    /// Shows the report of a finished sync, unless another popup is open,
+    /// in which case the report waits for it to close.
+    fn show_pending_sync_report(&mut self) {
+        if self.sync_report_pending && self.popup == PopupState::None {
+            self.sync_report_pending = false;
+            self.popup = PopupState::SyncReport;
+        }
+    }
+
+    /// Starts syncing the feeds that are due to be refreshed, in the
+    /// background.
+    fn sync_due_rss_feeds(&mut self) {
+        let Some(refresh_interval) = self.sync_options.refresh_interval else {
+            return;
+        };
+        let now = Utc::now();
+        let due_rss_feed_indices: Vec<usize> = self
+            .rss_feeds
+            .iter()
+            .enumerate()
+            .filter(|(_, f)| {
+                !self.syncing_rss_feed_ids.contains(&f.id) && f.is_sync_due(refresh_interval, now)
+            })
+            .map(|(i, _)| i)
+            .collect();
+        if !due_rss_feed_indices.is_empty() {
+            self.sync(due_rss_feed_indices);
+        }
+    }
+
+    /// Updates spinner appearance and starts background syncs of feeds
+    /// that are due.
+    pub fn on_tick(&mut self) {
+        if self.is_syncing() {
+            self.spinner_index = (self.spinner_index + 1) % SPINNER_CHARS.len();
+        }
+        self.sync_due_rss_feeds();
+    }
+
+    /// Fetches the full content of a feed's entries from their web pages,
+    /// one after another so that their website isn't flooded with
+    /// requests. `requested` is whether the user asked for the content.
+    fn fetch_full_rss_entries_content(
+        &mut self,
+        rss_feed_index: usize,
+        rss_entry_indices: Vec<usize>,
+        requested: bool,
+    ) {
+        let sender = self.sender.clone();
+        let rss_feed = &self.rss_feeds[rss_feed_index];
+        let rss_feed_id = rss_feed.id.clone();
+        let rss_entries: Vec<(String, String)> = rss_entry_indices
+            .into_iter()
+            .map(|i| {
+                let rss_entry = &rss_feed.rss_entries[i];
+                (rss_entry.id.clone(), rss_entry.link.clone())
+            })
+            .collect();
 
-        let html_width = self.last_frame_area.width;
+        let client = self.sync_options.http_client();
         tokio::spawn(async move {
-            let result = async {
-                let html = reqwest::get(&link)
-                    .await
-                    .map_err(|e| format!("Failed to load full content: {}", e.to_string()))?
-                    .text()
-                    .await
-                    .map_err(|e| format!("Failed to load full content: {}", e.to_string()))?;
-                let parsed_html =
-                    from_read(html.as_bytes(), html_width as usize).expect("Failed to parse HTML");
-                Ok(parsed_html)
+            for (rss_entry_id, link) in rss_entries {
+                let result = fetch_full_content(&client, &link).await;
+                let _ = sender.send(AppEvent::ScrapedEntry {
+                    rss_feed_id: rss_feed_id.clone(),
+                    rss_entry_id,
+                    result,
+                    requested,
+                });
             }
-            .await;
-
-            let _ = sender.send(AppEvent::ScrapedEntry {
-                rss_feed_index,
-                rss_entry_index,
-                result,
-            });
         });
     }
 
@@ -364,138 +1374,280 @@ impl App {
     pub fn handle_app_event(&mut self, app_event: AppEvent) {
         match app_event {
             AppEvent::ScrapedEntry {
-                rss_feed_index,
-                rss_entry_index,
+                rss_feed_id,
+                rss_entry_id,
                 result,
+                requested,
             } => match result {
-                Ok(content) => {
-                    self.rss_feeds[rss_feed_index].rss_entries[rss_entry_index].content = content;
-                    match self.storage.save_rss_entry(
-                        &self.rss_feeds[rss_feed_index].id,
-                        &self.rss_feeds[rss_feed_index].rss_entries[rss_entry_index],
-                    ) {
-                        Ok(_) => {}
-                        Err(err) => {
-                            self.error_message = Some(err.to_string());
-                            self.popup = PopupState::Error;
-                        }
+                Ok(html) => {
+                    // The entry may have been removed while its content
+                    // was being fetched.
+                    let Some((rss_feed_index, rss_entry_index)) =
+                        self.rss_entry_indices(&rss_feed_id, &rss_entry_id)
+                    else {
+                        return;
+                    };
+                    let rss_feed = &mut self.rss_feeds[rss_feed_index];
+                    let rss_entry = &mut rss_feed.rss_entries[rss_entry_index];
+                    rss_entry.full_content = Some(FullContent {
+                        html,
+                        fetched: Utc::now(),
+                    });
+                    if requested {
+                        self.show_full_content = true;
+                    }
+                    if let Err(err) = self.storage.save_rss_entry(&rss_feed.id, rss_entry) {
+                        self.error_message = Some(err.to_string());
+                        self.popup = PopupState::Error;
                     }
-                    self.error_message = None;
                 }
                 Err(err) => {
-                    self.error_message = Some(err);
-                    self.popup = PopupState::Error;
+                    if requested {
+                        self.error_message = Some(err);
+                        self.popup = PopupState::Error;
+                    }
                 }
             },
             AppEvent::FeedFetched(Ok(feed), feed_url) => {
-                let mut new_rss_feed = RssFeed::from(feed);
+                let mut new_rss_feed = RssFeed::from(*feed);
                 new_rss_feed.link = feed_url;
-                match self.storage.save_rss_feed(&new_rss_feed) {
-                    Ok(_) => {}
-                    Err(err) => {
-                        self.error_message = Some(err.to_string());
-                        self.popup = PopupState::Error;
-                    }
-                }
-                if let Some(_) = self.rss_feeds.iter().find(|f| f.id == new_rss_feed.id) {
+                if self
+                    .rss_feeds
+                    .iter()
+                    .any(|f| f.id == new_rss_feed.id || f.link == new_rss_feed.link)
+                {
                     self.error_message = Some(format!(
                         "failed to add {}: feed already exists",
                         new_rss_feed.title
                     ));
                     self.popup = PopupState::Error;
+                } else if let Err(err) = self.storage.save_rss_feed(&new_rss_feed) {
+                    self.error_message = Some(err.to_string());
+                    self.popup = PopupState::Error;
                 } else {
                     self.rss_feeds.push(new_rss_feed);
                     self.rss_feeds.sort_by_key(|e| e.title.to_string());
+                    self.reload_folders();
                 }
             }
             AppEvent::FeedFetched(Err(err), _) => {
                 self.error_message = Some(err);
                 self.popup = PopupState::Error;
             }
-            AppEvent::SyncFinished(result) => match result {
-                Ok(rss_feeds) => {
-                    self.rss_feeds = rss_feeds;
-                    match self.storage.save_rss_feeds(&self.rss_feeds) {
-                        Ok(_) => {
-                            self.popup = PopupState::None;
-                            self.syncing = false;
-                        }
-                        Err(err) => {
-                            self.error_message = Some(err.to_string());
-                            self.popup = PopupState::Error;
+            AppEvent::RssFeedsDiscovered(discovered_rss_feeds) => {
+                self.discovered_rss_feeds = discovered_rss_feeds;
+                self.discovered_rss_feed_cursor = 0;
+                self.popup = PopupState::PickDiscoveredRssFeed;
+            }
+            AppEvent::RssFeedSyncStarted(rss_feed_id) => {
+                if self.syncing_rss_feed_ids.contains(&rss_feed_id) {
+                    self.fetching_rss_feed_ids.push(rss_feed_id);
+                }
+            }
+            AppEvent::RssFeedSynced(fetched_rss_feed, mut sync_result) => {
+                // Only feeds that synced successfully have changed.
+                if let Some(fetched_rss_feed) = fetched_rss_feed
+                    && let Some(rss_feed_index) = self
+                        .rss_feeds
+                        .iter()
+                        .position(|f| f.id == sync_result.rss_feed_id)
+                {
+                    let rss_feed = &mut self.rss_feeds[rss_feed_index];
+                    let old_rss_entry_ids: HashSet<String> =
+                        rss_feed.rss_entries.iter().map(|e| e.id.clone()).collect();
+                    save_synced_rss_feed(
+                        &mut self.storage,
+                        rss_feed,
+                        *fetched_rss_feed,
+                        &mut sync_result,
+                    );
+                    if rss_feed.fetch_full_content {
+                        let new_rss_entry_indices: Vec<usize> = rss_feed
+                            .rss_entries
+                            .iter()
+                            .enumerate()
+                            .filter(|(_, e)| !old_rss_entry_ids.contains(&e.id))
+                            .map(|(i, _)| i)
+                            .collect();
+                        if !new_rss_entry_indices.is_empty() {
+                            self.fetch_full_rss_entries_content(
+                                rss_feed_index,
+                                new_rss_entry_indices,
+                                false,
+                            );
                         }
                     }
                 }
-                Err(e) => {
-                    self.error_message = Some(format!("Sync failed: {}", e));
-                    self.popup = PopupState::Error;
+                let was_syncing = self.is_syncing();
+                self.syncing_rss_feed_ids.remove(&sync_result.rss_feed_id);
+                self.fetching_rss_feed_ids
+                    .retain(|id| *id != sync_result.rss_feed_id);
+                self.sync_results.push(sync_result);
+                if was_syncing && !self.is_syncing() {
+                    self.finish_sync();
                 }
-            },
+            }
+        }
+    }
+
+    /// The mode keys are pressed in, which decides the actions they
+    /// perform.
+    pub fn mode(&self) -> Mode {
+        if self.popup.accepts_input() {
+            return Mode::Input;
+        }
+        if self.popup != PopupState::None {
+            return Mode::Popup;
+        }
+        match self.view_state {
+            ViewState::RssFeeds => Mode::Feeds,
+            ViewState::RssEntry { .. } => Mode::Entry,
+            ViewState::SearchResults => Mode::SearchResults,
         }
     }
 
-    /// Handles user key input. The behaviour of key inputs change depending
-    /// on the context, such as view state of the reader.
+    /// Handles user key input. Keys are resolved to actions with the
+    /// keymap of the current mode, and the behaviour of actions changes
+    /// depending on the context, such as view state of the reader.
     pub fn handle_key(&mut self, key: KeyEvent, rows: &[Row]) -> Result<bool> {
+        let exit = self.handle_key_actions(key, rows)?;
+        self.show_pending_sync_report();
+        Ok(exit)
+    }
+
+    /// Resolves a key press to actions and performs them.
+    fn handle_key_actions(&mut self, key: KeyEvent, rows: &[Row]) -> Result<bool> {
+        let actions = self
+            .keymap
+            .resolve(self.mode(), &mut self.pending_keys, key);
+        if actions.is_empty() && self.popup.accepts_input() {
+            return self.handle_input_popup(key, None, rows);
+        }
+        for (index, action) in actions.into_iter().enumerate() {
+            // An action before this one may have changed the rows.
+            let exit = if index == 0 {
+                self.handle_action(key, action, rows)?
+            } else {
+                self.handle_action(key, action, &get_rows(self))?
+            };
+            if exit {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Performs an action resolved from a key press.
+    fn handle_action(&mut self, key: KeyEvent, action: Action, rows: &[Row]) -> Result<bool> {
+        if self.popup.accepts_input() {
+            return self.handle_input_popup(key, Some(action), rows);
+        }
         match self.popup {
-            PopupState::AddRssFeed => self.handle_add_rss_feed_popup(key),
-            PopupState::ConfirmDeleteRssFeed => self.handle_delete_rss_feed_popup(key, rows),
-            PopupState::Error => self.handle_error_popup(key),
-            PopupState::RssEntryHelp => self.handle_rss_entry_help_popup(key),
-            PopupState::RssFeedHelp => self.handle_rss_feed_help_popup(key),
-            PopupState::None => self.handle_default(key, rows),
-            PopupState::Syncing => Ok(false),
+            PopupState::ConfirmDeleteRssFeed => self.handle_delete_rss_feed_popup(action, rows),
+            PopupState::Error => self.handle_error_popup(action),
+            PopupState::Info => self.handle_info_popup(action),
+            PopupState::MoveRssFeed => self.handle_move_rss_feed_popup(action, rows),
+            PopupState::PickDiscoveredRssFeed => self.handle_pick_discovered_rss_feed_popup(action),
+            PopupState::Links => self.handle_links_popup(action),
+            PopupState::RssEntryHelp | PopupState::RssFeedHelp => self.handle_help_popup(action),
+            PopupState::SyncReport => self.handle_sync_report_popup(action),
+            _ => self.handle_default(action, rows),
         }
     }
 
-    /// Handles input when the add RSS feed popup is displayed.
-    fn handle_add_rss_feed_popup(&mut self, key: KeyEvent) -> Result<bool> {
-        match key.code {
-            KeyCode::Esc | KeyCode::Char('q') => {
+    /// Handles input when a popup that accepts user input is displayed,
+    /// like the add RSS feed popup. Keys that don't submit or close the
+    /// popup edit the input.
+    fn handle_input_popup(
+        &mut self,
+        key: KeyEvent,
+        action: Option<Action>,
+        rows: &[Row],
+    ) -> Result<bool> {
+        match action {
+            Some(Action::Back) => {
                 self.input.clear();
                 self.character_index = 0;
                 self.popup = PopupState::None;
             }
-            KeyCode::Enter => {
-                self.add_rss_feed();
+            Some(Action::Select) => {
+                // Submitting may open an info or error popup.
+                let popup = std::mem::replace(&mut self.popup, PopupState::None);
+                match popup {
+                    PopupState::CreateFolder => self.create_folder(),
+                    PopupState::RenameFolder => {
+                        if let Some(Row::Folder(folder_index)) = rows.get(self.cursor) {
+                            self.rename_folder(*folder_index);
+                        }
+                    }
+                    PopupState::ImportOpml => self.import_opml(),
+                    PopupState::ExportOpml => self.export_opml(),
+                    PopupState::Search => self.search(),
+                    PopupState::MarkOlderRead => self.mark_older_read(),
+                    _ => self.add_rss_feed(),
+                }
                 self.input.clear();
                 self.character_index = 0;
-                self.popup = PopupState::None;
             }
-            KeyCode::Char(c) => self.enter_char(c),
-            KeyCode::Backspace => self.delete_char(),
-            KeyCode::Left => self.move_cursor_left(),
-            KeyCode::Right => self.move_cursor_right(),
-            _ => {}
+            _ => match key.code {
+                KeyCode::Char(c) => self.enter_char(c),
+                KeyCode::Backspace => self.delete_char(),
+                KeyCode::Left => self.move_cursor_left(),
+                KeyCode::Right => self.move_cursor_right(),
+                _ => {}
+            },
         }
         Ok(false)
     }
 
     /// Handles input when the delete RSS feed popup is displayed.
-    fn handle_delete_rss_feed_popup(&mut self, key: KeyEvent, rows: &[Row]) -> Result<bool> {
-        match key.code {
-            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('n') => self.popup = PopupState::None,
-            KeyCode::Char('y') => {
+    fn handle_delete_rss_feed_popup(&mut self, action: Action, rows: &[Row]) -> Result<bool> {
+        match action {
+            Action::Back | Action::Deny => self.popup = PopupState::None,
+            Action::Confirm => {
+                self.popup = PopupState::None;
                 let row = &rows[self.cursor];
+                // The cursor moves to the row before the deleted row.
                 match row {
+                    Row::Folder(folder_index) => {
+                        self.delete_folder(*folder_index);
+                        self.cursor = self.cursor.saturating_sub(1);
+                    }
                     Row::RssFeed(rss_feed_index) => {
                         self.delete_rss_feed(*rss_feed_index);
-                        if *rss_feed_index > 0 {
-                            self.cursor = rss_feed_index - 1;
-                        } else {
-                            self.cursor = 0;
-                        }
+                        self.cursor = self.cursor.saturating_sub(1);
                     }
                     Row::RssEntry(rss_feed_index, rss_entry_index) => {
                         self.delete_rss_feed(*rss_feed_index);
-                        if *rss_feed_index > 0 {
-                            self.cursor = self.cursor - rss_entry_index - 2;
-                        } else {
-                            self.cursor = 0;
-                        }
+                        self.cursor = (self.cursor - rss_entry_index - 1).saturating_sub(1);
                     }
+                    // Virtual feeds can't be deleted.
+                    Row::VirtualFeed(_) | Row::VirtualFeedEntry(..) => {}
                 }
+            }
+            _ => {}
+        }
+        Ok(false)
+    }
+
+    /// Handles input when the popup for moving a feed to a folder is
+    /// displayed.
+    fn handle_move_rss_feed_popup(&mut self, action: Action, rows: &[Row]) -> Result<bool> {
+        match action {
+            Action::Back => self.popup = PopupState::None,
+            Action::ScrollDown if self.folder_cursor < self.folders.len() => {
+                self.folder_cursor += 1;
+            }
+            Action::ScrollUp => {
+                self.folder_cursor = self.folder_cursor.saturating_sub(1);
+            }
+            Action::Select => {
                 self.popup = PopupState::None;
+                if let Some(Row::RssFeed(rss_feed_index) | Row::RssEntry(rss_feed_index, _)) =
+                    rows.get(self.cursor)
+                {
+                    self.move_rss_feed(*rss_feed_index);
+                }
             }
             _ => {}
         }
@@ -503,9 +1655,9 @@ impl App {
     }
 
     /// Handles input when the error popup is displayed.
-    fn handle_error_popup(&mut self, key: KeyEvent) -> Result<bool> {
-        match key.code {
-            KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') => {
+    fn handle_error_popup(&mut self, action: Action) -> Result<bool> {
+        match action {
+            Action::Back | Action::Select => {
                 self.error_message = None;
                 self.popup = PopupState::None;
             }
@@ -514,188 +1666,285 @@ impl App {
         Ok(false)
     }
 
-    /// Handles input when the RSS feed help popup is displayed.
-    fn handle_rss_feed_help_popup(&mut self, key: KeyEvent) -> Result<bool> {
-        match key.code {
-            KeyCode::Esc | KeyCode::Char('q') => self.popup = PopupState::None,
+    /// Handles input when the popup for picking a discovered feed is
+    /// displayed.
+    fn handle_pick_discovered_rss_feed_popup(&mut self, action: Action) -> Result<bool> {
+        match action {
+            Action::Back => {
+                self.discovered_rss_feeds.clear();
+                self.discovered_rss_feed_cursor = 0;
+                self.popup = PopupState::None;
+            }
+            Action::ScrollDown
+                if self.discovered_rss_feed_cursor + 1 < self.discovered_rss_feeds.len() =>
+            {
+                self.discovered_rss_feed_cursor += 1;
+            }
+            Action::ScrollUp => {
+                self.discovered_rss_feed_cursor = self.discovered_rss_feed_cursor.saturating_sub(1);
+            }
+            Action::Select => {
+                self.add_discovered_rss_feed();
+                self.popup = PopupState::None;
+            }
+            _ => {}
+        }
+        Ok(false)
+    }
+
+    /// Handles input when the popup listing the open entry's links is
+    /// displayed. The cursor wraps around the list.
+    fn handle_links_popup(&mut self, action: Action) -> Result<bool> {
+        let links = self.rendered_content.links();
+        let Some(link) = links.get(self.link_cursor).cloned() else {
+            self.popup = PopupState::None;
+            return Ok(false);
+        };
+        match action {
+            Action::Back => self.popup = PopupState::None,
+            Action::ScrollDown => self.link_cursor = (self.link_cursor + 1) % links.len(),
+            Action::ScrollUp => {
+                self.link_cursor = (self.link_cursor + links.len() - 1) % links.len();
+            }
+            Action::Select | Action::OpenInBrowser => {
+                self.popup = PopupState::None;
+                if let Err(err) = open::that(&link) {
+                    self.error_message = Some(format!("Failed to open {}: {}", link, err));
+                    self.popup = PopupState::Error;
+                }
+            }
+            Action::CopyLink => match copy_to_clipboard(&link) {
+                Ok(()) => {
+                    self.info_message = Some(format!("Copied {}", link));
+                    self.popup = PopupState::Info;
+                }
+                Err(err) => {
+                    self.error_message = Some(format!("Failed to copy {}: {}", link, err));
+                    self.popup = PopupState::Error;
+                }
+            },
+            Action::AddFeed if discovery::is_feed_url(&link) => {
+                self.input = link;
+                self.add_rss_feed();
+                self.popup = PopupState::None;
+            }
+            Action::AddFeed => {
+                self.info_message = Some(format!("{} isn't a feed", link));
+                self.popup = PopupState::Info;
+            }
+            _ => {}
+        }
+        Ok(false)
+    }
+
+    /// Handles input when the info popup is displayed.
+    fn handle_info_popup(&mut self, action: Action) -> Result<bool> {
+        match action {
+            Action::Back | Action::Select => {
+                self.info_message = None;
+                self.popup = PopupState::None;
+            }
             _ => {}
         }
         Ok(false)
     }
 
-    /// Handles input when the RSS entry help popup is displayed.
-    fn handle_rss_entry_help_popup(&mut self, key: KeyEvent) -> Result<bool> {
-        match key.code {
-            KeyCode::Esc | KeyCode::Char('q') => self.popup = PopupState::None,
+    /// Handles input when the sync report popup is displayed.
+    fn handle_sync_report_popup(&mut self, action: Action) -> Result<bool> {
+        match action {
+            Action::Back | Action::Select => {
+                self.popup = PopupState::None;
+            }
             _ => {}
         }
         Ok(false)
     }
 
+    /// Handles input when either help popup is displayed.
+    fn handle_help_popup(&mut self, action: Action) -> Result<bool> {
+        if action == Action::Back {
+            self.popup = PopupState::None;
+        }
+        Ok(false)
+    }
+
     /// Handles input for either the RSS feeds view or RSS entry view.
-    fn handle_default(&mut self, key: KeyEvent, rows: &[Row]) -> Result<bool> {
+    fn handle_default(&mut self, action: Action, rows: &[Row]) -> Result<bool> {
+        // Syncing can be cancelled from any view.
+        if action == Action::CancelSync && self.is_syncing() {
+            self.cancel_sync();
+            return Ok(false);
+        }
         match self.view_state {
-            ViewState::RssFeeds => self.handle_rss_feeds_view(key, rows),
-            ViewState::RssEntry {
-                rss_feed_index,
-                rss_entry_index,
-            } => self.handle_rss_entry_view(key, rss_feed_index, rss_entry_index),
+            ViewState::RssFeeds => self.handle_rss_feeds_view(action, rows),
+            ViewState::RssEntry { .. } => match self.open_rss_entry_indices() {
+                Some((rss_feed_index, rss_entry_index)) => {
+                    self.handle_rss_entry_view(action, rss_feed_index, rss_entry_index)
+                }
+                None => {
+                    self.close_rss_entry();
+                    Ok(false)
+                }
+            },
+            ViewState::SearchResults => self.handle_search_results_view(action),
         }
     }
 
     /// Handles input for the RSS feeds view. The RSS feeds view shows all RSS feeds
     /// and possibly their entries, if a feed is expanded.
-    fn handle_rss_feeds_view(&mut self, key: KeyEvent, rows: &[Row]) -> Result<bool> {
-        match key.code {
-            KeyCode::Char('s') => {
-                self.popup = PopupState::Syncing;
-                self.syncing = true;
-                self.sync();
-            }
-            KeyCode::Char('g') => {
-                if self.last_key == Some(KeyCode::Char('g')) {
-                    self.cursor = 0;
-                    self.last_key = None;
-                } else {
-                    self.last_key = Some(KeyCode::Char('g'));
-                }
-            }
-            KeyCode::Char('G') => {
-                self.last_key = Some(KeyCode::Char('G'));
-                self.cursor = rows.len() - 1;
+    fn handle_rss_feeds_view(&mut self, action: Action, rows: &[Row]) -> Result<bool> {
+        match action {
+            Action::Sync => {
+                self.report_sync = true;
+                self.sync((0..self.rss_feeds.len()).collect());
             }
-            KeyCode::Esc => {
-                self.last_key = Some(KeyCode::Esc);
-                return Ok(true);
-            }
-            KeyCode::Char('q') => {
-                self.last_key = Some(KeyCode::Char('q'));
-                return Ok(true);
-            }
-            KeyCode::Down => {
-                self.last_key = Some(KeyCode::Down);
-                if self.cursor + 1 < rows.len() {
-                    self.cursor += 1;
+            Action::Top => self.cursor = 0,
+            Action::Bottom => self.cursor = rows.len().saturating_sub(1),
+            Action::Quit => return Ok(true),
+            Action::ScrollDown if self.cursor + 1 < rows.len() => self.cursor += 1,
+            Action::ScrollUp if self.cursor > 0 => self.cursor -= 1,
+            Action::AddFeed => self.popup = PopupState::AddRssFeed,
+            Action::Help => self.popup = PopupState::RssFeedHelp,
+            Action::ImportOpml => self.popup = PopupState::ImportOpml,
+            Action::Search => self.popup = PopupState::Search,
+            Action::ExportOpml => self.popup = PopupState::ExportOpml,
+            Action::NewFolder => self.popup = PopupState::CreateFolder,
+            Action::RenameFolder => {
+                if let Some(Row::Folder(folder_index)) = rows.get(self.cursor) {
+                    self.input = self.folders[*folder_index].name.clone();
+                    self.character_index = self.input.chars().count();
+                    self.popup = PopupState::RenameFolder;
                 }
             }
-            KeyCode::Char('j') => {
-                self.last_key = Some(KeyCode::Char('j'));
-                if self.cursor + 1 < rows.len() {
-                    self.cursor += 1;
+            Action::MoveFeed => {
+                if let Some(Row::RssFeed(rss_feed_index) | Row::RssEntry(rss_feed_index, _)) =
+                    rows.get(self.cursor)
+                {
+                    self.folder_cursor = self
+                        .folder_index(&self.rss_feeds[*rss_feed_index])
+                        .map_or(0, |i| i + 1);
+                    self.popup = PopupState::MoveRssFeed;
                 }
             }
-            KeyCode::Up => {
-                self.last_key = Some(KeyCode::Up);
-                if self.cursor > 0 {
-                    self.cursor -= 1;
+            Action::ToggleFetchFullContent => {
+                if let Some(Row::RssFeed(rss_feed_index) | Row::RssEntry(rss_feed_index, _)) =
+                    rows.get(self.cursor)
+                {
+                    self.toggle_fetch_full_content(*rss_feed_index);
                 }
             }
-            KeyCode::Char('k') => {
-                self.last_key = Some(KeyCode::Char('k'));
-                if self.cursor > 0 {
-                    self.cursor -= 1;
+            Action::NextUnreadFeed => self.move_cursor_to_unread_rss_feed(rows, true),
+            Action::PreviousUnreadFeed => self.move_cursor_to_unread_rss_feed(rows, false),
+            Action::ToggleRead => {
+                if let Some(
+                    Row::RssEntry(rss_feed_index, rss_entry_index)
+                    | Row::VirtualFeedEntry(_, rss_feed_index, rss_entry_index),
+                ) = rows.get(self.cursor)
+                {
+                    self.toggle_read(*rss_feed_index, *rss_entry_index);
                 }
             }
-            KeyCode::Char('a') => {
-                self.last_key = Some(KeyCode::Char('a'));
-                self.popup = PopupState::AddRssFeed;
+            Action::MarkRead => {
+                if let Some(row) = rows.get(self.cursor) {
+                    self.mark_row_read(row);
+                }
             }
-            KeyCode::Char('h') => {
-                self.last_key = Some(KeyCode::Char('h'));
-                self.popup = PopupState::RssFeedHelp;
+            Action::MarkAllRead => self.mark_all_read(),
+            Action::MarkOlderRead => self.popup = PopupState::MarkOlderRead,
+            Action::ToggleStar => {
+                if let Some(
+                    Row::RssEntry(rss_feed_index, rss_entry_index)
+                    | Row::VirtualFeedEntry(_, rss_feed_index, rss_entry_index),
+                ) = rows.get(self.cursor)
+                {
+                    self.toggle_starred(*rss_feed_index, *rss_entry_index);
+                }
             }
-            KeyCode::Char('c') => {
-                self.last_key = Some(KeyCode::Char('c'));
-                match rows[self.cursor] {
-                    Row::RssFeed(rss_feed_index) => {
-                        self.rss_feeds[rss_feed_index].expanded = false;
-                        match self.storage.save_rss_feed(&self.rss_feeds[rss_feed_index]) {
-                            Ok(_) => {}
-                            Err(err) => {
-                                self.error_message = Some(err.to_string());
-                                self.popup = PopupState::Error;
-                            }
-                        }
+            Action::Collapse => match rows[self.cursor] {
+                Row::VirtualFeed(virtual_feed) => {
+                    self.expanded_virtual_feeds.remove(&virtual_feed);
+                }
+                Row::VirtualFeedEntry(virtual_feed, _, _) => {
+                    self.expanded_virtual_feeds.remove(&virtual_feed);
+                    if let Some(row) = rows
+                        .iter()
+                        .position(|r| *r == Row::VirtualFeed(virtual_feed))
+                    {
+                        self.cursor = row;
                     }
-                    Row::RssEntry(rss_feed_index, rss_entry_index) => {
-                        self.rss_feeds[rss_feed_index].expanded = false;
-                        self.cursor = self.cursor - rss_entry_index - 1;
-                        match self.storage.save_rss_feed(&self.rss_feeds[rss_feed_index]) {
-                            Ok(_) => {}
-                            Err(err) => {
-                                self.error_message = Some(err.to_string());
-                                self.popup = PopupState::Error;
-                            }
+                }
+                Row::Folder(folder_index) => self.set_folder_expanded(folder_index, false),
+                Row::RssFeed(rss_feed_index) => {
+                    self.rss_feeds[rss_feed_index].expanded = false;
+                    match self.storage.save_rss_feed(&self.rss_feeds[rss_feed_index]) {
+                        Ok(_) => {}
+                        Err(err) => {
+                            self.error_message = Some(err.to_string());
+                            self.popup = PopupState::Error;
                         }
                     }
                 }
-            }
-            KeyCode::Enter => {
-                self.last_key = Some(KeyCode::Enter);
-                if rows.len() > 0 {
-                    match rows[self.cursor] {
-                        Row::RssFeed(rss_feed_index) => {
-                            self.rss_feeds[rss_feed_index].expanded =
-                                !self.rss_feeds[rss_feed_index].expanded;
-                            match self.storage.save_rss_feed(&self.rss_feeds[rss_feed_index]) {
-                                Ok(_) => {}
-                                Err(err) => {
-                                    self.error_message = Some(err.to_string());
-                                    self.popup = PopupState::Error;
-                                }
-                            }
-                        }
-                        Row::RssEntry(rss_feed_index, rss_entry_index) => {
-                            self.rss_entry_scroll = 0;
-                            self.rss_feeds[rss_feed_index].rss_entries[rss_entry_index].read = true;
-                            match self.storage.save_rss_entry(
-                                &self.rss_feeds[rss_feed_index].id,
-                                &self.rss_feeds[rss_feed_index].rss_entries[rss_entry_index],
-                            ) {
-                                Ok(_) => {}
-                                Err(err) => {
-                                    self.error_message = Some(err.to_string());
-                                    self.popup = PopupState::Error;
-                                }
-                            }
-                            self.view_state = ViewState::RssEntry {
-                                rss_feed_index,
-                                rss_entry_index,
-                            };
+                Row::RssEntry(rss_feed_index, rss_entry_index) => {
+                    self.rss_feeds[rss_feed_index].expanded = false;
+                    self.cursor = self.cursor - rss_entry_index - 1;
+                    match self.storage.save_rss_feed(&self.rss_feeds[rss_feed_index]) {
+                        Ok(_) => {}
+                        Err(err) => {
+                            self.error_message = Some(err.to_string());
+                            self.popup = PopupState::Error;
                         }
                     }
                 }
-            }
-            KeyCode::Char('u') => {
-                if key.modifiers.contains(KeyModifiers::CONTROL) {
-                    let half_page = (self.last_frame_area.height as usize - 2) / 2;
-                    if self.cursor.saturating_sub(half_page) == 0 {
-                        self.cursor = 0;
-                    } else {
-                        self.cursor = self.cursor.saturating_sub(half_page);
+            },
+            Action::Select if !rows.is_empty() => match rows[self.cursor] {
+                Row::VirtualFeed(virtual_feed) => {
+                    if !self.expanded_virtual_feeds.remove(&virtual_feed) {
+                        self.expanded_virtual_feeds.insert(virtual_feed);
+                    }
+                }
+                Row::VirtualFeedEntry(_, rss_feed_index, rss_entry_index) => {
+                    self.open_rss_entry(rss_feed_index, rss_entry_index);
+                }
+                Row::Folder(folder_index) => {
+                    let expanded = !self.folders[folder_index].expanded;
+                    self.set_folder_expanded(folder_index, expanded);
+                }
+                Row::RssFeed(rss_feed_index) => {
+                    self.rss_feeds[rss_feed_index].expanded =
+                        !self.rss_feeds[rss_feed_index].expanded;
+                    match self.storage.save_rss_feed(&self.rss_feeds[rss_feed_index]) {
+                        Ok(_) => {}
+                        Err(err) => {
+                            self.error_message = Some(err.to_string());
+                            self.popup = PopupState::Error;
+                        }
                     }
+                }
+                Row::RssEntry(rss_feed_index, rss_entry_index) => {
+                    self.open_rss_entry(rss_feed_index, rss_entry_index);
+                }
+            },
+            Action::HalfPageUp => {
+                let half_page = (self.last_frame_area.height as usize - 2) / 2;
+                if self.cursor.saturating_sub(half_page) == 0 {
+                    self.cursor = 0;
                 } else {
-                    self.last_key = Some(KeyCode::Char('u'));
+                    self.cursor = self.cursor.saturating_sub(half_page);
                 }
             }
-            KeyCode::Char('d') => {
-                if key.modifiers.contains(KeyModifiers::CONTROL) {
-                    let half_page = (self.last_frame_area.height as usize - 2) / 2;
-                    if self.cursor + half_page >= rows.len() {
-                        self.cursor = rows.len() - 1;
-                    } else {
-                        self.cursor += half_page;
-                    }
+            Action::HalfPageDown => {
+                let half_page = (self.last_frame_area.height as usize - 2) / 2;
+                if self.cursor + half_page >= rows.len() {
+                    self.cursor = rows.len() - 1;
                 } else {
-                    self.last_key = Some(KeyCode::Char('d'));
-                    match rows[self.cursor] {
-                        Row::RssFeed(_) => {
-                            self.popup = PopupState::ConfirmDeleteRssFeed;
-                        }
-                        Row::RssEntry(_, _) => {
-                            self.popup = PopupState::ConfirmDeleteRssFeed;
-                        }
-                    }
+                    self.cursor += half_page;
+                }
+            }
+            Action::Delete => {
+                if let Some(Row::Folder(_) | Row::RssFeed(_) | Row::RssEntry(..)) =
+                    rows.get(self.cursor)
+                {
+                    self.popup = PopupState::ConfirmDeleteRssFeed;
                 }
             }
             _ => {}
@@ -707,35 +1956,41 @@ impl App {
     /// of an entry.
     fn handle_rss_entry_view(
         &mut self,
-        key: KeyEvent,
+        action: Action,
         rss_feed_index: usize,
         rss_entry_index: usize,
     ) -> Result<bool> {
-        match key.code {
-            KeyCode::Char('o') => {
-                self.last_key = Some(KeyCode::Char('o'));
-                open::that(
-                    self.rss_feeds[rss_feed_index].rss_entries[rss_entry_index]
-                        .link
-                        .clone(),
-                )?;
-            }
-            KeyCode::Char('f') => {
-                self.last_key = Some(KeyCode::Char('f'));
-                self.fetch_full_rss_entry_content(rss_feed_index, rss_entry_index);
-            }
-            KeyCode::Esc | KeyCode::Char('q') => {
-                self.last_key = Some(KeyCode::Char('q'));
-                self.view_state = ViewState::RssFeeds;
-            }
-            KeyCode::Up | KeyCode::Char('k') => {
-                self.last_key = Some(KeyCode::Char('k'));
-                if self.rss_entry_scroll > 0 {
-                    self.rss_entry_scroll -= 1;
+        match action {
+            Action::OpenInBrowser => {
+                let link = &self.rss_feeds[rss_feed_index].rss_entries[rss_entry_index].link;
+                if let Err(err) = open::that(link) {
+                    self.error_message = Some(format!("Failed to open {}: {}", link, err));
+                    self.popup = PopupState::Error;
                 }
             }
-            KeyCode::Down | KeyCode::Char('j') => {
-                self.last_key = Some(KeyCode::Char('j'));
+            Action::FetchFullContent => {
+                self.fetch_full_rss_entries_content(rss_feed_index, vec![rss_entry_index], true);
+            }
+            Action::ToggleFullContent => self.toggle_full_content(rss_feed_index, rss_entry_index),
+            Action::Links if self.rendered_content.links().is_empty() => {
+                self.info_message = Some("This entry has no links".to_string());
+                self.popup = PopupState::Info;
+            }
+            Action::Links => {
+                self.link_cursor = 0;
+                self.popup = PopupState::Links;
+            }
+            Action::ToggleStar => self.toggle_starred(rss_feed_index, rss_entry_index),
+            Action::ToggleRead => self.toggle_read(rss_feed_index, rss_entry_index),
+            Action::NextUnread => {
+                self.open_unread_rss_entry(rss_feed_index, rss_entry_index, true);
+            }
+            Action::PreviousUnread => {
+                self.open_unread_rss_entry(rss_feed_index, rss_entry_index, false);
+            }
+            Action::Back => self.close_rss_entry(),
+            Action::ScrollUp if self.rss_entry_scroll > 0 => self.rss_entry_scroll -= 1,
+            Action::ScrollDown => {
                 let area = self.last_frame_area;
                 let max_rss_entry_scroll =
                     self.get_max_rss_entry_scroll(rss_feed_index, rss_entry_index, area.height);
@@ -743,80 +1998,275 @@ impl App {
                     self.rss_entry_scroll += 1;
                 }
             }
-            KeyCode::Char('h') => {
-                self.last_key = Some(KeyCode::Char('h'));
-                self.popup = PopupState::RssEntryHelp;
-            }
-            KeyCode::Char('g') => {
-                if self.last_key == Some(KeyCode::Char('g')) {
-                    self.rss_entry_scroll = 0;
-                    self.last_key = None;
-                } else {
-                    self.last_key = Some(KeyCode::Char('g'));
-                }
-            }
-            KeyCode::End | KeyCode::Char('G') => {
-                self.last_key = Some(KeyCode::Char('G'));
+            Action::Help => self.popup = PopupState::RssEntryHelp,
+            Action::Top => self.rss_entry_scroll = 0,
+            Action::Bottom => {
                 let area = self.last_frame_area;
                 let max_scroll =
                     self.get_max_rss_entry_scroll(rss_feed_index, rss_entry_index, area.height);
                 self.rss_entry_scroll = max_scroll;
             }
-            KeyCode::Char('u') => {
-                if key.modifiers.contains(KeyModifiers::CONTROL) {
-                    let half_page = (self.last_frame_area.height - 2) / 2;
-                    if self.rss_entry_scroll.saturating_sub(half_page) == 0 {
-                        self.rss_entry_scroll = 0;
-                    } else {
-                        self.rss_entry_scroll = self.rss_entry_scroll.saturating_sub(half_page);
-                    }
+            Action::HalfPageUp => {
+                let half_page = (self.last_frame_area.height - 2) / 2;
+                if self.rss_entry_scroll.saturating_sub(half_page) == 0 {
+                    self.rss_entry_scroll = 0;
                 } else {
-                    self.last_key = Some(KeyCode::Char('u'));
+                    self.rss_entry_scroll = self.rss_entry_scroll.saturating_sub(half_page);
                 }
             }
-            KeyCode::Char('d') => {
+            Action::HalfPageDown => {
                 let rss_entry = &self.rss_feeds[rss_feed_index].rss_entries[rss_entry_index];
-                if key.modifiers.contains(KeyModifiers::CONTROL) {
-                    let frame_height = self.last_frame_area.height;
-                    let half_page = (self.last_frame_area.height - 2) / 2;
-                    if self.rss_entry_scroll + half_page
-                        >= (rss_entry.content_total_lines as u16).saturating_sub(frame_height)
-                    {
-                        self.rss_entry_scroll =
-                            ((rss_entry.content_total_lines) as u16).saturating_sub(frame_height);
-                    } else {
-                        self.rss_entry_scroll += half_page;
-                    }
+                let frame_height = self.last_frame_area.height;
+                let half_page = (self.last_frame_area.height - 2) / 2;
+                if self.rss_entry_scroll + half_page
+                    >= (rss_entry.content_total_lines as u16).saturating_sub(frame_height)
+                {
+                    self.rss_entry_scroll =
+                        ((rss_entry.content_total_lines) as u16).saturating_sub(frame_height);
                 } else {
-                    self.last_key = Some(KeyCode::Char('d'));
+                    self.rss_entry_scroll += half_page;
                 }
             }
             _ => {}
         }
         Ok(false)
     }
-}
 
-/// Updates a `Vec<RssFeeds>`, adding newer RSS entries.
-async fn sync_feeds(mut rss_feeds: Vec<RssFeed>) -> Result<Vec<RssFeed>> {
-    let client = reqwest::Client::new();
-    for rss_feed in rss_feeds.iter_mut() {
-        let newest_date: DateTime<Utc> = rss_feed
-            .rss_entries
-            .first()
-            .map(|e| e.published)
-            .unwrap_or(DateTime::<Utc>::MIN_UTC);
-        let response_text = client.get(&rss_feed.link).send().await?.text().await?;
-        let updated_feed = feed_rs::parser::parse(response_text.as_bytes())?;
-        for entry in updated_feed.entries {
-            if entry.published.unwrap_or(DateTime::<Utc>::MIN_UTC) > newest_date {
-                let rss_entry = RssEntry::from(entry);
-                rss_feed.rss_entries.push(rss_entry)
+    /// Handles input for the search results view, which lists the entries
+    /// matching a search.
+    fn handle_search_results_view(&mut self, action: Action) -> Result<bool> {
+        match action {
+            Action::Back => {
+                self.search_query = None;
+                self.search_results.clear();
+                self.search_cursor = 0;
+                self.view_state = ViewState::RssFeeds;
+            }
+            Action::ScrollDown if self.search_cursor + 1 < self.search_results.len() => {
+                self.search_cursor += 1;
+            }
+            Action::ScrollUp => {
+                self.search_cursor = self.search_cursor.saturating_sub(1);
+            }
+            Action::Search => self.popup = PopupState::Search,
+            Action::Select => {
+                let Some(search_result) = self.search_results.get(self.search_cursor) else {
+                    return Ok(false);
+                };
+                match self
+                    .rss_entry_indices(&search_result.rss_feed_id, &search_result.rss_entry_id)
+                {
+                    Some((rss_feed_index, rss_entry_index)) => {
+                        self.open_rss_entry(rss_feed_index, rss_entry_index)
+                    }
+                    None => {
+                        self.error_message = Some("entry no longer exists".to_string());
+                        self.popup = PopupState::Error;
+                    }
+                }
             }
+            _ => {}
+        }
+        Ok(false)
+    }
+}
+
+/// Updates a `Vec<RssFeeds>`, adding newer RSS entries. Each feed is
+/// synced independently, so a feed that fails to sync does not prevent
+/// other feeds from syncing. Feeds are fetched concurrently within the
+/// limits of `sync_options`. An `AppEvent::RssFeedSyncStarted` is sent as
+/// each feed starts being fetched and an `AppEvent::RssFeedSynced` as each
+/// feed finishes. Once `cancellation` is cancelled, feeds that haven't
+/// finished fail to sync.
+pub async fn sync_feeds(
+    rss_feeds: Vec<RssFeed>,
+    sync_options: SyncOptions,
+    sender: &mpsc::UnboundedSender<AppEvent>,
+    cancellation: CancellationToken,
+) {
+    let client = sync_options.http_client();
+    let semaphore = Arc::new(Semaphore::new(sync_options.max_concurrent_feeds.max(1)));
+    let mut host_semaphores: HashMap<String, Arc<Semaphore>> = HashMap::new();
+    let mut join_set = JoinSet::new();
+
+    for rss_feed in rss_feeds {
+        let host = reqwest::Url::parse(&rss_feed.link)
+            .ok()
+            .and_then(|url| url.host_str().map(|host| host.to_string()))
+            .unwrap_or_default();
+        let host_semaphore = host_semaphores
+            .entry(host)
+            .or_insert_with(|| {
+                Arc::new(Semaphore::new(
+                    sync_options.max_concurrent_feeds_per_host.max(1),
+                ))
+            })
+            .clone();
+        let semaphore = semaphore.clone();
+        let client = client.clone();
+        let sender = sender.clone();
+        let cancellation = cancellation.clone();
+        join_set.spawn(async move {
+            let sync = async {
+                // The host permit is acquired first so that feeds waiting on
+                // a busy host don't occupy one of the global permits.
+                let _host_permit = host_semaphore.acquire_owned().await;
+                let _permit = semaphore.acquire_owned().await;
+                let _ = sender.send(AppEvent::RssFeedSyncStarted(rss_feed.id.clone()));
+                sync_rss_feed(&client, &rss_feed).await
+            };
+            let (fetched_rss_feed, sync_result) = tokio::select! {
+                synced = sync => synced,
+                _ = cancellation.cancelled() => {
+                    let mut sync_result = RssFeedSyncResult::new(&rss_feed);
+                    sync_result.error = Some("cancelled".to_string());
+                    (None, sync_result)
+                }
+            };
+            let _ = sender.send(AppEvent::RssFeedSynced(
+                fetched_rss_feed.map(Box::new),
+                sync_result,
+            ));
+        });
+    }
+
+    while join_set.join_next().await.is_some() {}
+}
+
+/// Fetches a single RSS feed. There are no fetched contents if the feed
+/// fails to sync.
+async fn sync_rss_feed(
+    client: &reqwest::Client,
+    rss_feed: &RssFeed,
+) -> (Option<FetchedRssFeed>, RssFeedSyncResult) {
+    let mut sync_result = RssFeedSyncResult::new(rss_feed);
+
+    // Conditional request headers let the server skip sending a feed
+    // that hasn't changed since it was last fetched.
+    let mut request = client.get(&rss_feed.link);
+    if let Some(etag) = &rss_feed.etag {
+        request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+    }
+    if let Some(last_modified) = &rss_feed.last_modified {
+        request = request.header(reqwest::header::IF_MODIFIED_SINCE, last_modified);
+    }
+    let response = match request.send().await {
+        Ok(response) => response,
+        Err(err) => {
+            sync_result.error = Some(format!("request failed: {}", err));
+            return (None, sync_result);
+        }
+    };
+    let status = response.status();
+    sync_result.status = Some(status.as_u16());
+    let header_value = |name: reqwest::header::HeaderName| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_string())
+    };
+    let cache_control = header_value(reqwest::header::CACHE_CONTROL);
+    if status == reqwest::StatusCode::NOT_MODIFIED {
+        // An unchanged feed still advertises the interval it did before,
+        // unless its response says otherwise.
+        let fetched_rss_feed = FetchedRssFeed {
+            rss_entries: Vec::new(),
+            etag: rss_feed.etag.clone(),
+            last_modified: rss_feed.last_modified.clone(),
+            last_fetched: Utc::now(),
+            refresh_interval: cache_control
+                .as_deref()
+                .and_then(refresh::cache_control_max_age)
+                .or(rss_feed.refresh_interval),
+        };
+        return (Some(fetched_rss_feed), sync_result);
+    }
+    if !status.is_success() {
+        sync_result.error = Some(format!("unexpected HTTP status {}", status));
+        return (None, sync_result);
+    }
+
+    let etag = header_value(reqwest::header::ETAG);
+    let last_modified = header_value(reqwest::header::LAST_MODIFIED);
+    let response_text = match response.text().await {
+        Ok(response_text) => response_text,
+        Err(err) => {
+            sync_result.error = Some(format!("failed to read response: {}", err));
+            return (None, sync_result);
         }
-        rss_feed.rss_entries.sort_by_key(|e| Reverse(e.published));
+    };
+    let updated_feed = match feed_rs::parser::parse(response_text.as_bytes()) {
+        Ok(updated_feed) => updated_feed,
+        Err(err) => {
+            sync_result.error = Some(format!("failed to parse feed: {}", err));
+            return (None, sync_result);
+        }
+    };
+
+    let fetched_rss_feed = FetchedRssFeed {
+        refresh_interval: refresh::advertised_refresh_interval(
+            updated_feed.ttl,
+            &response_text,
+            cache_control.as_deref(),
+        ),
+        rss_entries: updated_feed
+            .entries
+            .into_iter()
+            .map(RssEntry::from)
+            .collect(),
+        etag,
+        last_modified,
+        last_fetched: Utc::now(),
+    };
+    (Some(fetched_rss_feed), sync_result)
+}
+
+/// Fetches an entry's full content from its web page, keeping only the
+/// page's main article when one is found. Error pages are failures rather
+/// than content.
+pub async fn fetch_full_content(client: &reqwest::Client, link: &str) -> Result<String, String> {
+    let html = client
+        .get(link)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| format!("Failed to load full content: {}", e))?
+        .text()
+        .await
+        .map_err(|e| format!("Failed to load full content: {}", e))?;
+    Ok(extract_article(&html).unwrap_or(html))
+}
+
+/// Merges a synced feed's fetched contents into the feed and saves it,
+/// recording the number of new and updated entries in its sync result.
+pub fn save_synced_rss_feed(
+    storage: &mut LocalStorage,
+    rss_feed: &mut RssFeed,
+    fetched_rss_feed: FetchedRssFeed,
+    sync_result: &mut RssFeedSyncResult,
+) {
+    let retention_policy = rss_feed.retention_override.or(storage.retention_policy);
+    let (num_new, num_updated) =
+        rss_feed.merge_rss_entries(fetched_rss_feed.rss_entries, retention_policy);
+    sync_result.new_rss_entries = num_new;
+    sync_result.updated_rss_entries = num_updated;
+    rss_feed.etag = fetched_rss_feed.etag;
+    rss_feed.last_modified = fetched_rss_feed.last_modified;
+    rss_feed.last_fetched = Some(fetched_rss_feed.last_fetched);
+    rss_feed.refresh_interval = fetched_rss_feed.refresh_interval;
+    if let Err(err) = storage.save_rss_feed(rss_feed) {
+        sync_result.error = Some(format!("failed to save feed: {}", err));
+    }
+}
+
+/// Retrieves the path of the database file, within `db_path` if given
+/// and within the default data directory otherwise.
+pub fn get_db_path(db_path: Option<PathBuf>) -> Result<PathBuf> {
+    match db_path {
+        Some(path) => Ok(path.join("rss.db")),
+        None => get_default_db_path(),
     }
-    Ok(rss_feeds)
 }
 
 fn get_default_db_path() -> Result<PathBuf, anyhow::Error> {
@@ -828,27 +2278,182 @@ fn get_default_db_path() -> Result<PathBuf, anyhow::Error> {
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
+    use std::sync::atomic::Ordering;
     use std::{str::FromStr, time::Duration};
 
     use super::*;
     use crate::tui::PopupState;
     use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
     use tempfile::tempdir;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
     use tokio::time::timeout;
 
-    /// Tests navigating the RSS feeds view, opening an RSS entry,
-    /// and quitting.
-    #[tokio::test]
-    async fn test_open_rss_entry() {
-        let temp_dir = tempdir().unwrap();
-        let db_path = PathBuf::from_str(temp_dir.path().to_str().unwrap()).unwrap();
-        let (sender, _) = mpsc::unbounded_channel();
-        let rows: Vec<Row> = vec![Row::RssFeed(0), Row::RssEntry(0, 0)];
-        let mut app = App::new(sender, Some(db_path), None).unwrap();
-        // Last frame area will affect the outcome of attempting to scroll.
-        // If this is left as its default, each 'j' key press will scroll
-        // downwards, when, in this test, the entry content is very small.
+    const TEST_RSS: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<rss version="2.0">
+  <channel>
+    <title>Test feed</title>
+    <link>https://example.com</link>
+    <description>A test feed.</description>
+    <item>
+      <guid>https://example.com/first</guid>
+      <title>First entry</title>
+      <link>https://example.com/first</link>
+      <description>First content.</description>
+      <pubDate>Mon, 05 Jan 2026 10:00:00 GMT</pubDate>
+    </item>
+    <item>
+      <guid>https://example.com/second</guid>
+      <title>Second entry</title>
+      <link>https://example.com/second</link>
+      <description>Second content.</description>
+      <pubDate>Tue, 06 Jan 2026 10:00:00 GMT</pubDate>
+    </item>
+  </channel>
+</rss>"#;
+
+    /// Serves HTTP on a local port and returns the server's base URL.
+    /// The handler receives each raw request and returns a raw response.
+    pub(crate) async fn serve_with(
+        handler: impl Fn(&str) -> String + Send + Sync + 'static,
+    ) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let handler = Arc::new(handler);
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let handler = handler.clone();
+                tokio::spawn(async move {
+                    let mut buffer = vec![0; 4096];
+                    let n = stream.read(&mut buffer).await.unwrap_or(0);
+                    let request = String::from_utf8_lossy(&buffer[..n]);
+                    let response = handler(&request);
+                    let _ = stream.write_all(response.as_bytes()).await;
+                });
+            }
+        });
+        format!("http://{}", address)
+    }
+
+    /// Serves canned responses over HTTP on a local port and returns the
+    /// server's base URL. Each route is a path, a status code, and a body.
+    async fn serve(routes: Vec<(&'static str, u16, &'static str)>) -> String {
+        serve_with(move |request| {
+            let path = request.split_whitespace().nth(1).unwrap_or("/");
+            let (status, body) = routes
+                .iter()
+                .find(|(route, _, _)| *route == path)
+                .map(|(_, status, body)| (*status, *body))
+                .unwrap_or((404, ""));
+            http_response(status, "", body)
+        })
+        .await
+    }
+
+    /// Serves a website on a local port and returns the server's base URL.
+    /// The website's home page is `html`, and each feed is a path and a
+    /// body.
+    async fn serve_website(html: &'static str, feeds: Vec<(&'static str, &'static str)>) -> String {
+        serve_with(move |request| {
+            let path = request.split_whitespace().nth(1).unwrap_or("/");
+            if path == "/" {
+                return http_response(200, "Content-Type: text/html\r\n", html);
+            }
+            match feeds.iter().find(|(route, _)| *route == path) {
+                Some((_, body)) => {
+                    http_response(200, "Content-Type: application/rss+xml\r\n", body)
+                }
+                None => http_response(404, "Content-Type: text/html\r\n", "<html></html>"),
+            }
+        })
+        .await
+    }
+
+    /// Adds a feed through the add feed popup and waits for the result.
+    async fn add_rss_feed(
+        app: &mut App,
+        receiver: &mut mpsc::UnboundedReceiver<AppEvent>,
+        url: &str,
+    ) -> AppEvent {
+        app.handle_key(KeyEvent::new(KeyCode::Char('a'), KeyModifiers::NONE), &[])
+            .unwrap();
+        app.input = url.to_string();
+        app.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE), &[])
+            .unwrap();
+        timeout(Duration::from_secs(2), receiver.recv())
+            .await
+            .expect("timed out waiting for AppEvent")
+            .expect("channel closed")
+    }
+
+    /// Formats a raw HTTP response. Each header must end with `\r\n`.
+    pub(crate) fn http_response(status: u16, headers: &str, body: &str) -> String {
+        format!(
+            "HTTP/1.1 {} Test\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            headers,
+            body.len(),
+            body
+        )
+    }
+
+    /// Collects the feeds that finished syncing, skipping other events.
+    fn synced_rss_feeds(
+        receiver: &mut mpsc::UnboundedReceiver<AppEvent>,
+    ) -> Vec<(Option<Box<FetchedRssFeed>>, RssFeedSyncResult)> {
+        let mut synced = Vec::new();
+        while let Ok(app_event) = receiver.try_recv() {
+            if let AppEvent::RssFeedSynced(fetched_rss_feed, sync_result) = app_event {
+                synced.push((fetched_rss_feed, sync_result));
+            }
+        }
+        synced
+    }
+
+    /// Creates an RSS feed with no entries.
+    fn test_rss_feed(id: &str, link: &str) -> RssFeed {
+        RssFeed {
+            id: id.to_string(),
+            title: format!("{} title", id),
+            link: link.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Creates an RSS entry published now.
+    fn test_rss_entry(id: &str, link: &str) -> RssEntry {
+        let title = format!("{} title", id);
+        let content = format!("{} content", id);
+        RssEntry {
+            id: id.to_string(),
+            content_hash: content_hash(&title, &content),
+            title,
+            content,
+            link: link.to_string(),
+            published: Utc::now(),
+            ..Default::default()
+        }
+    }
+
+    /// Tests navigating the RSS feeds view, opening an RSS entry,
+    /// and quitting.
+    #[tokio::test]
+    async fn test_open_rss_entry() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = PathBuf::from_str(temp_dir.path().to_str().unwrap()).unwrap();
+        let (sender, _) = mpsc::unbounded_channel();
+        let rows: Vec<Row> = vec![Row::RssFeed(0), Row::RssEntry(0, 0)];
+        let mut app = App::new(
+            sender,
+            Some(db_path),
+            RetentionPolicy::default(),
+            SyncOptions::default(),
+        )
+        .unwrap();
+        // Last frame area will affect the outcome of attempting to scroll.
+        // If this is left as its default, each 'j' key press will scroll
+        // downwards, when, in this test, the entry content is very small.
         app.last_frame_area = Rect {
             x: 0,
             y: 0,
@@ -867,9 +2472,11 @@ mod tests {
                 content_total_lines: 1,
                 read: false,
                 link: "https://example.com".to_string(),
+                ..Default::default()
             }],
             expanded: false,
             link: "https://example.com".to_string(),
+            ..Default::default()
         }];
 
         // Expand RSS feed.
@@ -894,13 +2501,7 @@ mod tests {
             .unwrap();
         app.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE), &rows)
             .unwrap();
-        assert!(
-            app.view_state
-                == ViewState::RssEntry {
-                    rss_feed_index: 0,
-                    rss_entry_index: 0
-                }
-        );
+        assert!(app.open_rss_entry_indices() == Some((0, 0)));
         assert!(
             app.rss_feeds
                 .first()
@@ -924,6 +2525,512 @@ mod tests {
         assert!(quit_result);
     }
 
+    /// Tests creating a folder, moving a feed into it, renaming it and
+    /// deleting it, with the changes saved.
+    #[tokio::test]
+    async fn test_folder_keys() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = PathBuf::from_str(temp_dir.path().to_str().unwrap()).unwrap();
+        let (sender, _) = mpsc::unbounded_channel();
+        let mut app = App::new(
+            sender,
+            Some(db_path),
+            RetentionPolicy::default(),
+            SyncOptions::default(),
+        )
+        .unwrap();
+        let rss_feed = test_rss_feed("feed", "https://example.com/feed.xml");
+        app.storage.save_rss_feed(&rss_feed).unwrap();
+        app.rss_feeds = vec![rss_feed];
+        let virtual_rows: Vec<Row> = VirtualFeed::ALL.into_iter().map(Row::VirtualFeed).collect();
+        let press = |app: &mut App, code: KeyCode| {
+            let rows = get_rows(app);
+            app.handle_key(KeyEvent::new(code, KeyModifiers::NONE), &rows)
+                .unwrap();
+        };
+
+        press(&mut app, KeyCode::Char('n'));
+        assert!(app.popup == PopupState::CreateFolder);
+        // Characters bound in other modes, like q, are typed.
+        for c in "Quick news".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        assert!(app.popup == PopupState::CreateFolder);
+        press(&mut app, KeyCode::Enter);
+        assert!(app.folders.len() == 1);
+        assert!(get_rows(&app)[3..] == [Row::Folder(0), Row::RssFeed(0)]);
+        assert!(get_rows(&app)[..3] == virtual_rows[..]);
+        assert!(app.cursor == 3);
+
+        app.cursor = 4;
+        press(&mut app, KeyCode::Char('m'));
+        assert!(app.popup == PopupState::MoveRssFeed);
+        press(&mut app, KeyCode::Char('j'));
+        press(&mut app, KeyCode::Enter);
+        assert!(app.rss_feeds[0].folder.as_deref() == Some("Quick news"));
+        assert!(app.folders[0].expanded);
+        assert!(app.cursor == 4);
+
+        app.cursor = 3;
+        press(&mut app, KeyCode::Char('r'));
+        assert!(app.popup == PopupState::RenameFolder);
+        assert!(app.input == "Quick news");
+        app.input = "Reading".to_string();
+        press(&mut app, KeyCode::Enter);
+        assert!(app.rss_feeds[0].folder.as_deref() == Some("Reading"));
+        let saved_rss_feeds = app.storage.load_rss_feeds().unwrap();
+        assert!(saved_rss_feeds[0].folder.as_deref() == Some("Reading"));
+        assert!(app.storage.load_folders().unwrap()[0].expanded);
+
+        press(&mut app, KeyCode::Char('d'));
+        press(&mut app, KeyCode::Char('y'));
+        assert!(app.folders.is_empty());
+        assert!(app.rss_feeds[0].folder.is_none());
+        assert!(app.storage.load_rss_feeds().unwrap().len() == 1);
+        assert!(get_rows(&app)[3..] == [Row::RssFeed(0)]);
+    }
+
+    /// Tests that the All Unread virtual feed lists unread entries across
+    /// feeds, newest first, and opens the entry in its own feed.
+    #[tokio::test]
+    async fn test_virtual_feed_opens_real_rss_entry() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = PathBuf::from_str(temp_dir.path().to_str().unwrap()).unwrap();
+        let (sender, _) = mpsc::unbounded_channel();
+        let mut app = App::new(
+            sender,
+            Some(db_path),
+            RetentionPolicy::default(),
+            SyncOptions::default(),
+        )
+        .unwrap();
+        let mut older = test_rss_feed("older", "https://example.com/older.xml");
+        let mut older_entry = test_rss_entry("older-entry", "https://example.com/older");
+        older_entry.published = Utc::now() - chrono::Duration::days(2);
+        let mut read_entry = test_rss_entry("read-entry", "https://example.com/read");
+        read_entry.read = true;
+        older.rss_entries = vec![read_entry, older_entry];
+        let mut newer = test_rss_feed("newer", "https://example.com/newer.xml");
+        newer.rss_entries = vec![test_rss_entry("newer-entry", "https://example.com/newer")];
+        for rss_feed in [&older, &newer] {
+            app.storage.save_rss_feed(rss_feed).unwrap();
+        }
+        app.rss_feeds = vec![older, newer];
+        let press = |app: &mut App, code: KeyCode| {
+            let rows = get_rows(app);
+            app.handle_key(KeyEvent::new(code, KeyModifiers::NONE), &rows)
+                .unwrap();
+        };
+
+        press(&mut app, KeyCode::Enter);
+        assert!(
+            get_rows(&app)[..3]
+                == [
+                    Row::VirtualFeed(VirtualFeed::AllUnread),
+                    Row::VirtualFeedEntry(VirtualFeed::AllUnread, 1, 0),
+                    Row::VirtualFeedEntry(VirtualFeed::AllUnread, 0, 1),
+                ]
+        );
+
+        app.cursor = 2;
+        press(&mut app, KeyCode::Enter);
+        assert!(app.open_rss_entry_indices() == Some((0, 1)));
+        assert!(app.rss_feeds[0].rss_entries[1].read);
+    }
+
+    /// Tests toggling an entry's read flag and marking a feed, older
+    /// entries and then every entry as read.
+    #[tokio::test]
+    async fn test_mark_read_keys() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = PathBuf::from_str(temp_dir.path().to_str().unwrap()).unwrap();
+        let (sender, _) = mpsc::unbounded_channel();
+        let mut app = App::new(
+            sender,
+            Some(db_path),
+            RetentionPolicy::default(),
+            SyncOptions::default(),
+        )
+        .unwrap();
+        let mut first = test_rss_feed("first", "https://example.com/first.xml");
+        first.expanded = true;
+        first.rss_entries = vec![
+            test_rss_entry("first-new", "https://example.com/first-new"),
+            test_rss_entry("first-newer", "https://example.com/first-newer"),
+        ];
+        let mut second = test_rss_feed("second", "https://example.com/second.xml");
+        let mut old_rss_entry = test_rss_entry("second-old", "https://example.com/second-old");
+        old_rss_entry.published = Utc::now() - chrono::Duration::days(10);
+        second.rss_entries = vec![
+            test_rss_entry("second-new", "https://example.com/second-new"),
+            old_rss_entry,
+        ];
+        for rss_feed in [&first, &second] {
+            app.storage.save_rss_feed(rss_feed).unwrap();
+        }
+        app.rss_feeds = vec![first, second];
+        let press = |app: &mut App, code: KeyCode| {
+            let rows = get_rows(app);
+            app.handle_key(KeyEvent::new(code, KeyModifiers::NONE), &rows)
+                .unwrap();
+        };
+        let saved_read = |app: &App| -> Vec<Vec<bool>> {
+            app.storage
+                .load_rss_feeds()
+                .unwrap()
+                .iter()
+                .map(|f| f.rss_entries.iter().map(|e| e.read).collect())
+                .collect()
+        };
+
+        app.cursor = 4;
+        assert!(get_rows(&app)[4] == Row::RssEntry(0, 0));
+        press(&mut app, KeyCode::Char('t'));
+        assert!(app.rss_feeds[0].rss_entries[0].read);
+        press(&mut app, KeyCode::Char('t'));
+        assert!(!app.rss_feeds[0].rss_entries[0].read);
+
+        press(&mut app, KeyCode::Char('A'));
+        assert!(saved_read(&app) == vec![vec![true, true], vec![false, false]]);
+
+        press(&mut app, KeyCode::Char('O'));
+        assert!(app.popup == PopupState::MarkOlderRead);
+        app.input = "5".to_string();
+        press(&mut app, KeyCode::Enter);
+        assert!(app.rss_feeds[1].rss_entries[1].read);
+        assert!(!app.rss_feeds[1].rss_entries[0].read);
+
+        press(&mut app, KeyCode::Char('C'));
+        assert!(saved_read(&app) == vec![vec![true, true], vec![true, true]]);
+        assert!(
+            app.rss_feeds
+                .iter()
+                .all(|f| f.rss_entries.iter().all(|e| e.read))
+        );
+    }
+
+    /// Tests cycling through the links of an entry, and adding only links
+    /// to feeds as feeds.
+    #[tokio::test]
+    async fn test_links_popup() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = PathBuf::from_str(temp_dir.path().to_str().unwrap()).unwrap();
+        let (sender, _) = mpsc::unbounded_channel();
+        let mut app = App::new(
+            sender,
+            Some(db_path),
+            RetentionPolicy::default(),
+            SyncOptions::default(),
+        )
+        .unwrap();
+        let mut rss_feed = test_rss_feed("feed", "https://example.com/feed.xml");
+        let mut rss_entry = test_rss_entry("entry", "https://example.com/posts/1");
+        rss_entry.content_html =
+            r#"<a href="/about">About</a> <a href="/other/feed.xml">Feed</a>"#.to_string();
+        rss_feed.rss_entries.push(rss_entry);
+        app.storage.save_rss_feed(&rss_feed).unwrap();
+        app.rss_feeds = vec![rss_feed];
+        app.open_rss_entry(0, 0);
+        let theme = app.theme;
+        let rss_entry = &app.rss_feeds[0].rss_entries[0];
+        app.rendered_content
+            .render(&rss_entry.content_html, &rss_entry.link, 80, &theme);
+        let press = |app: &mut App, code: KeyCode| {
+            app.handle_key(KeyEvent::new(code, KeyModifiers::NONE), &[])
+                .unwrap();
+        };
+
+        press(&mut app, KeyCode::Char('l'));
+        assert!(app.popup == PopupState::Links);
+        press(&mut app, KeyCode::Char('k'));
+        assert!(app.link_cursor == 1);
+        press(&mut app, KeyCode::Char('j'));
+        assert!(app.link_cursor == 0);
+
+        press(&mut app, KeyCode::Char('a'));
+        assert!(app.popup == PopupState::Info);
+        assert!(app.info_message.as_deref() == Some("https://example.com/about isn't a feed"));
+        press(&mut app, KeyCode::Enter);
+
+        press(&mut app, KeyCode::Char('l'));
+        press(&mut app, KeyCode::Char('j'));
+        press(&mut app, KeyCode::Char('a'));
+        assert!(app.popup == PopupState::None);
+
+        app.rendered_content
+            .render("<p>No links</p>", "", 80, &theme);
+        press(&mut app, KeyCode::Char('l'));
+        assert!(app.popup == PopupState::Info);
+    }
+
+    /// Tests that fetched full content is kept apart from the feed's
+    /// content, and that the entry view toggles between them.
+    #[tokio::test]
+    async fn test_toggle_full_content() {
+        let base_url = serve(vec![(
+            "/posts/1",
+            200,
+            "<html><body><p>The full text of the entry.</p></body></html>",
+        )])
+        .await;
+        let temp_dir = tempdir().unwrap();
+        let db_path = PathBuf::from_str(temp_dir.path().to_str().unwrap()).unwrap();
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        let mut app = App::new(
+            sender,
+            Some(db_path),
+            RetentionPolicy::default(),
+            SyncOptions::default(),
+        )
+        .unwrap();
+        let mut rss_feed = test_rss_feed("feed", "https://example.com/feed.xml");
+        let mut rss_entry = test_rss_entry("entry", &format!("{}/posts/1", base_url));
+        rss_entry.content_html = "<p>The summary.</p>".to_string();
+        rss_feed.rss_entries.push(rss_entry);
+        app.storage.save_rss_feed(&rss_feed).unwrap();
+        app.rss_feeds = vec![rss_feed];
+        app.open_rss_entry(0, 0);
+        let press = |app: &mut App, code: KeyCode| {
+            app.handle_key(KeyEvent::new(code, KeyModifiers::NONE), &[])
+                .unwrap();
+        };
+
+        // Without full content, toggling fetches it.
+        press(&mut app, KeyCode::Char('v'));
+        let app_event = timeout(Duration::from_secs(2), receiver.recv())
+            .await
+            .expect("timed out waiting for AppEvent")
+            .expect("channel closed");
+        app.handle_app_event(app_event);
+        let rss_entry = &app.rss_feeds[0].rss_entries[0];
+        assert!(rss_entry.content == "entry content");
+        assert!(rss_entry.content_html == "<p>The summary.</p>");
+        assert!(app.show_full_content);
+        assert!(
+            rss_entry
+                .displayed_html(app.show_full_content)
+                .contains("The full text of the entry.")
+        );
+        let saved_rss_entry = &app.storage.load_rss_feeds().unwrap()[0].rss_entries[0];
+        assert!(saved_rss_entry.content_html == "<p>The summary.</p>");
+        assert!(saved_rss_entry.full_content == rss_entry.full_content);
+
+        press(&mut app, KeyCode::Char('v'));
+        assert!(!app.show_full_content);
+        let rss_entry = &app.rss_feeds[0].rss_entries[0];
+        assert!(rss_entry.displayed_html(app.show_full_content) == "<p>The summary.</p>");
+        press(&mut app, KeyCode::Char('v'));
+        assert!(app.show_full_content);
+    }
+
+    /// Tests that a web page served with an error status isn't taken as an
+    /// entry's full content.
+    #[tokio::test]
+    async fn test_fetch_full_content_error_status() {
+        let base_url = serve(vec![(
+            "/posts/1",
+            404,
+            "<html><body><p>This page could not be found.</p></body></html>",
+        )])
+        .await;
+        let client = reqwest::Client::new();
+        let result = fetch_full_content(&client, &format!("{}/posts/1", base_url)).await;
+        assert!(result.unwrap_err().contains("404"));
+    }
+
+    /// Tests that scraped content and the open entry follow entries by ID
+    /// when feeds and entries are reordered, and that entries removed in
+    /// the meantime are tolerated.
+    #[tokio::test]
+    async fn test_rss_entry_ids_survive_reordering() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = PathBuf::from_str(temp_dir.path().to_str().unwrap()).unwrap();
+        let (sender, _) = mpsc::unbounded_channel();
+        let mut app = App::new(
+            sender,
+            Some(db_path),
+            RetentionPolicy::default(),
+            SyncOptions::default(),
+        )
+        .unwrap();
+        let mut rss_feed = test_rss_feed("feed", "https://example.com/feed.xml");
+        rss_feed.rss_entries = vec![
+            test_rss_entry("first", "https://example.com/first"),
+            test_rss_entry("second", "https://example.com/second"),
+        ];
+        app.storage.save_rss_feed(&rss_feed).unwrap();
+        app.rss_feeds = vec![rss_feed];
+        app.open_rss_entry(0, 1);
+
+        // A feed added before it and an entry added before the open entry
+        // shift both of their indices.
+        app.rss_feeds
+            .insert(0, test_rss_feed("added", "https://example.com/added.xml"));
+        app.rss_feeds[1]
+            .rss_entries
+            .insert(0, test_rss_entry("new", "https://example.com/new"));
+        assert!(app.open_rss_entry_indices() == Some((1, 2)));
+        app.handle_app_event(AppEvent::ScrapedEntry {
+            rss_feed_id: "feed".to_string(),
+            rss_entry_id: "second".to_string(),
+            result: Ok("<p>Full text</p>".to_string()),
+            requested: true,
+        });
+        assert!(app.popup == PopupState::None);
+        let rss_entries = &app.rss_feeds[1].rss_entries;
+        assert!(rss_entries[2].full_content.as_ref().unwrap().html == "<p>Full text</p>");
+        assert!(rss_entries[..2].iter().all(|e| e.full_content.is_none()));
+
+        // Content for an entry that was removed is dropped.
+        app.handle_app_event(AppEvent::ScrapedEntry {
+            rss_feed_id: "deleted".to_string(),
+            rss_entry_id: "second".to_string(),
+            result: Ok("<p>Full text</p>".to_string()),
+            requested: true,
+        });
+        assert!(app.popup == PopupState::None);
+
+        // Once the open entry is removed, keys return to the feeds view.
+        app.rss_feeds[1].rss_entries.remove(2);
+        assert!(app.open_rss_entry_indices().is_none());
+        app.handle_key(KeyEvent::new(KeyCode::Char('j'), KeyModifiers::NONE), &[])
+            .unwrap();
+        assert!(app.view_state == ViewState::RssFeeds);
+    }
+
+    /// Tests that syncing a feed set to fetch full content fetches the
+    /// full content of its new entries only, without reporting failures.
+    #[tokio::test]
+    async fn test_sync_fetches_full_content() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = PathBuf::from_str(temp_dir.path().to_str().unwrap()).unwrap();
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        let mut app = App::new(
+            sender,
+            Some(db_path),
+            RetentionPolicy::default(),
+            SyncOptions::default(),
+        )
+        .unwrap();
+        let mut rss_feed = test_rss_feed("feed", "https://example.com/feed.xml");
+        rss_feed
+            .rss_entries
+            .push(test_rss_entry("old", "http://127.0.0.1:1/old"));
+        app.rss_feeds = vec![rss_feed];
+        let rows = get_rows(&app);
+        app.cursor = rows.iter().position(|r| *r == Row::RssFeed(0)).unwrap();
+        app.handle_key(KeyEvent::new(KeyCode::Char('F'), KeyModifiers::NONE), &rows)
+            .unwrap();
+        assert!(app.rss_feeds[0].fetch_full_content);
+        assert!(app.storage.load_rss_feeds().unwrap()[0].fetch_full_content);
+        app.popup = PopupState::None;
+
+        app.syncing_rss_feed_ids = HashSet::from(["feed".to_string()]);
+        app.handle_app_event(AppEvent::RssFeedSynced(
+            Some(Box::new(FetchedRssFeed {
+                rss_entries: vec![
+                    test_rss_entry("old", "http://127.0.0.1:1/old"),
+                    test_rss_entry("new", "http://127.0.0.1:1/new"),
+                ],
+                etag: None,
+                last_modified: None,
+                last_fetched: Utc::now(),
+                refresh_interval: None,
+            })),
+            RssFeedSyncResult::new(&app.rss_feeds[0]),
+        ));
+        let app_event = timeout(Duration::from_secs(2), receiver.recv())
+            .await
+            .expect("timed out waiting for AppEvent")
+            .expect("channel closed");
+        let AppEvent::ScrapedEntry {
+            ref rss_feed_id,
+            ref rss_entry_id,
+            ref result,
+            requested,
+        } = app_event
+        else {
+            panic!("expected a scraped entry");
+        };
+        assert!(rss_feed_id == "feed");
+        assert!(rss_entry_id == "new");
+        assert!(result.is_err());
+        assert!(!requested);
+        app.handle_app_event(app_event);
+        assert!(app.popup == PopupState::None);
+        assert!(
+            app.rss_feeds[0]
+                .rss_entries
+                .iter()
+                .all(|e| e.full_content.is_none())
+        );
+        assert!(receiver.try_recv().is_err());
+    }
+
+    /// Tests jumping between feeds with unread entries in the feeds view,
+    /// and between unread entries across feeds in the entry view.
+    #[tokio::test]
+    async fn test_unread_navigation() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = PathBuf::from_str(temp_dir.path().to_str().unwrap()).unwrap();
+        let (sender, _) = mpsc::unbounded_channel();
+        let mut app = App::new(
+            sender,
+            Some(db_path),
+            RetentionPolicy::default(),
+            SyncOptions::default(),
+        )
+        .unwrap();
+        let mut unfiled = test_rss_feed("unfiled", "https://example.com/unfiled.xml");
+        let mut read_rss_entry = test_rss_entry("unfiled-read", "https://example.com/a0");
+        read_rss_entry.read = true;
+        unfiled.rss_entries = vec![
+            read_rss_entry,
+            test_rss_entry("unfiled-unread", "https://example.com/a1"),
+        ];
+        let mut filed = test_rss_feed("filed", "https://example.com/filed.xml");
+        filed.folder = Some("News".to_string());
+        let mut read_rss_entry = test_rss_entry("filed-read", "https://example.com/b1");
+        read_rss_entry.read = true;
+        filed.rss_entries = vec![
+            test_rss_entry("filed-unread", "https://example.com/b0"),
+            read_rss_entry,
+        ];
+        for rss_feed in [&unfiled, &filed] {
+            app.storage.save_rss_feed(rss_feed).unwrap();
+        }
+        app.rss_feeds = vec![unfiled, filed];
+        app.reload_folders();
+        let press = |app: &mut App, code: KeyCode| {
+            let rows = get_rows(app);
+            app.handle_key(KeyEvent::new(code, KeyModifiers::NONE), &rows)
+                .unwrap();
+        };
+
+        assert!(app.rss_feed_order() == vec![1, 0]);
+        press(&mut app, KeyCode::Char(']'));
+        assert!(app.folders[0].expanded);
+        assert!(app.cursor == 4);
+        assert!(get_rows(&app)[4] == Row::RssFeed(1));
+        press(&mut app, KeyCode::Char(']'));
+        assert!(app.cursor == 5);
+        press(&mut app, KeyCode::Char(']'));
+        assert!(app.cursor == 5);
+        press(&mut app, KeyCode::Char('['));
+        assert!(app.cursor == 4);
+
+        app.open_rss_entry(1, 0);
+        press(&mut app, KeyCode::Char('t'));
+        press(&mut app, KeyCode::Char('n'));
+        assert!(app.open_rss_entry_indices() == Some((0, 1)));
+        assert!(app.rss_feeds[0].rss_entries[1].read);
+        press(&mut app, KeyCode::Char('n'));
+        assert!(app.popup == PopupState::Info);
+        press(&mut app, KeyCode::Char('q'));
+        press(&mut app, KeyCode::Char('p'));
+        assert!(app.open_rss_entry_indices() == Some((1, 0)));
+    }
+
     /// Tests deleting an existing RSS feed.
     #[tokio::test]
     async fn test_delete_rss_feed() {
@@ -931,7 +3038,13 @@ mod tests {
         let db_path = PathBuf::from_str(temp_dir.path().to_str().unwrap()).unwrap();
         let (sender, _) = mpsc::unbounded_channel();
         let rows: Vec<Row> = vec![Row::RssFeed(0), Row::RssEntry(0, 0)];
-        let mut app = App::new(sender, Some(db_path), None).unwrap();
+        let mut app = App::new(
+            sender,
+            Some(db_path),
+            RetentionPolicy::default(),
+            SyncOptions::default(),
+        )
+        .unwrap();
         // Last frame area will affect the outcome of attempting to scroll.
         // If this is left as its default, each 'j' key press will scroll
         // downwards, when, in this test, the entry content is very small.
@@ -953,9 +3066,11 @@ mod tests {
                 content_total_lines: 1,
                 read: false,
                 link: "https://example.com".to_string(),
+                ..Default::default()
             }],
             expanded: false,
             link: "https://example.com".to_string(),
+            ..Default::default()
         }];
 
         // Delete the RSS feed.
@@ -968,7 +3083,7 @@ mod tests {
             .unwrap();
 
         assert!(app.popup == PopupState::None);
-        assert!(app.rss_feeds.len() == 0);
+        assert!(app.rss_feeds.is_empty());
 
         let quit_result = app
             .handle_key(KeyEvent::new(KeyCode::Char('q'), KeyModifiers::NONE), &rows)
@@ -977,14 +3092,21 @@ mod tests {
         assert!(quit_result);
     }
 
-    /// Tests attempting to add a non-existent RSS feed.
+    /// Tests attempting to add a URL that is neither a feed nor a website.
     #[tokio::test]
     async fn test_add_rss_feed_failure() {
+        let base_url = serve(vec![("/rss.xml", 200, "not a feed")]).await;
         let temp_dir = tempdir().unwrap();
         let db_path = PathBuf::from_str(temp_dir.path().to_str().unwrap()).unwrap();
         let (sender, mut receiver) = mpsc::unbounded_channel();
         let rows: Vec<Row> = Vec::new();
-        let mut app = App::new(sender, Some(db_path), None).unwrap();
+        let mut app = App::new(
+            sender,
+            Some(db_path),
+            RetentionPolicy::default(),
+            SyncOptions::default(),
+        )
+        .unwrap();
 
         // Enter 'a', causing the "Add feed" popup to open.
         let add_key_event = KeyEvent::new(KeyCode::Char('a'), KeyModifiers::NONE);
@@ -992,7 +3114,7 @@ mod tests {
         assert!(app.popup == PopupState::AddRssFeed);
 
         // Enter mock RSS feed URL.
-        app.input = "https://example.com/rss.xml".to_string();
+        app.input = format!("{}/rss.xml", base_url);
         let enter_key_event = KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE);
         app.handle_key(enter_key_event, &rows).unwrap();
         assert!(app.popup == PopupState::None);
@@ -1009,4 +3131,714 @@ mod tests {
         );
         assert!(app.popup == PopupState::Error);
     }
+
+    /// Tests that a feed failing to sync doesn't prevent other feeds
+    /// from syncing.
+    #[tokio::test]
+    async fn test_sync_feeds_isolates_failures() {
+        let base_url = serve(vec![
+            ("/feed.xml", 200, TEST_RSS),
+            ("/malformed.xml", 200, "not a feed"),
+        ])
+        .await;
+        let rss_feeds = vec![
+            test_rss_feed("good", &format!("{}/feed.xml", base_url)),
+            test_rss_feed("malformed", &format!("{}/malformed.xml", base_url)),
+            test_rss_feed("missing", &format!("{}/missing.xml", base_url)),
+        ];
+
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        sync_feeds(
+            rss_feeds,
+            SyncOptions::default(),
+            &sender,
+            CancellationToken::new(),
+        )
+        .await;
+        let synced: HashMap<String, (Option<Box<FetchedRssFeed>>, RssFeedSyncResult)> =
+            synced_rss_feeds(&mut receiver)
+                .into_iter()
+                .map(|synced| (synced.1.rss_feed_id.clone(), synced))
+                .collect();
+
+        assert!(synced.len() == 3);
+        let (good_fetched, good) = &synced["good"];
+        assert!(good.error.is_none());
+        assert!(good.status == Some(200));
+        assert!(good_fetched.as_ref().unwrap().rss_entries.len() == 2);
+        let (malformed_fetched, malformed) = &synced["malformed"];
+        assert!(malformed.status == Some(200));
+        assert!(
+            malformed
+                .error
+                .as_ref()
+                .unwrap()
+                .starts_with("failed to parse feed")
+        );
+        assert!(malformed_fetched.is_none());
+        let (missing_fetched, missing) = &synced["missing"];
+        assert!(missing.status == Some(404));
+        assert!(missing.error.is_some());
+        assert!(missing_fetched.is_none());
+    }
+
+    /// Tests that syncing never fetches more feeds from a single host
+    /// at once than the per-host limit allows.
+    #[tokio::test]
+    async fn test_sync_feeds_per_host_limit() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let active = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let max_active = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let (server_active, server_max_active) = (active.clone(), max_active.clone());
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let active = server_active.clone();
+                let max_active = server_max_active.clone();
+                tokio::spawn(async move {
+                    let mut buffer = vec![0; 4096];
+                    let _ = stream.read(&mut buffer).await;
+                    let now_active = active.fetch_add(1, Ordering::SeqCst) + 1;
+                    max_active.fetch_max(now_active, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                    active.fetch_sub(1, Ordering::SeqCst);
+                    let response = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        TEST_RSS.len(),
+                        TEST_RSS
+                    );
+                    let _ = stream.write_all(response.as_bytes()).await;
+                });
+            }
+        });
+        let rss_feeds: Vec<RssFeed> = (0..6)
+            .map(|i| test_rss_feed(&i.to_string(), &format!("http://{}/{}.xml", address, i)))
+            .collect();
+        let sync_options = SyncOptions {
+            max_concurrent_feeds: 8,
+            max_concurrent_feeds_per_host: 2,
+            refresh_interval: None,
+            user_agent: None,
+        };
+
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        sync_feeds(rss_feeds, sync_options, &sender, CancellationToken::new()).await;
+
+        let synced = synced_rss_feeds(&mut receiver);
+        assert!(
+            synced
+                .iter()
+                .all(|(_, sync_result)| sync_result.error.is_none())
+        );
+        assert!(synced.len() == 6);
+        assert!(max_active.load(Ordering::SeqCst) <= 2);
+    }
+
+    /// Tests that feeds that synced are saved as they finish, feeds that
+    /// failed are skipped, and the sync report is shown once syncing ends.
+    #[tokio::test]
+    async fn test_sync_finished_saves_synced_feeds() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = PathBuf::from_str(temp_dir.path().to_str().unwrap()).unwrap();
+        let (sender, _) = mpsc::unbounded_channel();
+        let mut app = App::new(
+            sender,
+            Some(db_path),
+            RetentionPolicy::default(),
+            SyncOptions::default(),
+        )
+        .unwrap();
+        app.syncing_rss_feed_ids = HashSet::from(["synced".to_string(), "failed".to_string()]);
+        app.report_sync = true;
+
+        app.rss_feeds = vec![
+            test_rss_feed("synced", "https://example.com/synced.xml"),
+            test_rss_feed("failed", "https://example.com/failed.xml"),
+        ];
+        app.handle_app_event(AppEvent::RssFeedSynced(
+            Some(Box::new(FetchedRssFeed {
+                rss_entries: vec![test_rss_entry("entry", "https://example.com/entry")],
+                etag: None,
+                last_modified: None,
+                last_fetched: Utc::now(),
+                refresh_interval: Some(chrono::Duration::hours(2)),
+            })),
+            RssFeedSyncResult {
+                rss_feed_id: "synced".to_string(),
+                rss_feed_title: "synced title".to_string(),
+                status: Some(200),
+                new_rss_entries: 0,
+                updated_rss_entries: 0,
+                error: None,
+            },
+        ));
+        assert!(app.is_syncing());
+        assert!(app.popup == PopupState::None);
+        app.handle_app_event(AppEvent::RssFeedSynced(
+            None,
+            RssFeedSyncResult {
+                rss_feed_id: "failed".to_string(),
+                rss_feed_title: "failed title".to_string(),
+                status: Some(500),
+                new_rss_entries: 0,
+                updated_rss_entries: 0,
+                error: Some("unexpected HTTP status 500".to_string()),
+            },
+        ));
+        assert!(app.sync_results.len() == 2);
+        assert!(!app.is_syncing());
+        assert!(!app.report_sync);
+        assert!(app.popup == PopupState::SyncReport);
+        assert!(app.rss_feeds.len() == 2);
+        let saved_rss_feeds = app.storage.load_rss_feeds().unwrap();
+        assert!(saved_rss_feeds.len() == 1);
+        assert!(saved_rss_feeds[0].id == "synced");
+        assert!(saved_rss_feeds[0].rss_entries.len() == 1);
+        assert!(saved_rss_feeds[0].refresh_interval == Some(chrono::Duration::hours(2)));
+        assert!(app.sync_results[0].new_rss_entries == 1);
+
+        app.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE), &[])
+            .unwrap();
+        assert!(app.popup == PopupState::None);
+    }
+
+    /// Tests that a sync finishing while an input popup is open leaves the
+    /// popup and its input alone, and shows its report once it closes.
+    #[tokio::test]
+    async fn test_sync_report_waits_for_popup() {
+        let temp_dir = tempdir().unwrap();
+        let (sender, _receiver) = mpsc::unbounded_channel();
+        let mut app = App::new(
+            sender,
+            Some(temp_dir.path().to_path_buf()),
+            RetentionPolicy::default(),
+            SyncOptions::default(),
+        )
+        .unwrap();
+        app.rss_feeds = vec![test_rss_feed("feed", "https://example.com/feed.xml")];
+        app.syncing_rss_feed_ids = HashSet::from(["feed".to_string()]);
+        app.report_sync = true;
+        let press = |app: &mut App, code: KeyCode| {
+            app.handle_key(KeyEvent::new(code, KeyModifiers::NONE), &[])
+                .unwrap();
+        };
+        press(&mut app, KeyCode::Char('a'));
+        press(&mut app, KeyCode::Char('x'));
+        assert!(app.popup == PopupState::AddRssFeed);
+
+        app.handle_app_event(AppEvent::RssFeedSynced(
+            None,
+            RssFeedSyncResult::new(&app.rss_feeds[0]),
+        ));
+        assert!(!app.is_syncing());
+        assert!(app.popup == PopupState::AddRssFeed);
+        assert!(app.input == "x");
+
+        press(&mut app, KeyCode::Char('y'));
+        assert!(app.popup == PopupState::AddRssFeed);
+        assert!(app.input == "xy");
+        press(&mut app, KeyCode::Esc);
+        assert!(app.popup == PopupState::SyncReport);
+        press(&mut app, KeyCode::Enter);
+        assert!(app.popup == PopupState::None);
+    }
+
+    /// Tests that feeds are due once their refresh interval has passed
+    /// since they were last fetched or last attempted.
+    #[test]
+    fn test_is_sync_due() {
+        let now = Utc::now();
+        let default_refresh_interval = chrono::Duration::hours(1);
+        let mut rss_feed = test_rss_feed("feed", "https://example.com/feed.xml");
+        assert!(rss_feed.is_sync_due(default_refresh_interval, now));
+
+        rss_feed.last_fetched = Some(now - chrono::Duration::minutes(30));
+        assert!(!rss_feed.is_sync_due(default_refresh_interval, now));
+        rss_feed.refresh_interval = Some(chrono::Duration::minutes(20));
+        assert!(rss_feed.is_sync_due(default_refresh_interval, now));
+        rss_feed.last_sync_attempt = Some(now - chrono::Duration::minutes(10));
+        assert!(!rss_feed.is_sync_due(default_refresh_interval, now));
+
+        // Feeds are never refreshed more often than the minimum interval.
+        rss_feed.refresh_interval = Some(chrono::Duration::minutes(1));
+        rss_feed.last_sync_attempt = Some(now - chrono::Duration::minutes(3));
+        assert!(!rss_feed.is_sync_due(default_refresh_interval, now));
+    }
+
+    /// Tests that ticking syncs due feeds in the background, merging their
+    /// entries without showing the sync report, and stores the refresh
+    /// interval they advertise.
+    #[tokio::test]
+    async fn test_on_tick_syncs_due_feeds() {
+        let base_url =
+            serve_with(|_| http_response(200, "Cache-Control: public, max-age=7200\r\n", TEST_RSS))
+                .await;
+        let temp_dir = tempdir().unwrap();
+        let db_path = PathBuf::from_str(temp_dir.path().to_str().unwrap()).unwrap();
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        let mut app = App::new(
+            sender,
+            Some(db_path),
+            RetentionPolicy::default(),
+            SyncOptions::default(),
+        )
+        .unwrap();
+        app.rss_feeds = vec![
+            test_rss_feed("due", &format!("{}/due.xml", base_url)),
+            RssFeed {
+                last_fetched: Some(Utc::now()),
+                ..test_rss_feed("fresh", &format!("{}/fresh.xml", base_url))
+            },
+        ];
+
+        app.on_tick();
+        assert!(app.syncing_rss_feed_ids == HashSet::from(["due".to_string()]));
+        assert!(app.popup == PopupState::None);
+        while app.is_syncing() {
+            let app_event = timeout(Duration::from_secs(2), receiver.recv())
+                .await
+                .expect("timed out waiting for AppEvent")
+                .unwrap();
+            app.handle_app_event(app_event);
+        }
+
+        assert!(app.popup == PopupState::None);
+        assert!(app.rss_feeds[0].rss_entries.len() == 2);
+        assert!(app.rss_feeds[0].refresh_interval == Some(chrono::Duration::hours(2)));
+        app.on_tick();
+        assert!(!app.is_syncing());
+    }
+
+    /// Tests that cancelling a sync fails the feeds still being fetched,
+    /// without showing the sync report, and that reading stays possible
+    /// while syncing.
+    #[tokio::test]
+    async fn test_cancel_sync() {
+        // A server that accepts connections but never responds.
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut streams = Vec::new();
+            while let Ok((stream, _)) = listener.accept().await {
+                streams.push(stream);
+            }
+        });
+        let temp_dir = tempdir().unwrap();
+        let db_path = PathBuf::from_str(temp_dir.path().to_str().unwrap()).unwrap();
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        let mut app = App::new(
+            sender,
+            Some(db_path),
+            RetentionPolicy::default(),
+            SyncOptions::default(),
+        )
+        .unwrap();
+        app.rss_feeds = vec![test_rss_feed(
+            "stalled",
+            &format!("http://{}/feed.xml", address),
+        )];
+        let rows = vec![Row::RssFeed(0)];
+
+        app.handle_key(KeyEvent::new(KeyCode::Char('s'), KeyModifiers::NONE), &rows)
+            .unwrap();
+        assert!(app.is_syncing());
+        assert!(app.popup == PopupState::None);
+        let app_event = timeout(Duration::from_secs(2), receiver.recv())
+            .await
+            .expect("timed out waiting for AppEvent")
+            .unwrap();
+        app.handle_app_event(app_event);
+        assert!(app.fetching_rss_feed_ids == vec!["stalled".to_string()]);
+
+        app.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE), &rows)
+            .unwrap();
+        assert!(app.rss_feeds[0].expanded);
+        app.handle_key(KeyEvent::new(KeyCode::Char('x'), KeyModifiers::NONE), &rows)
+            .unwrap();
+        let Some(AppEvent::RssFeedSynced(None, sync_result)) =
+            timeout(Duration::from_secs(2), receiver.recv())
+                .await
+                .expect("timed out waiting for AppEvent")
+        else {
+            panic!("expected a cancelled feed");
+        };
+        assert!(sync_result.error.as_deref() == Some("cancelled"));
+        app.handle_app_event(AppEvent::RssFeedSynced(None, sync_result));
+
+        assert!(!app.is_syncing());
+        assert!(app.fetching_rss_feed_ids.is_empty());
+        assert!(app.popup == PopupState::None);
+    }
+
+    /// Tests that syncing stores a feed's cache validators and sends them
+    /// back, treating a 304 response as a feed without new entries.
+    #[tokio::test]
+    async fn test_sync_feeds_conditional_request() {
+        let base_url = serve_with(|request| {
+            if request.contains("if-none-match: \"v1\"")
+                && request.contains("if-modified-since: Tue, 06 Jan 2026 10:00:00 GMT")
+            {
+                http_response(304, "", "")
+            } else {
+                http_response(
+                    200,
+                    "ETag: \"v1\"\r\nLast-Modified: Tue, 06 Jan 2026 10:00:00 GMT\r\n",
+                    TEST_RSS,
+                )
+            }
+        })
+        .await;
+        let rss_feeds = vec![test_rss_feed("feed", &format!("{}/feed.xml", base_url))];
+
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        sync_feeds(
+            rss_feeds,
+            SyncOptions::default(),
+            &sender,
+            CancellationToken::new(),
+        )
+        .await;
+        let Some((Some(fetched_rss_feed), sync_result)) = synced_rss_feeds(&mut receiver).pop()
+        else {
+            panic!("expected a synced feed");
+        };
+        assert!(sync_result.status == Some(200));
+        assert!(fetched_rss_feed.rss_entries.len() == 2);
+        assert!(fetched_rss_feed.etag.as_deref() == Some("\"v1\""));
+        assert!(fetched_rss_feed.last_modified.as_deref() == Some("Tue, 06 Jan 2026 10:00:00 GMT"));
+
+        let rss_feed = RssFeed {
+            etag: fetched_rss_feed.etag.clone(),
+            last_modified: fetched_rss_feed.last_modified.clone(),
+            last_fetched: Some(fetched_rss_feed.last_fetched),
+            ..test_rss_feed("feed", &format!("{}/feed.xml", base_url))
+        };
+        sync_feeds(
+            vec![rss_feed],
+            SyncOptions::default(),
+            &sender,
+            CancellationToken::new(),
+        )
+        .await;
+        let Some((Some(refetched_rss_feed), sync_result)) = synced_rss_feeds(&mut receiver).pop()
+        else {
+            panic!("expected a synced feed");
+        };
+        assert!(sync_result.status == Some(304));
+        assert!(sync_result.error.is_none());
+        assert!(refetched_rss_feed.rss_entries.is_empty());
+        assert!(refetched_rss_feed.etag.as_deref() == Some("\"v1\""));
+        assert!(refetched_rss_feed.last_fetched >= fetched_rss_feed.last_fetched);
+    }
+
+    /// Tests that fetched entries are matched to existing entries by ID,
+    /// then link, then content hash, and that duplicates are dropped.
+    #[test]
+    fn test_merge_rss_entries_deduplicates() {
+        let mut rss_feed = test_rss_feed("feed", "https://example.com/feed.xml");
+        rss_feed.rss_entries = vec![
+            test_rss_entry("by-id", "https://example.com/by-id"),
+            test_rss_entry("by-link", "https://example.com/by-link"),
+            test_rss_entry("by-hash", ""),
+        ];
+
+        let fetched = vec![
+            test_rss_entry("by-id", "https://example.com/by-id"),
+            test_rss_entry("changed-id", "https://example.com/by-link"),
+            RssEntry {
+                id: "another-id".to_string(),
+                ..test_rss_entry("by-hash", "")
+            },
+            test_rss_entry("new", "https://example.com/new"),
+            test_rss_entry("new", "https://example.com/new"),
+        ];
+        let (num_new, num_updated) =
+            rss_feed.merge_rss_entries(fetched, RetentionPolicy::default());
+
+        assert!(num_new == 1);
+        assert!(num_updated == 1);
+        assert!(rss_feed.rss_entries.len() == 4);
+        assert!(rss_feed.rss_entries.iter().any(|e| e.id == "new"));
+        assert!(!rss_feed.rss_entries.iter().any(|e| e.id == "changed-id"));
+        assert!(!rss_feed.rss_entries.iter().any(|e| e.id == "another-id"));
+    }
+
+    /// Tests that an edited entry is refreshed in place and keeps its
+    /// read and starred flags.
+    #[test]
+    fn test_merge_rss_entries_refreshes_edited_entry() {
+        let mut rss_feed = test_rss_feed("feed", "https://example.com/feed.xml");
+        let mut rss_entry = test_rss_entry("entry", "https://example.com/entry");
+        rss_entry.read = true;
+        rss_entry.starred = true;
+        rss_feed.rss_entries = vec![rss_entry];
+
+        let mut edited = test_rss_entry("entry", "https://example.com/entry");
+        edited.title = "Edited title".to_string();
+        edited.content_hash = content_hash(&edited.title, &edited.content);
+        let (num_new, num_updated) =
+            rss_feed.merge_rss_entries(vec![edited], RetentionPolicy::default());
+
+        assert!(num_new == 0);
+        assert!(num_updated == 1);
+        assert!(rss_feed.rss_entries.len() == 1);
+        assert!(rss_feed.rss_entries[0].title == "Edited title");
+        assert!(rss_feed.rss_entries[0].read);
+        assert!(rss_feed.rss_entries[0].starred);
+    }
+
+    /// Tests that starring an entry is saved and that the entry survives
+    /// a sync that fills the feed with newer entries.
+    #[tokio::test]
+    async fn test_starred_rss_entry_survives_sync() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = PathBuf::from_str(temp_dir.path().to_str().unwrap()).unwrap();
+        let (sender, _) = mpsc::unbounded_channel();
+        let retention_policy = RetentionPolicy {
+            max_rss_entries: Some(1),
+            ..Default::default()
+        };
+        let mut app = App::new(
+            sender,
+            Some(db_path),
+            retention_policy,
+            SyncOptions::default(),
+        )
+        .unwrap();
+        let mut rss_feed = test_rss_feed("feed", "https://example.com/feed.xml");
+        let mut old_rss_entry = test_rss_entry("old", "https://example.com/old");
+        old_rss_entry.published = Utc::now() - chrono::Duration::days(1);
+        rss_feed.rss_entries = vec![old_rss_entry];
+        app.storage.save_rss_feed(&rss_feed).unwrap();
+        app.rss_feeds = vec![rss_feed];
+        let rows = vec![Row::RssFeed(0), Row::RssEntry(0, 0)];
+        app.cursor = 1;
+
+        app.handle_key(KeyEvent::new(KeyCode::Char('*'), KeyModifiers::NONE), &rows)
+            .unwrap();
+        assert!(app.rss_feeds[0].rss_entries[0].starred);
+
+        let fetched_rss_feed = FetchedRssFeed {
+            rss_entries: vec![
+                test_rss_entry("new", "https://example.com/new"),
+                test_rss_entry("newer", "https://example.com/newer"),
+            ],
+            etag: None,
+            last_modified: None,
+            last_fetched: Utc::now(),
+            refresh_interval: None,
+        };
+        let mut sync_result = RssFeedSyncResult::new(&app.rss_feeds[0]);
+        save_synced_rss_feed(
+            &mut app.storage,
+            &mut app.rss_feeds[0],
+            fetched_rss_feed,
+            &mut sync_result,
+        );
+        app.storage.expire_old_entries().unwrap();
+
+        let saved_rss_entries = app.storage.load_rss_feeds().unwrap().remove(0).rss_entries;
+        assert!(saved_rss_entries.len() == 2);
+        assert!(saved_rss_entries.iter().any(|e| e.id == "old" && e.starred));
+    }
+
+    /// Tests that fetched entries the retention policy would expire
+    /// straight away aren't added.
+    #[test]
+    fn test_merge_rss_entries_skips_expired_entries() {
+        let mut rss_feed = test_rss_feed("feed", "https://example.com/feed.xml");
+        let mut old_rss_entry = test_rss_entry("old", "https://example.com/old");
+        old_rss_entry.published = Utc::now() - chrono::Duration::days(10);
+        let fetched = vec![
+            old_rss_entry,
+            test_rss_entry("recent", "https://example.com/recent"),
+        ];
+        let retention_policy = RetentionPolicy {
+            unread_ttl: Some(chrono::Duration::days(5)),
+            ..Default::default()
+        };
+        let (num_new, _) = rss_feed.merge_rss_entries(fetched, retention_policy);
+
+        assert!(num_new == 1);
+        assert!(rss_feed.rss_entries.len() == 1);
+        assert!(rss_feed.rss_entries[0].id == "recent");
+    }
+
+    /// Tests that adding a website that links to a single feed adds that
+    /// feed.
+    #[tokio::test]
+    async fn test_add_rss_feed_discovers_linked_feed() {
+        let base_url = serve_website(
+            r#"<html><head>
+            <link rel="alternate" type="application/rss+xml" href="/posts.xml">
+            </head></html>"#,
+            vec![("/posts.xml", TEST_RSS)],
+        )
+        .await;
+        let temp_dir = tempdir().unwrap();
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        let mut app = App::new(
+            sender,
+            Some(temp_dir.path().to_path_buf()),
+            RetentionPolicy::default(),
+            SyncOptions::default(),
+        )
+        .unwrap();
+
+        let app_event = add_rss_feed(&mut app, &mut receiver, &base_url).await;
+        app.handle_app_event(app_event);
+        assert!(app.error_message.is_none());
+        assert!(app.rss_feeds.len() == 1);
+        assert!(app.rss_feeds[0].link == format!("{}/posts.xml", base_url));
+    }
+
+    /// Tests that adding a feed that already exists fails and leaves the
+    /// existing feed's folder and starred entries alone.
+    #[tokio::test]
+    async fn test_add_existing_rss_feed_keeps_state() {
+        let base_url = serve(vec![("/rss.xml", 200, TEST_RSS)]).await;
+        let temp_dir = tempdir().unwrap();
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        let mut app = App::new(
+            sender,
+            Some(temp_dir.path().to_path_buf()),
+            RetentionPolicy::default(),
+            SyncOptions::default(),
+        )
+        .unwrap();
+        let url = format!("{}/rss.xml", base_url);
+
+        let app_event = add_rss_feed(&mut app, &mut receiver, &url).await;
+        app.handle_app_event(app_event);
+        assert!(app.rss_feeds.len() == 1);
+        app.storage.create_folder("News").unwrap();
+        app.rss_feeds[0].folder = Some("News".to_string());
+        app.storage.save_rss_feed(&app.rss_feeds[0]).unwrap();
+        app.toggle_starred(0, 0);
+
+        let app_event = add_rss_feed(&mut app, &mut receiver, &url).await;
+        app.handle_app_event(app_event);
+        assert!(app.popup == PopupState::Error);
+        assert!(app.error_message.unwrap().ends_with("feed already exists"));
+        let saved_rss_feeds = app.storage.load_rss_feeds().unwrap();
+        assert!(saved_rss_feeds.len() == 1);
+        assert!(saved_rss_feeds[0].folder == Some("News".to_string()));
+        assert!(saved_rss_feeds[0].rss_entries[0].starred);
+    }
+
+    /// Tests that adding a website that links to no feeds adds a feed
+    /// found at a common feed path, and fails if there is none.
+    #[tokio::test]
+    async fn test_add_rss_feed_discovers_common_path() {
+        let base_url = serve_website("<html></html>", vec![("/atom.xml", TEST_RSS)]).await;
+        let temp_dir = tempdir().unwrap();
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        let mut app = App::new(
+            sender,
+            Some(temp_dir.path().to_path_buf()),
+            RetentionPolicy::default(),
+            SyncOptions::default(),
+        )
+        .unwrap();
+
+        let app_event = add_rss_feed(&mut app, &mut receiver, &base_url).await;
+        app.handle_app_event(app_event);
+        assert!(app.rss_feeds.len() == 1);
+        assert!(app.rss_feeds[0].link == format!("{}/atom.xml", base_url));
+
+        let base_url = serve_website("<html></html>", Vec::new()).await;
+        let app_event = add_rss_feed(&mut app, &mut receiver, &base_url).await;
+        app.handle_app_event(app_event);
+        assert!(app.popup == PopupState::Error);
+        assert!(
+            app.error_message.unwrap()
+                == format!("Failed to add feed: no feeds found at {}", base_url)
+        );
+    }
+
+    /// Tests that adding a website that links to several feeds shows a
+    /// picker, and that the picked feed is added.
+    #[tokio::test]
+    async fn test_add_rss_feed_picks_discovered_feed() {
+        let base_url = serve_website(
+            r#"<html><head>
+            <link rel="alternate" type="application/rss+xml" title="Posts" href="/posts.xml">
+            <link rel="alternate" type="application/atom+xml" title="Comments" href="/comments.xml">
+            </head></html>"#,
+            vec![("/posts.xml", TEST_RSS), ("/comments.xml", TEST_RSS)],
+        )
+        .await;
+        let temp_dir = tempdir().unwrap();
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        let mut app = App::new(
+            sender,
+            Some(temp_dir.path().to_path_buf()),
+            RetentionPolicy::default(),
+            SyncOptions::default(),
+        )
+        .unwrap();
+
+        let app_event = add_rss_feed(&mut app, &mut receiver, &base_url).await;
+        app.handle_app_event(app_event);
+        assert!(app.popup == PopupState::PickDiscoveredRssFeed);
+        assert!(app.discovered_rss_feeds.len() == 2);
+        assert!(app.discovered_rss_feeds[1].title == "Comments");
+
+        app.handle_key(KeyEvent::new(KeyCode::Char('j'), KeyModifiers::NONE), &[])
+            .unwrap();
+        app.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE), &[])
+            .unwrap();
+        assert!(app.popup == PopupState::None);
+        let app_event = timeout(Duration::from_secs(2), receiver.recv())
+            .await
+            .expect("timed out waiting for AppEvent")
+            .expect("channel closed");
+        app.handle_app_event(app_event);
+        assert!(app.rss_feeds.len() == 1);
+        assert!(app.rss_feeds[0].link == format!("{}/comments.xml", base_url));
+    }
+
+    /// Tests searching entries, opening a result, and returning to the
+    /// results and then the feeds.
+    #[tokio::test]
+    async fn test_search_opens_rss_entry() {
+        let temp_dir = tempdir().unwrap();
+        let (sender, _) = mpsc::unbounded_channel();
+        let mut app = App::new(
+            sender,
+            Some(temp_dir.path().to_path_buf()),
+            RetentionPolicy::default(),
+            SyncOptions::default(),
+        )
+        .unwrap();
+        let mut rss_feed = test_rss_feed("feed", "https://example.com/feed.xml");
+        rss_feed.rss_entries = vec![
+            test_rss_entry("first", "https://example.com/first"),
+            test_rss_entry("second", "https://example.com/second"),
+        ];
+        app.storage.save_rss_feed(&rss_feed).unwrap();
+        app.rss_feeds = vec![rss_feed];
+
+        app.handle_key(KeyEvent::new(KeyCode::Char('/'), KeyModifiers::NONE), &[])
+            .unwrap();
+        assert!(app.popup == PopupState::Search);
+        app.input = "second".to_string();
+        app.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE), &[])
+            .unwrap();
+        assert!(app.view_state == ViewState::SearchResults);
+        assert!(app.search_results.len() == 1);
+        assert!(app.search_results[0].rss_entry_id == "second");
+
+        app.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE), &[])
+            .unwrap();
+        assert!(app.open_rss_entry_indices() == Some((0, 1)));
+        assert!(app.rss_feeds[0].rss_entries[1].read);
+
+        let quit_key_event = KeyEvent::new(KeyCode::Char('q'), KeyModifiers::NONE);
+        app.handle_key(quit_key_event, &[]).unwrap();
+        assert!(app.view_state == ViewState::SearchResults);
+        app.handle_key(quit_key_event, &[]).unwrap();
+        assert!(app.view_state == ViewState::RssFeeds);
+        assert!(app.search_query.is_none());
+    }
 }
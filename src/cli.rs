@@ -0,0 +1,870 @@
+//! Commands run from the command line without starting the TUI, for use
+//! in scripts and scheduled jobs.
+
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+use anyhow::{Result, anyhow};
+use chrono::{Duration, Local, Utc};
+use clap::Subcommand;
+use serde::Serialize;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+use crate::app::{
+    AppEvent, FullContent, RssEntry, RssFeed, RssFeedSyncResult, SyncOptions, fetch_full_content,
+    save_synced_rss_feed, sync_feeds,
+};
+use crate::discovery::{self, Discovery};
+use crate::local_storage::{LocalStorage, RetentionPolicy};
+use crate::opml;
+
+/// Commands run without starting the TUI. Feeds can be given by ID, URL
+/// or title.
+#[derive(Subcommand)]
+pub enum Command {
+    /// Adds a feed, or the feed a website offers.
+    Add { url: String },
+    /// Removes a feed and its entries.
+    Remove { feed: String },
+    /// Lists every feed.
+    ListFeeds,
+    /// Lists entries, newest first.
+    ListEntries {
+        /// Only lists unread entries.
+        #[arg(long)]
+        unread: bool,
+        /// Only lists the entries of this feed.
+        #[arg(long)]
+        feed: Option<String>,
+    },
+    /// Fetches new entries for every feed.
+    Sync,
+    /// Marks entries as read. Without entry IDs, marks every entry of
+    /// the feed given with --feed, or every entry with --all.
+    MarkRead {
+        /// The IDs of the entries to mark as read.
+        entry_ids: Vec<String>,
+        /// The feed the entries belong to.
+        #[arg(long)]
+        feed: Option<String>,
+        /// Marks every entry as read.
+        #[arg(long, conflicts_with_all = ["entry_ids", "feed"])]
+        all: bool,
+    },
+    /// Shows an entry and its content.
+    Show {
+        entry_id: String,
+        /// The feed the entry belongs to, needed if several feeds have
+        /// an entry with the same ID.
+        #[arg(long)]
+        feed: Option<String>,
+    },
+    /// Sets how long a feed's entries are kept, overriding the global
+    /// retention. Shows the feed's overrides if none are given.
+    Retention {
+        feed: String,
+        /// How many days read entries are kept.
+        #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+        read_ttl_days: Option<u32>,
+        /// How many days unread entries are kept.
+        #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+        unread_ttl_days: Option<u32>,
+        /// The most entries kept.
+        #[arg(long)]
+        max_entries: Option<usize>,
+        /// Clears the feed's overrides before setting any given, so the
+        /// global retention applies.
+        #[arg(long)]
+        clear: bool,
+    },
+    /// Imports feeds from an OPML file.
+    Import { path: PathBuf },
+    /// Exports feeds to an OPML file, or to standard output if no path
+    /// is given.
+    Export { path: Option<PathBuf> },
+}
+
+/// A feed, as output by commands.
+#[derive(Serialize)]
+struct RssFeedOutput {
+    id: String,
+    title: String,
+    link: String,
+    html_url: Option<String>,
+    folder: Option<String>,
+    entries: usize,
+    unread_entries: usize,
+    last_fetched: Option<String>,
+}
+
+impl From<&RssFeed> for RssFeedOutput {
+    fn from(rss_feed: &RssFeed) -> Self {
+        RssFeedOutput {
+            id: rss_feed.id.clone(),
+            title: rss_feed.title.clone(),
+            link: rss_feed.link.clone(),
+            html_url: rss_feed.html_url.clone(),
+            folder: rss_feed.folder.clone(),
+            entries: rss_feed.rss_entries.len(),
+            unread_entries: rss_feed.rss_entries.iter().filter(|e| !e.read).count(),
+            last_fetched: rss_feed.last_fetched.map(|t| t.to_rfc3339()),
+        }
+    }
+}
+
+impl RssFeedOutput {
+    fn to_human(&self) -> String {
+        let folder = self
+            .folder
+            .as_ref()
+            .map(|f| format!("{}/", f))
+            .unwrap_or_default();
+        format!(
+            "{}{} ({} unread) {}",
+            folder, self.title, self.unread_entries, self.link
+        )
+    }
+}
+
+/// The retention overrides of a feed, as output by commands. Unset rules
+/// fall back to the global retention.
+#[derive(Serialize)]
+struct RetentionOutput {
+    feed_id: String,
+    feed_title: String,
+    read_ttl_days: Option<i64>,
+    unread_ttl_days: Option<i64>,
+    max_entries: Option<usize>,
+}
+
+impl From<&RssFeed> for RetentionOutput {
+    fn from(rss_feed: &RssFeed) -> Self {
+        let retention_override = rss_feed.retention_override;
+        RetentionOutput {
+            feed_id: rss_feed.id.clone(),
+            feed_title: rss_feed.title.clone(),
+            read_ttl_days: retention_override.read_ttl.map(|ttl| ttl.num_days()),
+            unread_ttl_days: retention_override.unread_ttl.map(|ttl| ttl.num_days()),
+            max_entries: retention_override.max_rss_entries,
+        }
+    }
+}
+
+impl RetentionOutput {
+    fn to_human(&self) -> String {
+        let rule = |value: Option<String>| value.unwrap_or_else(|| "global".to_string());
+        format!(
+            "{}: read entries {}, unread entries {}, max entries {}",
+            self.feed_title,
+            rule(self.read_ttl_days.map(|days| format!("{} days", days))),
+            rule(self.unread_ttl_days.map(|days| format!("{} days", days))),
+            rule(self.max_entries.map(|max| max.to_string())),
+        )
+    }
+}
+
+/// An entry, as output by commands.
+#[derive(Serialize)]
+struct RssEntryOutput {
+    feed_id: String,
+    feed_title: String,
+    id: String,
+    title: String,
+    link: String,
+    published: String,
+    read: bool,
+    starred: bool,
+}
+
+impl RssEntryOutput {
+    fn new(rss_feed: &RssFeed, rss_entry: &RssEntry) -> Self {
+        RssEntryOutput {
+            feed_id: rss_feed.id.clone(),
+            feed_title: rss_feed.title.clone(),
+            id: rss_entry.id.clone(),
+            title: rss_entry.title.clone(),
+            link: rss_entry.link.clone(),
+            published: rss_entry.published.to_rfc3339(),
+            read: rss_entry.read,
+            starred: rss_entry.starred,
+        }
+    }
+}
+
+/// An entry with its content, as output by the show command.
+#[derive(Serialize)]
+struct RssEntryDetailsOutput {
+    #[serde(flatten)]
+    rss_entry: RssEntryOutput,
+    authors: Vec<String>,
+    content: String,
+}
+
+/// A feed's sync result, as output by the sync command.
+#[derive(Serialize)]
+struct SyncResultOutput {
+    feed_id: String,
+    feed_title: String,
+    status: Option<u16>,
+    new_entries: usize,
+    updated_entries: usize,
+    error: Option<String>,
+}
+
+impl From<RssFeedSyncResult> for SyncResultOutput {
+    fn from(sync_result: RssFeedSyncResult) -> Self {
+        SyncResultOutput {
+            feed_id: sync_result.rss_feed_id,
+            feed_title: sync_result.rss_feed_title,
+            status: sync_result.status,
+            new_entries: sync_result.new_rss_entries,
+            updated_entries: sync_result.updated_rss_entries,
+            error: sync_result.error,
+        }
+    }
+}
+
+/// Formats a value as JSON.
+fn to_json<T: Serialize>(value: &T) -> Result<String> {
+    Ok(serde_json::to_string_pretty(value)?)
+}
+
+/// Finds a feed by ID, URL or title.
+fn find_rss_feed<'a>(rss_feeds: &'a [RssFeed], feed: &str) -> Result<&'a RssFeed> {
+    if let Some(rss_feed) = rss_feeds.iter().find(|f| f.id == feed || f.link == feed) {
+        return Ok(rss_feed);
+    }
+    let matches: Vec<&RssFeed> = rss_feeds
+        .iter()
+        .filter(|f| f.title.eq_ignore_ascii_case(feed))
+        .collect();
+    match matches.as_slice() {
+        [rss_feed] => Ok(rss_feed),
+        [] => Err(anyhow!("no feed matches {}", feed)),
+        _ => Err(anyhow!(
+            "several feeds are titled {}, give the feed's URL instead",
+            feed
+        )),
+    }
+}
+
+/// Retrieves every feed, or only the given feed.
+fn scoped_rss_feeds<'a>(rss_feeds: &'a [RssFeed], feed: Option<&str>) -> Result<Vec<&'a RssFeed>> {
+    match feed {
+        Some(feed) => Ok(vec![find_rss_feed(rss_feeds, feed)?]),
+        None => Ok(rss_feeds.iter().collect()),
+    }
+}
+
+/// Finds an entry by ID. Entry IDs are only unique within a feed, so an
+/// entry found in several feeds is an error.
+fn find_rss_entry<'a>(
+    rss_feeds: &[&'a RssFeed],
+    rss_entry_id: &str,
+) -> Result<(&'a RssFeed, &'a RssEntry)> {
+    let matches: Vec<(&RssFeed, &RssEntry)> = rss_feeds
+        .iter()
+        .filter_map(|rss_feed| {
+            rss_feed
+                .rss_entries
+                .iter()
+                .find(|e| e.id == rss_entry_id)
+                .map(|rss_entry| (*rss_feed, rss_entry))
+        })
+        .collect();
+    match matches.as_slice() {
+        [found] => Ok(*found),
+        [] => Err(anyhow!("no entry has the ID {}", rss_entry_id)),
+        _ => Err(anyhow!(
+            "several feeds have an entry with the ID {}, give the feed with --feed",
+            rss_entry_id
+        )),
+    }
+}
+
+/// Runs a command against the database, returning its output. Output is
+/// JSON if `json` is set and human-readable otherwise, with dates shown
+/// in `date_format`.
+pub async fn run_command(
+    command: Command,
+    storage: &mut LocalStorage,
+    sync_options: SyncOptions,
+    date_format: &str,
+    json: bool,
+) -> Result<String> {
+    match command {
+        Command::Add { url } => add(storage, &url, &sync_options, json).await,
+        Command::Remove { feed } => {
+            let rss_feeds = storage.load_rss_feeds()?;
+            let rss_feed = find_rss_feed(&rss_feeds, &feed)?;
+            storage.delete_rss_feed(&rss_feed.id)?;
+            let output = RssFeedOutput::from(rss_feed);
+            if json {
+                to_json(&output)
+            } else {
+                Ok(format!("Removed {}", output.title))
+            }
+        }
+        Command::ListFeeds => {
+            let rss_feeds = storage.load_rss_feeds()?;
+            let output: Vec<RssFeedOutput> = rss_feeds.iter().map(RssFeedOutput::from).collect();
+            if json {
+                to_json(&output)
+            } else {
+                Ok(output
+                    .iter()
+                    .map(RssFeedOutput::to_human)
+                    .collect::<Vec<_>>()
+                    .join("\n"))
+            }
+        }
+        Command::ListEntries { unread, feed } => {
+            let rss_feeds = storage.load_rss_feeds()?;
+            let mut rss_entries: Vec<(&RssFeed, &RssEntry)> =
+                scoped_rss_feeds(&rss_feeds, feed.as_deref())?
+                    .into_iter()
+                    .flat_map(|f| f.rss_entries.iter().map(move |e| (f, e)))
+                    .filter(|(_, e)| !unread || !e.read)
+                    .collect();
+            rss_entries.sort_by_key(|(_, e)| std::cmp::Reverse(e.published));
+            if json {
+                let output: Vec<RssEntryOutput> = rss_entries
+                    .into_iter()
+                    .map(|(f, e)| RssEntryOutput::new(f, e))
+                    .collect();
+                to_json(&output)
+            } else {
+                Ok(rss_entries
+                    .into_iter()
+                    .map(|(rss_feed, rss_entry)| {
+                        format!(
+                            "{} {} {}: {} [{}]",
+                            rss_entry
+                                .published
+                                .with_timezone(&Local)
+                                .format(date_format),
+                            if rss_entry.read { " " } else { "*" },
+                            rss_feed.title,
+                            rss_entry.title,
+                            rss_entry.id
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n"))
+            }
+        }
+        Command::Sync => sync(storage, sync_options, json).await,
+        Command::MarkRead {
+            entry_ids,
+            feed,
+            all,
+        } => {
+            let rss_feeds = storage.load_rss_feeds()?;
+            let scoped = scoped_rss_feeds(&rss_feeds, feed.as_deref())?;
+            let rss_entries: Vec<(String, String)> = if !entry_ids.is_empty() {
+                entry_ids
+                    .iter()
+                    .map(|id| {
+                        find_rss_entry(&scoped, id).map(|(f, e)| (f.id.clone(), e.id.clone()))
+                    })
+                    .collect::<Result<_>>()?
+            } else if all || feed.is_some() {
+                scoped
+                    .iter()
+                    .flat_map(|f| f.rss_entries.iter().map(|e| (f.id.clone(), e.id.clone())))
+                    .collect()
+            } else {
+                return Err(anyhow!("give the IDs of entries to mark, --feed or --all"));
+            };
+            let affected = storage.set_rss_entries_read(&rss_entries, true)?;
+            if json {
+                to_json(&serde_json::json!({ "marked_read": affected }))
+            } else {
+                Ok(format!("Marked {} entries as read", affected))
+            }
+        }
+        Command::Show { entry_id, feed } => {
+            let rss_feeds = storage.load_rss_feeds()?;
+            let scoped = scoped_rss_feeds(&rss_feeds, feed.as_deref())?;
+            let (rss_feed, rss_entry) = find_rss_entry(&scoped, &entry_id)?;
+            if json {
+                to_json(&RssEntryDetailsOutput {
+                    rss_entry: RssEntryOutput::new(rss_feed, rss_entry),
+                    authors: rss_entry.authors.clone(),
+                    content: rss_entry.content.clone(),
+                })
+            } else {
+                let mut lines = vec![rss_entry.title.clone(), format!("Feed: {}", rss_feed.title)];
+                if !rss_entry.authors.is_empty() {
+                    lines.push(format!("Authors: {}", rss_entry.authors.join(", ")));
+                }
+                lines.push(format!(
+                    "Published: {}",
+                    rss_entry
+                        .published
+                        .with_timezone(&Local)
+                        .format(date_format)
+                ));
+                lines.push(format!("Link: {}", rss_entry.link));
+                lines.push(String::new());
+                lines.push(rss_entry.content.clone());
+                Ok(lines.join("\n"))
+            }
+        }
+        Command::Retention {
+            feed,
+            read_ttl_days,
+            unread_ttl_days,
+            max_entries,
+            clear,
+        } => {
+            let rss_feeds = storage.load_rss_feeds()?;
+            let mut rss_feed = find_rss_feed(&rss_feeds, &feed)?.clone();
+            let retention_override = &mut rss_feed.retention_override;
+            if clear {
+                *retention_override = RetentionPolicy::default();
+            }
+            if let Some(days) = read_ttl_days {
+                retention_override.read_ttl = Some(Duration::days(days.into()));
+            }
+            if let Some(days) = unread_ttl_days {
+                retention_override.unread_ttl = Some(Duration::days(days.into()));
+            }
+            if let Some(max) = max_entries {
+                retention_override.max_rss_entries = Some(max);
+            }
+            storage.save_rss_feed(&rss_feed)?;
+            let output = RetentionOutput::from(&rss_feed);
+            if json {
+                to_json(&output)
+            } else {
+                Ok(output.to_human())
+            }
+        }
+        Command::Import { path } => {
+            let opml = std::fs::read_to_string(&path)
+                .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+            let imported_rss_feeds = opml::import_opml(storage, &opml)?;
+            if json {
+                let output: Vec<RssFeedOutput> =
+                    imported_rss_feeds.iter().map(RssFeedOutput::from).collect();
+                to_json(&output)
+            } else {
+                Ok(format!("Imported {} feeds", imported_rss_feeds.len()))
+            }
+        }
+        Command::Export { path } => {
+            let rss_feeds = storage.load_rss_feeds()?;
+            let opml = opml::write_opml(&rss_feeds);
+            match path {
+                Some(path) => {
+                    std::fs::write(&path, opml)
+                        .map_err(|e| anyhow!("Failed to write {}: {}", path.display(), e))?;
+                    Ok(format!(
+                        "Exported {} feeds to {}",
+                        rss_feeds.len(),
+                        path.display()
+                    ))
+                }
+                None => Ok(opml),
+            }
+        }
+    }
+}
+
+/// Fetches the full content of a synced feed's new entries from their web
+/// pages, one after another. Entries whose pages fail to load keep only
+/// the feed's content.
+async fn fetch_new_full_content(
+    storage: &mut LocalStorage,
+    client: &reqwest::Client,
+    rss_feed: &mut RssFeed,
+    old_rss_entry_ids: &HashSet<String>,
+) -> Result<()> {
+    for rss_entry in rss_feed
+        .rss_entries
+        .iter_mut()
+        .filter(|e| !old_rss_entry_ids.contains(&e.id))
+    {
+        if let Ok(html) = fetch_full_content(client, &rss_entry.link).await {
+            rss_entry.full_content = Some(FullContent {
+                html,
+                fetched: Utc::now(),
+            });
+            storage.save_rss_entry(&rss_feed.id, rss_entry)?;
+        }
+    }
+    Ok(())
+}
+
+/// Adds the feed at a URL, or the single feed a website offers.
+async fn add(
+    storage: &mut LocalStorage,
+    url: &str,
+    sync_options: &SyncOptions,
+    json: bool,
+) -> Result<String> {
+    let client = sync_options.http_client();
+    let (feed, rss_feed_url) = match discovery::discover_feeds(&client, url)
+        .await
+        .map_err(|e| anyhow!(e))?
+    {
+        Discovery::Feed(feed, rss_feed_url) => (feed, rss_feed_url),
+        Discovery::Candidates(discovered_rss_feeds) if discovered_rss_feeds.len() == 1 => {
+            let rss_feed_url = discovered_rss_feeds[0].url.clone();
+            let feed = discovery::fetch_feed(&client, &rss_feed_url)
+                .await
+                .map_err(|e| anyhow!(e))?;
+            (feed, rss_feed_url)
+        }
+        Discovery::Candidates(discovered_rss_feeds) => {
+            let candidates: Vec<String> = discovered_rss_feeds
+                .iter()
+                .map(|f| format!("  {} ({})", f.url, f.title))
+                .collect();
+            return Err(anyhow!(
+                "several feeds found at {}, add one of:\n{}",
+                url,
+                candidates.join("\n")
+            ));
+        }
+    };
+
+    let mut rss_feed = RssFeed::from(*feed);
+    rss_feed.link = rss_feed_url;
+    if storage
+        .load_rss_feeds()?
+        .iter()
+        .any(|f| f.id == rss_feed.id || f.link == rss_feed.link)
+    {
+        return Err(anyhow!(
+            "failed to add {}: feed already exists",
+            rss_feed.title
+        ));
+    }
+    storage.save_rss_feed(&rss_feed)?;
+    let output = RssFeedOutput::from(&rss_feed);
+    if json {
+        to_json(&output)
+    } else {
+        Ok(format!("Added {}", output.to_human()))
+    }
+}
+
+/// Syncs every feed, saving each as it finishes.
+async fn sync(storage: &mut LocalStorage, sync_options: SyncOptions, json: bool) -> Result<String> {
+    storage.expire_old_entries()?;
+    let mut rss_feeds = storage.load_rss_feeds()?;
+    let client = sync_options.http_client();
+    let (sender, mut receiver) = mpsc::unbounded_channel();
+    sync_feeds(
+        rss_feeds.clone(),
+        sync_options,
+        &sender,
+        CancellationToken::new(),
+    )
+    .await;
+    drop(sender);
+
+    let mut output: Vec<SyncResultOutput> = Vec::new();
+    while let Some(app_event) = receiver.recv().await {
+        let AppEvent::RssFeedSynced(fetched_rss_feed, mut sync_result) = app_event else {
+            continue;
+        };
+        if let Some(fetched_rss_feed) = fetched_rss_feed
+            && let Some(rss_feed) = rss_feeds
+                .iter_mut()
+                .find(|f| f.id == sync_result.rss_feed_id)
+        {
+            let old_rss_entry_ids: HashSet<String> =
+                rss_feed.rss_entries.iter().map(|e| e.id.clone()).collect();
+            save_synced_rss_feed(storage, rss_feed, *fetched_rss_feed, &mut sync_result);
+            if rss_feed.fetch_full_content {
+                fetch_new_full_content(storage, &client, rss_feed, &old_rss_entry_ids).await?;
+            }
+        }
+        output.push(SyncResultOutput::from(sync_result));
+    }
+
+    if json {
+        return to_json(&output);
+    }
+    let num_failed = output.iter().filter(|r| r.error.is_some()).count();
+    let num_new_rss_entries: usize = output.iter().map(|r| r.new_entries).sum();
+    let mut lines: Vec<String> = output
+        .iter()
+        .map(|r| match &r.error {
+            Some(error) => format!("{}: failed: {}", r.feed_title, error),
+            None => format!(
+                "{}: {} new, {} updated",
+                r.feed_title, r.new_entries, r.updated_entries
+            ),
+        })
+        .collect();
+    lines.push(format!(
+        "Synced {} of {} feeds, {} new entries",
+        output.len() - num_failed,
+        output.len(),
+        num_new_rss_entries
+    ));
+    Ok(lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::DEFAULT_DATE_FORMAT;
+    use crate::app::tests::{http_response, serve_with};
+    use tempfile::{TempDir, tempdir};
+
+    /// Opens storage holding two feeds that share an entry ID.
+    fn open_storage() -> (TempDir, LocalStorage) {
+        let temp_dir = tempdir().unwrap();
+        let mut storage =
+            LocalStorage::new(temp_dir.path().join("rss.db"), RetentionPolicy::default()).unwrap();
+        for (id, title) in [("first", "First"), ("second", "Second")] {
+            storage
+                .save_rss_feed(&RssFeed {
+                    id: id.to_string(),
+                    title: title.to_string(),
+                    link: format!("https://example.com/{}.xml", id),
+                    rss_entries: vec![
+                        RssEntry {
+                            id: "shared".to_string(),
+                            title: format!("{} shared", title),
+                            ..Default::default()
+                        },
+                        RssEntry {
+                            id: format!("{}-only", id),
+                            title: format!("{} only", title),
+                            content: format!("{} content", title),
+                            ..Default::default()
+                        },
+                    ],
+                    ..Default::default()
+                })
+                .unwrap();
+        }
+        (temp_dir, storage)
+    }
+
+    /// Runs a command, returning its output.
+    async fn run(storage: &mut LocalStorage, command: Command, json: bool) -> Result<String> {
+        run_command(
+            command,
+            storage,
+            SyncOptions::default(),
+            DEFAULT_DATE_FORMAT,
+            json,
+        )
+        .await
+    }
+
+    /// Tests finding feeds by ID, URL and title.
+    #[test]
+    fn test_find_rss_feed() {
+        let (_temp_dir, storage) = open_storage();
+        let rss_feeds = storage.load_rss_feeds().unwrap();
+        assert!(find_rss_feed(&rss_feeds, "first").unwrap().id == "first");
+        assert!(
+            find_rss_feed(&rss_feeds, "https://example.com/second.xml")
+                .unwrap()
+                .id
+                == "second"
+        );
+        assert!(find_rss_feed(&rss_feeds, "SECOND").unwrap().id == "second");
+        assert!(find_rss_feed(&rss_feeds, "third").is_err());
+    }
+
+    /// Tests listing unread entries and marking entries as read, where
+    /// an entry ID shared by feeds needs the feed to be given.
+    #[tokio::test]
+    async fn test_list_entries_and_mark_read() {
+        let (_temp_dir, mut storage) = open_storage();
+        let list_unread = || Command::ListEntries {
+            unread: true,
+            feed: None,
+        };
+        let output = run(&mut storage, list_unread(), true).await.unwrap();
+        let rss_entries: Vec<serde_json::Value> = serde_json::from_str(&output).unwrap();
+        assert!(rss_entries.len() == 4);
+
+        let mark_shared = |feed: Option<&str>| Command::MarkRead {
+            entry_ids: vec!["shared".to_string(), "first-only".to_string()],
+            feed: feed.map(str::to_string),
+            all: false,
+        };
+        assert!(run(&mut storage, mark_shared(None), false).await.is_err());
+        let output = run(&mut storage, mark_shared(Some("First")), false)
+            .await
+            .unwrap();
+        assert!(output == "Marked 2 entries as read");
+
+        let output = run(&mut storage, list_unread(), true).await.unwrap();
+        let rss_entries: Vec<serde_json::Value> = serde_json::from_str(&output).unwrap();
+        assert!(rss_entries.len() == 2);
+        assert!(rss_entries.iter().all(|e| e["feed_id"] == "second"));
+
+        let mark_all = Command::MarkRead {
+            entry_ids: Vec::new(),
+            feed: None,
+            all: true,
+        };
+        run(&mut storage, mark_all, false).await.unwrap();
+        let output = run(&mut storage, list_unread(), false).await.unwrap();
+        assert!(output.is_empty());
+    }
+
+    /// Tests that entries are listed with the configured date format.
+    #[tokio::test]
+    async fn test_list_entries_date_format() {
+        let (_temp_dir, mut storage) = open_storage();
+        let list_entries = Command::ListEntries {
+            unread: false,
+            feed: Some("First".to_string()),
+        };
+        let output = run_command(
+            list_entries,
+            &mut storage,
+            SyncOptions::default(),
+            "%d/%m/%Y",
+            false,
+        )
+        .await
+        .unwrap();
+        let published = RssEntry::default()
+            .published
+            .with_timezone(&Local)
+            .format("%d/%m/%Y")
+            .to_string();
+        assert!(output.lines().count() == 2);
+        assert!(output.lines().all(|line| line.starts_with(&published)));
+    }
+
+    /// Tests showing an entry.
+    #[tokio::test]
+    async fn test_show_rss_entry() {
+        let (_temp_dir, mut storage) = open_storage();
+        let show = Command::Show {
+            entry_id: "second-only".to_string(),
+            feed: None,
+        };
+        let output = run(&mut storage, show, false).await.unwrap();
+        assert!(output.starts_with("Second only\nFeed: Second\n"));
+        assert!(output.ends_with("\nSecond content"));
+
+        let show = Command::Show {
+            entry_id: "shared".to_string(),
+            feed: Some("second".to_string()),
+        };
+        let output = run(&mut storage, show, true).await.unwrap();
+        let rss_entry: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert!(rss_entry["title"] == "Second shared");
+        assert!(rss_entry["feed_id"] == "second");
+    }
+
+    /// Tests setting and clearing the retention overrides of a feed, and
+    /// that they're applied when entries expire.
+    #[tokio::test]
+    async fn test_set_retention() {
+        let (_temp_dir, mut storage) = open_storage();
+        let retention = |unread_ttl_days: Option<u32>, max_entries: Option<usize>, clear: bool| {
+            Command::Retention {
+                feed: "First".to_string(),
+                read_ttl_days: None,
+                unread_ttl_days,
+                max_entries,
+                clear,
+            }
+        };
+        let output = run(&mut storage, retention(None, Some(1), false), false)
+            .await
+            .unwrap();
+        assert!(output == "First: read entries global, unread entries global, max entries 1");
+        let rss_feeds = storage.load_rss_feeds().unwrap();
+        assert!(rss_feeds[0].retention_override.max_rss_entries == Some(1));
+        assert!(rss_feeds[1].retention_override == RetentionPolicy::default());
+        assert!(storage.expire_old_entries().unwrap() == 1);
+
+        let output = run(&mut storage, retention(Some(30), None, true), true)
+            .await
+            .unwrap();
+        let output: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert!(output["unread_ttl_days"] == 30);
+        assert!(output["max_entries"].is_null());
+        let rss_feeds = storage.load_rss_feeds().unwrap();
+        let retention_override = rss_feeds[0].retention_override;
+        assert!(retention_override.unread_ttl == Some(Duration::days(30)));
+        assert!(retention_override.max_rss_entries.is_none());
+    }
+
+    /// Tests that syncing fetches the full content of new entries of feeds
+    /// that ask for it, leaving entries that were already synced alone.
+    #[tokio::test]
+    async fn test_sync_fetches_full_content() {
+        let page_url = serve_with(|_| {
+            http_response(200, "", "<html><body><p>The full text.</p></body></html>")
+        })
+        .await;
+        let rss = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<rss version="2.0">
+  <channel>
+    <title>Full feed</title>
+    <item><guid>old</guid><title>Old</title><link>{0}/old</link></item>
+    <item><guid>new</guid><title>New</title><link>{0}/new</link></item>
+  </channel>
+</rss>"#,
+            page_url
+        );
+        let feed_url = serve_with(move |_| http_response(200, "", &rss)).await;
+        let (_temp_dir, mut storage) = open_storage();
+        for rss_feed in storage.load_rss_feeds().unwrap() {
+            storage.delete_rss_feed(&rss_feed.id).unwrap();
+        }
+        storage
+            .save_rss_feed(&RssFeed {
+                id: "full".to_string(),
+                title: "Full feed".to_string(),
+                link: format!("{}/feed.xml", feed_url),
+                rss_entries: vec![RssEntry {
+                    id: "old".to_string(),
+                    link: format!("{}/old", page_url),
+                    ..Default::default()
+                }],
+                fetch_full_content: true,
+                ..Default::default()
+            })
+            .unwrap();
+
+        let output = run(&mut storage, Command::Sync, false).await.unwrap();
+        assert!(output.ends_with("Synced 1 of 1 feeds, 1 new entries"));
+        let rss_feeds = storage.load_rss_feeds().unwrap();
+        let rss_entry = |id: &str| {
+            rss_feeds[0]
+                .rss_entries
+                .iter()
+                .find(|e| e.id == id)
+                .unwrap()
+                .clone()
+        };
+        assert!(rss_entry("old").full_content.is_none());
+        let full_content = rss_entry("new").full_content.unwrap();
+        assert!(full_content.html.contains("The full text."));
+    }
+
+    /// Tests removing a feed and listing the remaining feeds.
+    #[tokio::test]
+    async fn test_remove_rss_feed() {
+        let (_temp_dir, mut storage) = open_storage();
+        let remove = Command::Remove {
+            feed: "First".to_string(),
+        };
+        let output = run(&mut storage, remove, false).await.unwrap();
+        assert!(output == "Removed First");
+
+        let output = run(&mut storage, Command::ListFeeds, false).await.unwrap();
+        assert!(output == "Second (2 unread) https://example.com/second.xml");
+    }
+}
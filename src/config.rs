@@ -0,0 +1,278 @@
+//! The configuration file, which sets defaults that command line flags
+//! override.
+
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use anyhow::{Result, anyhow};
+use chrono::format::{Item, StrftimeItems};
+use directories::ProjectDirs;
+use ratatui::style::Color;
+use serde::{Deserialize, Deserializer};
+
+use crate::keymap::Keymap;
+use crate::theme::{Theme, ThemeName};
+
+/// The name of the configuration file within the configuration directory.
+const CONFIG_FILE_NAME: &str = "config.toml";
+
+/// Settings read from the configuration file. Every setting is optional.
+#[derive(Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// The directory the database is kept in.
+    pub db_path: Option<PathBuf>,
+    pub retention: RetentionConfig,
+    pub sync: SyncConfig,
+    pub ui: UiConfig,
+    pub theme: ThemeConfig,
+    /// The keys that perform each action, replacing the default keys of
+    /// the actions they list.
+    pub keys: Keymap,
+}
+
+/// How long entries are kept. See `RetentionPolicy`.
+#[derive(Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct RetentionConfig {
+    pub max_ttl_days: Option<usize>,
+    pub read_ttl_days: Option<usize>,
+    pub unread_ttl_days: Option<usize>,
+    pub max_entries_per_feed: Option<usize>,
+}
+
+/// How feeds are synced. See `SyncOptions`.
+#[derive(Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct SyncConfig {
+    pub refresh_minutes: Option<usize>,
+    pub concurrency: Option<usize>,
+    pub host_concurrency: Option<usize>,
+    pub user_agent: Option<String>,
+}
+
+/// How the TUI behaves and displays entries.
+#[derive(Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct UiConfig {
+    /// How many milliseconds pass between ticks, which drive the spinner
+    /// and background syncing.
+    pub tick_rate_ms: Option<u64>,
+    /// The `strftime` format dates are displayed in.
+    pub date_format: Option<String>,
+}
+
+/// The theme, and colors that replace the theme's. Colors are names like
+/// `"yellow"`, indices like `"214"` or hex codes like `"#ffb300"`.
+#[derive(Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThemeConfig {
+    /// The built-in theme the colors are applied on top of.
+    pub name: Option<ThemeName>,
+    #[serde(deserialize_with = "deserialize_color")]
+    pub text: Option<Color>,
+    #[serde(deserialize_with = "deserialize_color")]
+    pub background: Option<Color>,
+    #[serde(deserialize_with = "deserialize_color")]
+    pub border: Option<Color>,
+    #[serde(deserialize_with = "deserialize_color")]
+    pub title: Option<Color>,
+    #[serde(deserialize_with = "deserialize_color")]
+    pub muted: Option<Color>,
+    #[serde(deserialize_with = "deserialize_color")]
+    pub selection_fg: Option<Color>,
+    #[serde(deserialize_with = "deserialize_color")]
+    pub selection_bg: Option<Color>,
+    #[serde(deserialize_with = "deserialize_color")]
+    pub unread: Option<Color>,
+    #[serde(deserialize_with = "deserialize_color")]
+    pub accent: Option<Color>,
+    #[serde(deserialize_with = "deserialize_color")]
+    pub input: Option<Color>,
+    #[serde(deserialize_with = "deserialize_color")]
+    pub error: Option<Color>,
+    #[serde(deserialize_with = "deserialize_color")]
+    pub key: Option<Color>,
+    #[serde(deserialize_with = "deserialize_color")]
+    pub heading: Option<Color>,
+    #[serde(deserialize_with = "deserialize_color")]
+    pub link: Option<Color>,
+    #[serde(deserialize_with = "deserialize_color")]
+    pub code: Option<Color>,
+}
+
+impl ThemeConfig {
+    /// Retrieves the configured theme. Without a theme name, the
+    /// monochrome theme is used if `no_color` is set, and the dark theme
+    /// otherwise. Configured colors apply either way.
+    pub fn theme(&self, no_color: bool) -> Theme {
+        let name = match self.name {
+            Some(name) => name,
+            None if no_color => ThemeName::Monochrome,
+            None => ThemeName::Dark,
+        };
+        self.apply(Theme::builtin(name))
+    }
+
+    /// Applies the configured colors on top of a theme.
+    pub fn apply(&self, theme: Theme) -> Theme {
+        Theme {
+            text: self.text.unwrap_or(theme.text),
+            background: self.background.unwrap_or(theme.background),
+            border: self.border.unwrap_or(theme.border),
+            title: self.title.unwrap_or(theme.title),
+            muted: self.muted.unwrap_or(theme.muted),
+            selection_fg: self.selection_fg.unwrap_or(theme.selection_fg),
+            selection_bg: self.selection_bg.unwrap_or(theme.selection_bg),
+            unread: self.unread.unwrap_or(theme.unread),
+            accent: self.accent.unwrap_or(theme.accent),
+            input: self.input.unwrap_or(theme.input),
+            error: self.error.unwrap_or(theme.error),
+            key: self.key.unwrap_or(theme.key),
+            heading: self.heading.unwrap_or(theme.heading),
+            link: self.link.unwrap_or(theme.link),
+            code: self.code.unwrap_or(theme.code),
+        }
+    }
+}
+
+fn deserialize_color<'de, D>(deserializer: D) -> Result<Option<Color>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let color = String::deserialize(deserializer)?;
+    Color::from_str(&color)
+        .map(Some)
+        .map_err(|_| serde::de::Error::custom(format!("unknown color \"{}\"", color)))
+}
+
+impl Config {
+    /// Loads the configuration file at `path`, or at the default path if
+    /// no path is given. A missing file at the default path gives the
+    /// default configuration.
+    pub fn load(path: Option<PathBuf>) -> Result<Config> {
+        let path = match path {
+            Some(path) => path,
+            None => match default_config_path() {
+                Some(path) if path.exists() => path,
+                _ => return Ok(Config::default()),
+            },
+        };
+        let text = std::fs::read_to_string(&path)
+            .map_err(|e| anyhow!("Failed to read config file {}: {}", path.display(), e))?;
+        Config::parse(&text).map_err(|e| anyhow!("Invalid config file {}: {}", path.display(), e))
+    }
+
+    /// Parses and validates a configuration.
+    fn parse(text: &str) -> Result<Config> {
+        let config: Config = toml::from_str(text)?;
+        if config.ui.tick_rate_ms == Some(0) {
+            return Err(anyhow!("ui.tick_rate_ms must be greater than 0"));
+        }
+        if let Some(date_format) = &config.ui.date_format
+            && StrftimeItems::new(date_format).any(|item| item == Item::Error)
+        {
+            return Err(anyhow!(
+                "ui.date_format \"{}\" is not a valid format",
+                date_format
+            ));
+        }
+        if config.sync.concurrency == Some(0) || config.sync.host_concurrency == Some(0) {
+            return Err(anyhow!("sync concurrency must be greater than 0"));
+        }
+        Ok(config)
+    }
+}
+
+/// Retrieves the path of the configuration file within the default
+/// configuration directory.
+pub fn default_config_path() -> Option<PathBuf> {
+    ProjectDirs::from("com", "trevorbonas", "pequod-reader")
+        .map(|dirs| dirs.config_dir().join(CONFIG_FILE_NAME))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tests parsing a configuration that sets every section.
+    #[test]
+    fn test_parse_config() {
+        let config = Config::parse(
+            r##"
+db_path = "/tmp/pequod"
+
+[retention]
+max_ttl_days = 10
+max_entries_per_feed = 50
+
+[sync]
+refresh_minutes = 30
+user_agent = "pequod-test"
+
+[ui]
+tick_rate_ms = 250
+date_format = "%d/%m %H:%M"
+
+[theme]
+unread = "yellow"
+key = "#00ff00"
+"##,
+        )
+        .unwrap();
+
+        assert!(config.db_path == Some(PathBuf::from("/tmp/pequod")));
+        assert!(config.retention.max_ttl_days == Some(10));
+        assert!(config.retention.read_ttl_days.is_none());
+        assert!(config.sync.refresh_minutes == Some(30));
+        assert!(config.sync.user_agent.as_deref() == Some("pequod-test"));
+        assert!(config.ui.tick_rate_ms == Some(250));
+        let theme = config.theme.apply(Theme::default());
+        assert!(theme.unread == Color::Yellow);
+        assert!(theme.key == Color::Rgb(0, 255, 0));
+        assert!(theme.error == Theme::default().error);
+        assert!(Config::parse("").unwrap() == Config::default());
+    }
+
+    /// Tests that the theme is picked by name or by `NO_COLOR`, with
+    /// configured colors applied on top.
+    #[test]
+    fn test_theme_config() {
+        let config = Config::parse("[theme]\nname = \"light\"\nunread = \"red\"").unwrap();
+        let theme = config.theme.theme(true);
+        assert!(theme.unread == Color::Red);
+        assert!(theme.key == Theme::builtin(ThemeName::Light).key);
+
+        let config = Config::parse("[theme]\nkey = \"green\"").unwrap();
+        let theme = config.theme.theme(true);
+        assert!(theme.key == Color::Green);
+        assert!(theme.unread == Color::Reset);
+        assert!(config.theme.theme(false).unread == Theme::default().unread);
+        assert!(Config::parse("[theme]\nname = \"neon\"").is_err());
+    }
+
+    /// Tests that invalid configurations are rejected with errors naming
+    /// the problem.
+    #[test]
+    fn test_parse_invalid_config() {
+        let error = |text: &str| Config::parse(text).unwrap_err().to_string();
+        assert!(error("[sync]\nrefresh = 5").contains("unknown field `refresh`"));
+        assert!(error("[retention]\nmax_ttl_days = \"five\"").contains("invalid type"));
+        assert!(error("[theme]\nunread = \"orangeish\"").contains("unknown color \"orangeish\""));
+        assert!(error("[ui]\ndate_format = \"%Q\"").contains("not a valid format"));
+        assert!(error("[ui]\ntick_rate_ms = 0").contains("greater than 0"));
+    }
+
+    /// Tests that a missing configuration file is only an error when its
+    /// path was given.
+    #[test]
+    fn test_load_missing_config() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("config.toml");
+        let error = Config::load(Some(path.clone())).unwrap_err().to_string();
+        assert!(error.contains("Failed to read config file"));
+
+        std::fs::write(&path, "[ui]\ntick_rate_ms = 50").unwrap();
+        assert!(Config::load(Some(path)).unwrap().ui.tick_rate_ms == Some(50));
+    }
+}
@@ -0,0 +1,216 @@
+//! Discovery of the feeds offered by a website.
+
+use feed_rs::model::Feed;
+use reqwest::Url;
+use scraper::{Html, Selector};
+
+/// Paths commonly used for feeds, tried when a website doesn't link to
+/// any feeds.
+pub const COMMON_FEED_PATHS: &[&str] = &[
+    "/feed",
+    "/rss",
+    "/rss.xml",
+    "/atom.xml",
+    "/feed.xml",
+    "/index.xml",
+    "/feed.json",
+];
+
+/// The feed media types a website may link to.
+const FEED_TYPES: &[&str] = &[
+    "application/rss+xml",
+    "application/atom+xml",
+    "application/feed+json",
+];
+
+/// A feed offered by a website.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DiscoveredFeed {
+    pub title: String,
+    pub url: String,
+}
+
+/// The result of looking for feeds at a URL.
+pub enum Discovery {
+    /// The URL is a feed.
+    Feed(Box<Feed>, String),
+    /// The URL is a website offering these feeds.
+    Candidates(Vec<DiscoveredFeed>),
+}
+
+/// The file extensions feeds are commonly served with.
+const FEED_EXTENSIONS: &[&str] = &[".rss", ".atom", ".xml", ".rdf"];
+
+/// Whether a URL looks like it's a feed, going by its path.
+pub fn is_feed_url(url: &str) -> bool {
+    let Ok(url) = Url::parse(url) else {
+        return false;
+    };
+    let path = url.path().trim_end_matches('/').to_lowercase();
+    COMMON_FEED_PATHS.iter().any(|p| path.ends_with(p))
+        || FEED_EXTENSIONS.iter().any(|e| path.ends_with(e))
+}
+
+/// Finds the feeds a HTML document links to with
+/// `<link rel="alternate">` tags. Relative links are resolved against
+/// `base_url`.
+pub fn find_feed_links(html: &str, base_url: &Url) -> Vec<DiscoveredFeed> {
+    let document = Html::parse_document(html);
+    let selector = Selector::parse("link[rel][href][type]").expect("selector is valid");
+    let mut discovered_feeds: Vec<DiscoveredFeed> = Vec::new();
+    for link in document.select(&selector) {
+        let element = link.value();
+        let is_alternate = element
+            .attr("rel")
+            .unwrap_or_default()
+            .split_whitespace()
+            .any(|rel| rel.eq_ignore_ascii_case("alternate"));
+        let feed_type = element.attr("type").unwrap_or_default().trim();
+        if !is_alternate || !FEED_TYPES.iter().any(|t| t.eq_ignore_ascii_case(feed_type)) {
+            continue;
+        }
+        let Some(url) = element
+            .attr("href")
+            .and_then(|href| base_url.join(href.trim()).ok())
+        else {
+            continue;
+        };
+        if discovered_feeds.iter().any(|f| f.url == url.as_str()) {
+            continue;
+        }
+        discovered_feeds.push(DiscoveredFeed {
+            title: element
+                .attr("title")
+                .map(|t| t.trim().to_string())
+                .filter(|t| !t.is_empty())
+                .unwrap_or_else(|| url.to_string()),
+            url: url.to_string(),
+        });
+    }
+    discovered_feeds
+}
+
+/// Fetches and parses the feed at a URL.
+pub async fn fetch_feed(client: &reqwest::Client, url: &str) -> Result<Box<Feed>, String> {
+    let body = client
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to add feed: {}", e))?
+        .text()
+        .await
+        .map_err(|e| format!("Failed to add feed: {}", e))?;
+    let feed = feed_rs::parser::parse(body.as_bytes())
+        .map_err(|e| format!("Failed to add feed: {}", e))?;
+    Ok(Box::new(feed))
+}
+
+/// Looks for feeds at a URL. If the URL is a feed, the feed is returned.
+/// If it's a website, the feeds it links to are returned, or, if it
+/// links to none, the feeds found at common feed paths.
+pub async fn discover_feeds(client: &reqwest::Client, url: &str) -> Result<Discovery, String> {
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to add feed: {}", e))?;
+    let base_url = response.url().clone();
+    let is_html = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.contains("html"));
+    let body = response
+        .text()
+        .await
+        .map_err(|e| format!("Failed to add feed: {}", e))?;
+
+    let parse_error = match feed_rs::parser::parse(body.as_bytes()) {
+        Ok(feed) => return Ok(Discovery::Feed(Box::new(feed), url.to_string())),
+        Err(err) => format!("Failed to add feed: {}", err),
+    };
+    if !is_html {
+        return Err(parse_error);
+    }
+
+    let mut discovered_feeds = find_feed_links(&body, &base_url);
+    if discovered_feeds.is_empty() {
+        for path in COMMON_FEED_PATHS {
+            let Ok(feed_url) = base_url.join(path) else {
+                continue;
+            };
+            if let Ok(feed) = fetch_feed(client, feed_url.as_str()).await {
+                discovered_feeds.push(DiscoveredFeed {
+                    title: feed
+                        .title
+                        .map(|t| t.content)
+                        .unwrap_or_else(|| feed_url.to_string()),
+                    url: feed_url.to_string(),
+                });
+            }
+        }
+    }
+    if discovered_feeds.is_empty() {
+        return Err(format!("Failed to add feed: no feeds found at {}", url));
+    }
+    Ok(Discovery::Candidates(discovered_feeds))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tests that only alternate links to feeds are found, with relative
+    /// links resolved.
+    #[test]
+    fn test_find_feed_links() {
+        let html = r#"<!DOCTYPE html>
+<html>
+  <head>
+    <link rel="stylesheet" type="text/css" href="/style.css">
+    <link rel="alternate" type="application/rss+xml" title="Posts" href="/posts.xml">
+    <link rel="Alternate" type="application/atom+xml" href="https://other.example.com/atom">
+    <link rel="alternate" type="application/feed+json" title=" JSON " href="feed.json">
+    <link rel="alternate" type="application/rss+xml" title="Duplicate" href="/posts.xml">
+    <link rel="alternate" hreflang="fr" type="text/html" href="/fr/">
+  </head>
+  <body></body>
+</html>"#;
+        let base_url = Url::parse("https://example.com/blog/").unwrap();
+        let discovered_feeds = find_feed_links(html, &base_url);
+        assert!(
+            discovered_feeds
+                == vec![
+                    DiscoveredFeed {
+                        title: "Posts".to_string(),
+                        url: "https://example.com/posts.xml".to_string(),
+                    },
+                    DiscoveredFeed {
+                        title: "https://other.example.com/atom".to_string(),
+                        url: "https://other.example.com/atom".to_string(),
+                    },
+                    DiscoveredFeed {
+                        title: "JSON".to_string(),
+                        url: "https://example.com/blog/feed.json".to_string(),
+                    },
+                ]
+        );
+    }
+
+    /// Tests that URLs are recognized as feeds by their paths.
+    #[test]
+    fn test_is_feed_url() {
+        assert!(is_feed_url("https://example.com/feed/"));
+        assert!(is_feed_url("https://example.com/blog/index.xml"));
+        assert!(is_feed_url("https://example.com/posts.RSS?page=2"));
+        assert!(!is_feed_url("https://example.com/posts/feeding-cats"));
+        assert!(!is_feed_url("/feed.xml"));
+    }
+
+    /// Tests that a document without feed links has no feeds.
+    #[test]
+    fn test_find_feed_links_none() {
+        let base_url = Url::parse("https://example.com").unwrap();
+        assert!(find_feed_links("<html><body>Hello</body></html>", &base_url).is_empty());
+    }
+}
@@ -0,0 +1,385 @@
+//! Extraction of the main article from a web page, for entries whose
+//! feeds only provide a summary. Elements are scored the way
+//! Readability scores them: by the paragraphs they contain, their class
+//! names and how much of their text is links.
+
+use std::collections::HashMap;
+
+use ego_tree::{NodeId, NodeRef};
+use scraper::{ElementRef, Html, Node, Selector};
+
+/// Elements that never belong to an article.
+const REMOVED_TAGS: &[&str] = &[
+    "aside", "button", "dialog", "footer", "form", "header", "iframe", "input", "menu", "nav",
+    "noscript", "object", "script", "select", "style", "svg", "template", "textarea",
+];
+
+/// Elements that are kept even when their class names look unlikely.
+const KEPT_TAGS: &[&str] = &["html", "body", "article", "main"];
+
+/// The beginnings of class name and id words that mark an element as
+/// unlikely to be part of an article.
+const NEGATIVE_WORDS: &[&str] = &[
+    "advert",
+    "banner",
+    "breadcrumb",
+    "comment",
+    "consent",
+    "cookie",
+    "footer",
+    "masthead",
+    "menu",
+    "modal",
+    "nav",
+    "newsletter",
+    "pagination",
+    "popup",
+    "promo",
+    "related",
+    "share",
+    "sharing",
+    "sidebar",
+    "social",
+    "sponsor",
+    "subscribe",
+    "trending",
+    "widget",
+];
+
+/// The beginnings of class name and id words that mark an element as
+/// likely to hold an article.
+const POSITIVE_WORDS: &[&str] = &[
+    "article", "blog", "body", "content", "entry", "post", "story", "text",
+];
+
+/// The elements whose text is scored.
+const PARAGRAPH_TAGS: &[&str] = &["p", "pre", "blockquote", "td"];
+
+/// Elements that make a `<div>` a container rather than a paragraph.
+const BLOCK_TAGS: &[&str] = &[
+    "article",
+    "blockquote",
+    "div",
+    "dl",
+    "figure",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "table",
+    "ul",
+];
+
+/// The attributes kept on extracted elements.
+const KEPT_ATTRIBUTES: &[&str] = &["href", "src", "alt", "title"];
+
+/// Elements without closing tags.
+const VOID_TAGS: &[&str] = &["br", "hr", "img", "wbr"];
+
+/// Paragraphs shorter than this many characters aren't scored.
+const MIN_PARAGRAPH_LENGTH: usize = 25;
+
+/// Articles shorter than this many characters are discarded.
+const MIN_ARTICLE_LENGTH: usize = 250;
+
+/// Extracts the main article from a HTML document as a HTML fragment,
+/// without menus, banners, comments and the like. Returns `None` when no
+/// article is found, in which case the whole page should be used.
+pub fn extract_article(html: &str) -> Option<String> {
+    let document = Html::parse_document(html);
+    let scores = score_candidates(&document);
+    let (top_id, top_score) = scores
+        .iter()
+        .map(|(id, score)| {
+            let element = element(&document, *id);
+            (*id, score * (1.0 - link_density(element)))
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1))?;
+    let top = element(&document, top_id);
+
+    // Articles are sometimes split across siblings, so siblings that
+    // scored well enough are kept with the top candidate.
+    let threshold = (top_score * 0.2).max(10.0);
+    let top_class = top.value().attr("class").unwrap_or_default();
+    let mut article = String::new();
+    let siblings: Vec<ElementRef> = match top.parent().and_then(ElementRef::wrap) {
+        Some(parent) => parent.child_elements().collect(),
+        None => vec![top],
+    };
+    for sibling in siblings {
+        let mut keep = sibling.id() == top_id;
+        if !keep && let Some(score) = scores.get(&sibling.id()) {
+            let mut score = score * (1.0 - link_density(sibling));
+            if !top_class.is_empty() && sibling.value().attr("class") == Some(top_class) {
+                score += top_score * 0.2;
+            }
+            keep = score >= threshold;
+        }
+        if !keep && sibling.value().name() == "p" && !is_removed(sibling) {
+            let length = text_length(sibling);
+            let density = link_density(sibling);
+            keep = (length > 80 && density < 0.25) || (length > 0 && density == 0.0);
+        }
+        if keep {
+            write_clean_html(*sibling, &mut article);
+        }
+    }
+
+    let text_length = Html::parse_fragment(&article)
+        .root_element()
+        .text()
+        .map(|t| t.trim().chars().count())
+        .sum::<usize>();
+    (text_length >= MIN_ARTICLE_LENGTH).then_some(article)
+}
+
+/// Scores the parents and grandparents of a document's paragraphs.
+fn score_candidates(document: &Html) -> HashMap<NodeId, f64> {
+    let selector = Selector::parse("p, pre, blockquote, td, div").expect("selector is valid");
+    let mut scores: HashMap<NodeId, f64> = HashMap::new();
+    for paragraph in document.select(&selector) {
+        let name = paragraph.value().name();
+        let is_paragraph = PARAGRAPH_TAGS.contains(&name)
+            || (name == "div"
+                && !paragraph
+                    .descendent_elements()
+                    .skip(1)
+                    .any(|e| BLOCK_TAGS.contains(&e.value().name())));
+        if !is_paragraph || is_within_removed(paragraph) {
+            continue;
+        }
+        let text = paragraph.text().collect::<String>();
+        let length = text.trim().chars().count();
+        if length < MIN_PARAGRAPH_LENGTH {
+            continue;
+        }
+        let score = 1.0 + text.matches(',').count() as f64 + (length / 100).min(3) as f64;
+
+        let ancestors = paragraph.ancestors().filter_map(ElementRef::wrap).take(2);
+        for (level, ancestor) in ancestors.enumerate() {
+            *scores
+                .entry(ancestor.id())
+                .or_insert_with(|| initial_score(ancestor)) += score / (level + 1) as f64;
+        }
+    }
+    scores
+}
+
+/// The score of a candidate before its paragraphs are counted.
+fn initial_score(element: ElementRef) -> f64 {
+    let tag_score = match element.value().name() {
+        "div" | "article" => 5.0,
+        "pre" | "td" | "blockquote" => 3.0,
+        "address" | "ol" | "ul" | "dl" | "dd" | "dt" | "li" | "form" => -3.0,
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "th" => -5.0,
+        _ => 0.0,
+    };
+    let mut class_score = 0.0;
+    if has_word(element, NEGATIVE_WORDS) {
+        class_score -= 25.0;
+    }
+    if has_word(element, POSITIVE_WORDS) {
+        class_score += 25.0;
+    }
+    tag_score + class_score
+}
+
+/// Whether one of the words in an element's class names or id begins
+/// with one of `words`.
+fn has_word(element: ElementRef, words: &[&str]) -> bool {
+    let value = element.value();
+    let names = format!(
+        "{} {}",
+        value.attr("class").unwrap_or_default(),
+        value.attr("id").unwrap_or_default()
+    )
+    .to_lowercase();
+    names
+        .split(|c: char| !c.is_alphanumeric())
+        .any(|name| words.iter().any(|word| name.starts_with(word)))
+}
+
+/// Whether an element should be left out of an article.
+fn is_removed(element: ElementRef) -> bool {
+    let name = element.value().name();
+    if REMOVED_TAGS.contains(&name) {
+        return true;
+    }
+    !KEPT_TAGS.contains(&name)
+        && has_word(element, NEGATIVE_WORDS)
+        && !has_word(element, POSITIVE_WORDS)
+}
+
+/// Whether an element, or one of its ancestors, should be left out of an
+/// article.
+fn is_within_removed(element: ElementRef) -> bool {
+    is_removed(element)
+        || element
+            .ancestors()
+            .filter_map(ElementRef::wrap)
+            .any(is_removed)
+}
+
+/// The number of characters in an element's text.
+fn text_length(element: ElementRef) -> usize {
+    element.text().map(|t| t.trim().chars().count()).sum()
+}
+
+/// The share of an element's text that is within links.
+fn link_density(element: ElementRef) -> f64 {
+    let length = text_length(element);
+    if length == 0 {
+        return 0.0;
+    }
+    let selector = Selector::parse("a").expect("selector is valid");
+    let link_length: usize = element.select(&selector).map(text_length).sum();
+    link_length as f64 / length as f64
+}
+
+/// Retrieves an element of a document by its id.
+fn element(document: &Html, id: NodeId) -> ElementRef<'_> {
+    document
+        .tree
+        .get(id)
+        .and_then(ElementRef::wrap)
+        .expect("scored nodes are elements")
+}
+
+/// Writes the HTML of a node, leaving out removed elements, comments and
+/// most attributes.
+fn write_clean_html(node: NodeRef<Node>, html: &mut String) {
+    match node.value() {
+        Node::Text(text) => html.push_str(&escape(text)),
+        Node::Element(value) => {
+            let Some(element) = ElementRef::wrap(node) else {
+                return;
+            };
+            if is_removed(element) {
+                return;
+            }
+            let name = value.name();
+            html.push('<');
+            html.push_str(name);
+            for attribute in KEPT_ATTRIBUTES {
+                if let Some(value) = value.attr(attribute) {
+                    html.push_str(&format!(" {}=\"{}\"", attribute, escape(value)));
+                }
+            }
+            html.push('>');
+            if VOID_TAGS.contains(&name) {
+                return;
+            }
+            for child in node.children() {
+                write_clean_html(child, html);
+            }
+            html.push_str(&format!("</{}>", name));
+        }
+        _ => {}
+    }
+}
+
+/// Escapes text for use in HTML.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Extracts the article of a fixture, as plain text.
+    fn extract_text(html: &str) -> Option<String> {
+        extract_article(html)
+            .map(|article| html2text::from_read(article.as_bytes(), usize::MAX).unwrap_or_default())
+    }
+
+    /// Tests that a blog post is extracted without its cookie banner,
+    /// navigation, share buttons, comments, sidebar and footer.
+    #[test]
+    fn test_extract_blog_post() {
+        let text = extract_text(include_str!("../tests/fixtures/extract/blog_post.html")).unwrap();
+        assert!(text.contains("Why I Switched Back to a Terminal Feed Reader"));
+        assert!(text.contains("For years, I read my feeds in a web app"));
+        assert!(text.contains("The best interface for reading text"));
+        assert!(text.contains("Everything stored locally"));
+        assert!(text.contains("setup I described last year"));
+        assert!(text.contains("with one small change to the refresh interval."));
+        for unwanted in [
+            "Accept cookies",
+            "Archive",
+            "Share on Social",
+            "3 Comments",
+            "Great post!",
+            "Popular posts",
+            "Get new posts in your inbox",
+            "All rights reserved",
+            "dataLayer",
+        ] {
+            assert!(!text.contains(unwanted), "{unwanted:?} was extracted");
+        }
+    }
+
+    /// Tests that a news article is extracted without its menus, ads,
+    /// related links and promotions.
+    #[test]
+    fn test_extract_news_article() {
+        let article =
+            extract_article(include_str!("../tests/fixtures/extract/news_article.html")).unwrap();
+        assert!(article.contains(
+            r#"<img src="/images/bike-lanes.jpg" alt="A cyclist rides along Main Street">"#
+        ));
+        assert!(!article.contains("class="));
+        let text = html2text::from_read(article.as_bytes(), usize::MAX).unwrap();
+        assert!(text.contains("The city council voted 7 to 2 on Tuesday night"));
+        assert!(text.contains("where the first lane will be built."));
+        assert!(text.contains("said council member Luis Ortega"));
+        assert!(text.contains("from a state transportation grant and the city’s capital budget."));
+        for unwanted in [
+            "Subscribe for $1",
+            "Obituaries",
+            "ads.example.com",
+            "Local",
+            "Parking rates to rise downtown",
+            "Facebook",
+            "morning newsletter",
+            "Trending now",
+            "privacy policy",
+        ] {
+            assert!(!text.contains(unwanted), "{unwanted:?} was extracted");
+        }
+    }
+
+    /// Tests that an article split across sibling elements is extracted
+    /// whole.
+    #[test]
+    fn test_extract_split_article() {
+        let text =
+            extract_text(include_str!("../tests/fixtures/extract/split_article.html")).unwrap();
+        assert!(text.contains("In the first part, we looked at why caches go stale"));
+        assert!(text.contains("Versioned keys avoid the race entirely."));
+        assert!(text.contains(r#"format!("user:{}:v{}", id, version);"#));
+        assert!(text.contains("Next time, we will measure how much memory"));
+        assert!(!text.contains("contact"));
+        assert!(!text.contains("About me"));
+    }
+
+    /// Tests that pages without an article aren't extracted, so the
+    /// whole page is used instead.
+    #[test]
+    fn test_extract_without_article() {
+        assert!(
+            extract_article(include_str!("../tests/fixtures/extract/link_index.html")).is_none()
+        );
+        assert!(extract_article("").is_none());
+        assert!(extract_article("<p>Too short to be an article.</p>").is_none());
+    }
+}
@@ -0,0 +1,631 @@
+//! Keybindings, which map the keys pressed in each mode to the actions
+//! they perform.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+
+/// Where keys are pressed. Each mode has its own bindings.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Mode {
+    /// The list of feeds.
+    Feeds,
+    /// An entry being read.
+    Entry,
+    /// The entries matching a search.
+    SearchResults,
+    /// Any popup that doesn't accept user input.
+    Popup,
+    /// A popup that accepts user input, where typed characters edit the
+    /// input.
+    Input,
+}
+
+impl Mode {
+    fn name(&self) -> &'static str {
+        match self {
+            Mode::Feeds => "feeds",
+            Mode::Entry => "entry",
+            Mode::SearchResults => "search_results",
+            Mode::Popup => "popup",
+            Mode::Input => "input",
+        }
+    }
+}
+
+/// Something a key does.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    ScrollDown,
+    ScrollUp,
+    HalfPageDown,
+    HalfPageUp,
+    Top,
+    Bottom,
+    Select,
+    Collapse,
+    AddFeed,
+    Delete,
+    Sync,
+    CancelSync,
+    NewFolder,
+    RenameFolder,
+    MoveFeed,
+    ImportOpml,
+    ExportOpml,
+    Search,
+    ToggleStar,
+    ToggleRead,
+    MarkRead,
+    MarkAllRead,
+    MarkOlderRead,
+    NextUnreadFeed,
+    PreviousUnreadFeed,
+    NextUnread,
+    PreviousUnread,
+    FetchFullContent,
+    ToggleFullContent,
+    ToggleFetchFullContent,
+    OpenInBrowser,
+    Links,
+    CopyLink,
+    Confirm,
+    Deny,
+    Help,
+    Back,
+    Quit,
+}
+
+impl Action {
+    /// The label the action is shown with in help popups and
+    /// instructions.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Action::ScrollDown => "↓",
+            Action::ScrollUp => "↑",
+            Action::HalfPageDown => "Half page down",
+            Action::HalfPageUp => "Half page up",
+            Action::Top => "Top",
+            Action::Bottom => "Bottom",
+            Action::Select => "Select",
+            Action::Collapse => "Collapse",
+            Action::AddFeed => "Add",
+            Action::Delete => "Delete",
+            Action::Sync => "Sync",
+            Action::CancelSync => "Cancel sync",
+            Action::NewFolder => "New folder",
+            Action::RenameFolder => "Rename folder",
+            Action::MoveFeed => "Move to folder",
+            Action::ImportOpml => "Import OPML",
+            Action::ExportOpml => "Export OPML",
+            Action::Search => "Search",
+            Action::ToggleStar => "Star",
+            Action::ToggleRead => "Toggle read",
+            Action::MarkRead => "Mark read",
+            Action::MarkAllRead => "Mark all read",
+            Action::MarkOlderRead => "Mark older read",
+            Action::NextUnreadFeed => "Next unread feed",
+            Action::PreviousUnreadFeed => "Previous unread feed",
+            Action::NextUnread => "Next unread",
+            Action::PreviousUnread => "Previous unread",
+            Action::FetchFullContent => "Fetch",
+            Action::ToggleFullContent => "Full text",
+            Action::ToggleFetchFullContent => "Always fetch full text",
+            Action::OpenInBrowser => "Open",
+            Action::Links => "Links",
+            Action::CopyLink => "Copy",
+            Action::Confirm => "Yes",
+            Action::Deny => "No",
+            Action::Help => "Help",
+            Action::Back => "Back",
+            Action::Quit => "Quit",
+        }
+    }
+}
+
+/// An action and the keys that perform it.
+type Binding = (Action, &'static [&'static str]);
+
+/// The default bindings of each mode, in the order they're listed in help
+/// popups.
+const DEFAULT_BINDINGS: &[(Mode, &[Binding])] = &[
+    (
+        Mode::Feeds,
+        &[
+            (Action::ScrollDown, &["j", "Down"]),
+            (Action::ScrollUp, &["k", "Up"]),
+            (Action::AddFeed, &["a"]),
+            (Action::Help, &["h"]),
+            (Action::Quit, &["q", "Esc"]),
+            (Action::Select, &["Enter"]),
+            (Action::Delete, &["d"]),
+            (Action::Sync, &["s"]),
+            (Action::CancelSync, &["x"]),
+            (Action::ImportOpml, &["i"]),
+            (Action::ExportOpml, &["e"]),
+            (Action::Search, &["/"]),
+            (Action::NewFolder, &["n"]),
+            (Action::RenameFolder, &["r"]),
+            (Action::MoveFeed, &["m"]),
+            (Action::ToggleFetchFullContent, &["F"]),
+            (Action::Bottom, &["G"]),
+            (Action::Top, &["gg"]),
+            (Action::Collapse, &["c"]),
+            (Action::ToggleStar, &["*"]),
+            (Action::ToggleRead, &["t"]),
+            (Action::MarkRead, &["A"]),
+            (Action::MarkAllRead, &["C"]),
+            (Action::MarkOlderRead, &["O"]),
+            (Action::NextUnreadFeed, &["]"]),
+            (Action::PreviousUnreadFeed, &["["]),
+            (Action::HalfPageUp, &["ctrl+u"]),
+            (Action::HalfPageDown, &["ctrl+d"]),
+        ],
+    ),
+    (
+        Mode::Entry,
+        &[
+            (Action::ScrollDown, &["j", "Down"]),
+            (Action::ScrollUp, &["k", "Up"]),
+            (Action::Help, &["h"]),
+            (Action::Back, &["q", "Esc"]),
+            (Action::FetchFullContent, &["f"]),
+            (Action::ToggleFullContent, &["v"]),
+            (Action::OpenInBrowser, &["o"]),
+            (Action::Links, &["l"]),
+            (Action::CancelSync, &["x"]),
+            (Action::Bottom, &["G", "End"]),
+            (Action::Top, &["gg"]),
+            (Action::ToggleStar, &["*"]),
+            (Action::ToggleRead, &["t"]),
+            (Action::NextUnread, &["n"]),
+            (Action::PreviousUnread, &["p"]),
+            (Action::HalfPageUp, &["ctrl+u"]),
+            (Action::HalfPageDown, &["ctrl+d"]),
+        ],
+    ),
+    (
+        Mode::SearchResults,
+        &[
+            (Action::ScrollDown, &["j", "Down"]),
+            (Action::ScrollUp, &["k", "Up"]),
+            (Action::Select, &["Enter"]),
+            (Action::Search, &["/"]),
+            (Action::CancelSync, &["x"]),
+            (Action::Back, &["q", "Esc"]),
+        ],
+    ),
+    (
+        Mode::Popup,
+        &[
+            (Action::ScrollDown, &["j", "Down"]),
+            (Action::ScrollUp, &["k", "Up"]),
+            (Action::Select, &["Enter"]),
+            (Action::Confirm, &["y"]),
+            (Action::Deny, &["n"]),
+            (Action::OpenInBrowser, &["o"]),
+            (Action::CopyLink, &["c"]),
+            (Action::AddFeed, &["a"]),
+            (Action::Back, &["q", "Esc"]),
+        ],
+    ),
+    (
+        Mode::Input,
+        &[(Action::Select, &["Enter"]), (Action::Back, &["Esc"])],
+    ),
+];
+
+/// A single key press, like `j` or `ctrl+d`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KeyPress {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl From<KeyEvent> for KeyPress {
+    fn from(key: KeyEvent) -> Self {
+        // Shift is already part of the character typed, like `G`.
+        let mut modifiers = key.modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT);
+        if !matches!(key.code, KeyCode::Char(_)) {
+            modifiers |= key.modifiers & KeyModifiers::SHIFT;
+        }
+        KeyPress {
+            code: key.code,
+            modifiers,
+        }
+    }
+}
+
+/// Named keys, matched case-insensitively.
+const NAMED_KEYS: &[(&str, KeyCode)] = &[
+    ("Enter", KeyCode::Enter),
+    ("Esc", KeyCode::Esc),
+    ("Up", KeyCode::Up),
+    ("Down", KeyCode::Down),
+    ("Left", KeyCode::Left),
+    ("Right", KeyCode::Right),
+    ("Home", KeyCode::Home),
+    ("End", KeyCode::End),
+    ("PageUp", KeyCode::PageUp),
+    ("PageDown", KeyCode::PageDown),
+    ("Tab", KeyCode::Tab),
+    ("Backspace", KeyCode::Backspace),
+    ("Delete", KeyCode::Delete),
+    ("Space", KeyCode::Char(' ')),
+];
+
+impl fmt::Display for KeyPress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "ctrl + ")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "alt + ")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            write!(f, "shift + ")?;
+        }
+        match NAMED_KEYS.iter().find(|(_, code)| *code == self.code) {
+            Some((name, _)) => write!(f, "{}", name),
+            None => match self.code {
+                KeyCode::Char(c) => write!(f, "{}", c),
+                KeyCode::F(n) => write!(f, "F{}", n),
+                code => write!(f, "{:?}", code),
+            },
+        }
+    }
+}
+
+/// The keys pressed one after another to perform an action, like `gg`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeySequence(pub Vec<KeyPress>);
+
+impl KeySequence {
+    /// Parses a key sequence. Sequences are either a single key, like
+    /// `Enter`, `ctrl+d` or `F1`, or characters typed one after
+    /// another, like `gg`. Shift with a letter is the uppercase letter, so
+    /// `shift+a` is `A`.
+    pub fn parse(keys: &str) -> Result<KeySequence, String> {
+        let invalid = || format!("invalid key \"{}\"", keys);
+        if keys.is_empty() {
+            return Err(invalid());
+        }
+        // A lone `+` is a key rather than a modifier separator.
+        let (modifier_names, key) = match keys.rsplit_once('+') {
+            Some((modifiers, key)) if !modifiers.is_empty() && !key.is_empty() => {
+                (modifiers.split('+').collect(), key)
+            }
+            _ => (Vec::new(), keys),
+        };
+        let mut modifiers = KeyModifiers::NONE;
+        for modifier_name in modifier_names {
+            modifiers |= match modifier_name.trim().to_lowercase().as_str() {
+                "ctrl" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return Err(invalid()),
+            };
+        }
+
+        let named = NAMED_KEYS
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(key))
+            .map(|(_, code)| *code);
+        let function = key
+            .strip_prefix(['F', 'f'])
+            .and_then(|n| n.parse::<u8>().ok())
+            .filter(|n| (1..=12).contains(n))
+            .map(KeyCode::F);
+        if let Some(code) = named.or(function) {
+            return Ok(KeySequence(vec![KeyPress { code, modifiers }]));
+        }
+        // Characters typed with modifiers can't be part of a sequence.
+        if !modifiers.is_empty() && key.chars().count() > 1 {
+            return Err(invalid());
+        }
+        // Shift changes the character typed rather than being reported,
+        // which only has a known result for letters.
+        if modifiers.contains(KeyModifiers::SHIFT) && !key.chars().all(|c| c.is_ascii_alphabetic())
+        {
+            return Err(format!(
+                "invalid key \"{}\": bind the character typed with shift instead",
+                keys
+            ));
+        }
+        Ok(KeySequence(
+            key.chars()
+                .map(|c| KeyPress {
+                    code: KeyCode::Char(if modifiers.contains(KeyModifiers::SHIFT) {
+                        c.to_ascii_uppercase()
+                    } else if modifiers.is_empty() {
+                        c
+                    } else {
+                        c.to_ascii_lowercase()
+                    }),
+                    modifiers: modifiers - KeyModifiers::SHIFT,
+                })
+                .collect(),
+        ))
+    }
+}
+
+impl fmt::Display for KeySequence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for key_press in &self.0 {
+            write!(f, "{}", key_press)?;
+        }
+        Ok(())
+    }
+}
+
+/// Keys given for an action in the configuration file, either a single
+/// key sequence or a list of them.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ConfiguredKeys {
+    One(String),
+    Many(Vec<String>),
+}
+
+/// The bindings of every mode.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Keymap {
+    modes: HashMap<Mode, Vec<(Action, Vec<KeySequence>)>>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let modes = DEFAULT_BINDINGS
+            .iter()
+            .map(|(mode, bindings)| {
+                let bindings = bindings
+                    .iter()
+                    .map(|(action, keys)| {
+                        let keys = keys
+                            .iter()
+                            .map(|k| KeySequence::parse(k).expect("default key is invalid"))
+                            .collect();
+                        (*action, keys)
+                    })
+                    .collect();
+                (*mode, bindings)
+            })
+            .collect();
+        Keymap { modes }
+    }
+}
+
+impl<'de> Deserialize<'de> for Keymap {
+    /// Reads the bindings that replace the defaults, like
+    /// `feeds.sync = ["s", "ctrl+r"]`. Each mode only accepts the actions
+    /// that can be performed in it.
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let configured =
+            HashMap::<Mode, HashMap<Action, ConfiguredKeys>>::deserialize(deserializer)?;
+        let mut keymap = Keymap::default();
+        for (mode, actions) in configured {
+            for (action, keys) in actions {
+                let keys = match keys {
+                    ConfiguredKeys::One(key) => vec![key],
+                    ConfiguredKeys::Many(keys) => keys,
+                };
+                let keys = keys
+                    .iter()
+                    .map(|k| KeySequence::parse(k))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(serde::de::Error::custom)?;
+                let binding = keymap
+                    .bindings_mut(mode)
+                    .iter_mut()
+                    .find(|(a, _)| *a == action)
+                    .ok_or_else(|| {
+                        serde::de::Error::custom(format!(
+                            "{:?} can't be bound in {}",
+                            action,
+                            mode.name()
+                        ))
+                    })?;
+                binding.1 = keys;
+            }
+        }
+        for (mode, bindings) in &keymap.modes {
+            for (index, (action, keys)) in bindings.iter().enumerate() {
+                for (other_action, other_keys) in &bindings[index + 1..] {
+                    if let Some(key) = keys.iter().find(|k| other_keys.contains(k)) {
+                        return Err(serde::de::Error::custom(format!(
+                            "\"{}\" is bound to both {:?} and {:?} in {}",
+                            key,
+                            action,
+                            other_action,
+                            mode.name()
+                        )));
+                    }
+                }
+            }
+        }
+        Ok(keymap)
+    }
+}
+
+impl Keymap {
+    /// The bindings of a mode, in the order they're listed in help popups.
+    pub fn bindings(&self, mode: Mode) -> &[(Action, Vec<KeySequence>)] {
+        self.modes.get(&mode).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    fn bindings_mut(&mut self, mode: Mode) -> &mut Vec<(Action, Vec<KeySequence>)> {
+        self.modes.entry(mode).or_default()
+    }
+
+    /// The first key sequence bound to an action, as it's shown to the
+    /// user. Empty if the action isn't bound.
+    pub fn key(&self, mode: Mode, action: Action) -> String {
+        self.bindings(mode)
+            .iter()
+            .find(|(a, _)| *a == action)
+            .and_then(|(_, keys)| keys.first())
+            .map(|k| k.to_string())
+            .unwrap_or_default()
+    }
+
+    /// Resolves a key press to the actions it performs, given the keys
+    /// pressed before it that haven't performed an action yet. Returns no
+    /// actions while `pending` is the start of a longer sequence, like the
+    /// first `g` of `gg`. A sequence that's the start of a longer one only
+    /// performs its action if the key after it doesn't continue the longer
+    /// one, in which case that key's action is performed after it.
+    pub fn resolve(&self, mode: Mode, pending: &mut Vec<KeyPress>, key: KeyEvent) -> Vec<Action> {
+        pending.push(KeyPress::from(key));
+        let mut actions = Vec::new();
+        loop {
+            let (exact, longer) = self.lookup(mode, pending);
+            if longer {
+                return actions;
+            }
+            if exact.is_some() || pending.len() == 1 {
+                pending.clear();
+                actions.extend(exact);
+                return actions;
+            }
+            // The keys before this one didn't lead anywhere longer, so they
+            // perform their own action, if any, and this key starts over.
+            let previous = &pending[..pending.len() - 1];
+            actions.extend(self.lookup(mode, previous).0);
+            pending.drain(..pending.len() - 1);
+        }
+    }
+
+    /// Finds the action bound to exactly `keys`, and whether `keys` is the
+    /// start of a longer sequence.
+    fn lookup(&self, mode: Mode, keys: &[KeyPress]) -> (Option<Action>, bool) {
+        let mut exact = None;
+        let mut longer = false;
+        for (action, sequences) in self.bindings(mode) {
+            for sequence in sequences {
+                if sequence.0 == keys {
+                    exact = Some(*action);
+                } else if sequence.0.starts_with(keys) {
+                    longer = true;
+                }
+            }
+        }
+        (exact, longer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    /// Tests parsing and displaying single keys, modified keys and
+    /// sequences.
+    #[test]
+    fn test_parse_key_sequence() {
+        let parse = |keys: &str| KeySequence::parse(keys).unwrap();
+        assert!(parse("gg").0.len() == 2);
+        assert!(parse("gg").to_string() == "gg");
+        assert!(parse("enter").0 == vec![KeyPress::from(key(KeyCode::Enter))]);
+        assert!(parse("Ctrl+D").to_string() == "ctrl + d");
+        assert!(parse("+").to_string() == "+");
+        assert!(parse("F5").0[0].code == KeyCode::F(5));
+        assert!(parse("shift+a").0 == vec![KeyPress::from(key(KeyCode::Char('A')))]);
+        assert!(parse("ctrl+shift+a").to_string() == "ctrl + A");
+        assert!(KeySequence::parse("shift+1").is_err());
+        assert!(KeySequence::parse("hyper+x").is_err());
+        assert!(KeySequence::parse("ctrl+gg").is_err());
+        assert!(KeySequence::parse("").is_err());
+    }
+
+    /// Tests that sequences wait for their next key, and that a key that
+    /// doesn't continue a sequence performs its own action.
+    #[test]
+    fn test_resolve_sequences() {
+        let keymap = Keymap::default();
+        let mut pending = Vec::new();
+        let g = key(KeyCode::Char('g'));
+        assert!(keymap.resolve(Mode::Feeds, &mut pending, g).is_empty());
+        assert!(keymap.resolve(Mode::Feeds, &mut pending, g) == vec![Action::Top]);
+        assert!(pending.is_empty());
+
+        assert!(keymap.resolve(Mode::Feeds, &mut pending, g).is_empty());
+        let j = key(KeyCode::Char('j'));
+        assert!(keymap.resolve(Mode::Feeds, &mut pending, j) == vec![Action::ScrollDown]);
+
+        let shift_g = KeyEvent::new(KeyCode::Char('G'), KeyModifiers::SHIFT);
+        assert!(keymap.resolve(Mode::Feeds, &mut pending, shift_g) == vec![Action::Bottom]);
+        let ctrl_d = KeyEvent::new(KeyCode::Char('d'), KeyModifiers::CONTROL);
+        assert!(keymap.resolve(Mode::Feeds, &mut pending, ctrl_d) == vec![Action::HalfPageDown]);
+        assert!(
+            keymap
+                .resolve(Mode::Feeds, &mut pending, key(KeyCode::Char('z')))
+                .is_empty()
+        );
+        assert!(
+            keymap.resolve(Mode::Entry, &mut pending, key(KeyCode::End)) == vec![Action::Bottom]
+        );
+    }
+
+    /// Tests that a sequence that's the start of a longer one performs its
+    /// action when the next key doesn't continue the longer one, followed
+    /// by the action of that key.
+    #[test]
+    fn test_resolve_shorter_sequence() {
+        let keymap: Keymap = toml::from_str("feeds.sync = \"g\"").unwrap();
+        let mut pending = Vec::new();
+        let g = key(KeyCode::Char('g'));
+        assert!(keymap.resolve(Mode::Feeds, &mut pending, g).is_empty());
+        assert!(keymap.resolve(Mode::Feeds, &mut pending, g) == vec![Action::Top]);
+
+        assert!(keymap.resolve(Mode::Feeds, &mut pending, g).is_empty());
+        let j = key(KeyCode::Char('j'));
+        assert!(
+            keymap.resolve(Mode::Feeds, &mut pending, j) == vec![Action::Sync, Action::ScrollDown]
+        );
+        assert!(pending.is_empty());
+    }
+
+    /// Tests that configured bindings replace the defaults, and that
+    /// actions can only be bound in modes they're performed in.
+    #[test]
+    fn test_deserialize_keymap() {
+        let keymap: Keymap = toml::from_str(
+            r#"
+feeds.sync = "S"
+feeds.scroll_down = ["ctrl+n", "Down"]
+"#,
+        )
+        .unwrap();
+        assert!(keymap.key(Mode::Feeds, Action::Sync) == "S");
+        assert!(keymap.key(Mode::Feeds, Action::ScrollDown) == "ctrl + n");
+        assert!(keymap.key(Mode::Entry, Action::ScrollDown) == "j");
+        let mut pending = Vec::new();
+        assert!(
+            keymap
+                .resolve(Mode::Feeds, &mut pending, key(KeyCode::Char('s')))
+                .is_empty()
+        );
+
+        let error = toml::from_str::<Keymap>("entry.sync = \"s\"").unwrap_err();
+        assert!(error.to_string().contains("Sync can't be bound in entry"));
+        assert!(toml::from_str::<Keymap>("feeds.fly = \"f\"").is_err());
+
+        assert!(toml::from_str::<Keymap>("").is_ok());
+        let error = toml::from_str::<Keymap>("feeds.sync = \"j\"").unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .contains("\"j\" is bound to both ScrollDown and Sync in feeds")
+        );
+    }
+}
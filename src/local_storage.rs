@@ -1,70 +1,131 @@
 //! Local storage that contains RSS feed data.
 
+use std::ops::Range;
 use std::path::PathBuf;
 
 use chrono::{DateTime, Duration, Utc};
-use rusqlite::{Connection, params};
+use rusqlite::{Connection, Transaction, params};
 
-use crate::app::{RssEntry, RssFeed};
+use crate::app::{Folder, FullContent, RssEntry, RssFeed};
+use crate::migrations;
+
+/// Rules for how long entries are kept before they expire. Starred
+/// entries never expire.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct RetentionPolicy {
+    /// How long read entries are kept. `None` keeps them forever.
+    pub read_ttl: Option<Duration>,
+    /// How long unread entries are kept. `None` keeps them forever.
+    pub unread_ttl: Option<Duration>,
+    /// The most entries kept per feed. `None` keeps any number.
+    pub max_rss_entries: Option<usize>,
+}
+
+impl RetentionPolicy {
+    /// Combines two policies, taking each rule from `self` if it is set
+    /// and from `fallback` otherwise. Used to apply a feed's overrides on
+    /// top of the global policy.
+    pub fn or(self, fallback: RetentionPolicy) -> RetentionPolicy {
+        RetentionPolicy {
+            read_ttl: self.read_ttl.or(fallback.read_ttl),
+            unread_ttl: self.unread_ttl.or(fallback.unread_ttl),
+            max_rss_entries: self.max_rss_entries.or(fallback.max_rss_entries),
+        }
+    }
+}
+
+/// An entry matching a search.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SearchResult {
+    pub rss_feed_id: String,
+    pub rss_entry_id: String,
+    pub title: String,
+    /// The byte ranges of `title` that matched the search.
+    pub title_matches: Vec<Range<usize>>,
+}
+
+/// Marks the start of a match in highlighted search results.
+const MATCH_START: char = '\u{1}';
+/// Marks the end of a match in highlighted search results.
+const MATCH_END: char = '\u{2}';
+
+/// Converts a search into an FTS5 query that matches entries containing
+/// every word, or words starting with it. Words are quoted so that FTS5
+/// syntax in the search is matched literally. Returns `None` if the
+/// search has no words.
+fn fts_query(search: &str) -> Option<String> {
+    let words: Vec<String> = search
+        .split_whitespace()
+        .map(|word| format!("\"{}\"*", word.replace('"', "\"\"")))
+        .collect();
+    (!words.is_empty()).then(|| words.join(" "))
+}
+
+/// Splits a title highlighted with `MATCH_START` and `MATCH_END` into the
+/// plain title and the byte ranges of its matches.
+fn parse_highlighted_title(highlighted_title: &str) -> (String, Vec<Range<usize>>) {
+    let mut title = String::with_capacity(highlighted_title.len());
+    let mut title_matches = Vec::new();
+    let mut match_start = 0;
+    for ch in highlighted_title.chars() {
+        match ch {
+            MATCH_START => match_start = title.len(),
+            MATCH_END => title_matches.push(match_start..title.len()),
+            _ => title.push(ch),
+        }
+    }
+    (title, title_matches)
+}
 
 /// Handles saving to and loading from a local
 /// SQLite database.
 pub struct LocalStorage {
     pub conn: Connection,
-    pub max_ttl: Duration,
+    /// The retention policy for feeds without their own overrides.
+    pub retention_policy: RetentionPolicy,
 }
 
 impl LocalStorage {
-    pub fn new(db_path: PathBuf, max_ttl: Duration) -> rusqlite::Result<Self> {
-        let conn = Connection::open(db_path.clone())?;
-        Self::init(&conn)?;
-        Ok(Self { conn, max_ttl })
-    }
-
-    /// Creates tables if they don't already exist.
-    fn init(conn: &Connection) -> rusqlite::Result<()> {
-        conn.execute_batch(
-            r#"
-            PRAGMA foreign_keys = ON;
-
-            CREATE TABLE IF NOT EXISTS rss_feeds (
-                id TEXT PRIMARY KEY,
-                title TEXT NOT NULL,
-                link TEXT NOT NULL,
-                expanded INTEGER NOT NULL
-            );
-
-            CREATE TABLE IF NOT EXISTS rss_entries (
-                id TEXT PRIMARY KEY,
-                rss_feed_id TEXT NOT NULL,
-                title TEXT NOT NULL,
-                content TEXT NOT NULL,
-                content_total_lines INTEGER NOT NULL,
-                link TEXT NOT NULL,
-                published TEXT NOT NULL,
-                read INTEGER NOT NULL,
-                authors TEXT,
-                FOREIGN KEY(rss_feed_id) REFERENCES rss_feeds(id) ON DELETE CASCADE
-            )
-            "#,
-        )?;
-        Ok(())
+    /// Opens the database, bringing its schema up to date.
+    pub fn new(db_path: PathBuf, retention_policy: RetentionPolicy) -> anyhow::Result<Self> {
+        let mut conn = Connection::open(db_path.clone())?;
+        conn.execute_batch("PRAGMA foreign_keys = ON;")?;
+        migrations::migrate(&mut conn)?;
+        Ok(Self {
+            conn,
+            retention_policy,
+        })
     }
 
-    /// Persists a single RSS entry.
-    pub fn save_rss_entry(
-        &mut self,
+    /// Inserts or updates an RSS entry as part of a transaction.
+    fn insert_rss_entry(
+        transaction: &Transaction,
         rss_feed_id: &String,
         rss_entry: &RssEntry,
     ) -> rusqlite::Result<()> {
-        let transaction = self.conn.transaction()?;
         let authors_json =
             serde_json::to_string(&rss_entry.authors).expect("authors failed to serialize");
+        // An upsert is used rather than a replace, since the rows a replace
+        // deletes don't fire delete triggers, leaving the search index stale.
         transaction.execute(
-            "INSERT OR REPLACE INTO rss_entries
+            "INSERT INTO rss_entries
             (id, rss_feed_id, title, authors, content, content_total_lines,
-             link, published, read)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+             link, published, read, starred, content_hash, content_html,
+             full_content_html, full_content_fetched)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)
+            ON CONFLICT(rss_feed_id, id) DO UPDATE SET
+                title = excluded.title,
+                authors = excluded.authors,
+                content = excluded.content,
+                content_total_lines = excluded.content_total_lines,
+                link = excluded.link,
+                published = excluded.published,
+                read = excluded.read,
+                starred = excluded.starred,
+                content_hash = excluded.content_hash,
+                content_html = excluded.content_html,
+                full_content_html = excluded.full_content_html,
+                full_content_fetched = excluded.full_content_fetched",
             params![
                 rss_entry.id,
                 rss_feed_id,
@@ -74,98 +135,231 @@ impl LocalStorage {
                 rss_entry.content_total_lines as i64,
                 rss_entry.link,
                 rss_entry.published.to_rfc3339(),
-                rss_entry.read as i32
+                rss_entry.read as i32,
+                rss_entry.starred as i32,
+                rss_entry.content_hash,
+                rss_entry.content_html,
+                rss_entry.full_content.as_ref().map(|c| &c.html),
+                rss_entry
+                    .full_content
+                    .as_ref()
+                    .map(|c| c.fetched.to_rfc3339()),
             ],
         )?;
+        Ok(())
+    }
+
+    /// Persists a single RSS entry.
+    pub fn save_rss_entry(
+        &mut self,
+        rss_feed_id: &String,
+        rss_entry: &RssEntry,
+    ) -> rusqlite::Result<()> {
+        let transaction = self.conn.transaction()?;
+        Self::insert_rss_entry(&transaction, rss_feed_id, rss_entry)?;
+        transaction.commit()?;
+        Ok(())
+    }
+
+    /// Sets whether entries are read in a single transaction. Each entry
+    /// is a feed ID and an entry ID. Returns the number of entries
+    /// changed.
+    pub fn set_rss_entries_read(
+        &mut self,
+        rss_entries: &[(String, String)],
+        read: bool,
+    ) -> rusqlite::Result<usize> {
+        let transaction = self.conn.transaction()?;
+        let mut affected = 0;
+        {
+            let mut statement = transaction.prepare(
+                "UPDATE rss_entries SET read = ?1
+                WHERE rss_feed_id = ?2 AND id = ?3 AND read != ?1",
+            )?;
+            for (rss_feed_id, rss_entry_id) in rss_entries {
+                affected += statement.execute(params![read as i32, rss_feed_id, rss_entry_id])?;
+            }
+        }
         transaction.commit()?;
+        Ok(affected)
+    }
+
+    /// Retrieves the ID of the folder with a name, creating the folder if
+    /// it doesn't exist.
+    fn folder_id(conn: &Connection, name: &str) -> rusqlite::Result<i64> {
+        conn.execute(
+            "INSERT INTO folders (name) VALUES (?1) ON CONFLICT(name) DO NOTHING",
+            params![name],
+        )?;
+        conn.query_row(
+            "SELECT id FROM folders WHERE name = ?1",
+            params![name],
+            |row| row.get(0),
+        )
+    }
+
+    /// Creates a folder, unless a folder with the same name exists.
+    pub fn create_folder(&self, name: &str) -> rusqlite::Result<()> {
+        Self::folder_id(&self.conn, name)?;
+        Ok(())
+    }
+
+    /// Saves a folder's name and whether it's expanded.
+    pub fn save_folder(&self, folder: &Folder) -> rusqlite::Result<()> {
+        self.conn.execute(
+            "UPDATE folders SET name = ?1, expanded = ?2 WHERE id = ?3",
+            params![folder.name, folder.expanded as i32, folder.id],
+        )?;
         Ok(())
     }
 
-    /// Saves an RSS feed and all of its entries.
+    /// Deletes a folder. The feeds within it are kept, without a folder.
+    pub fn delete_folder(&self, folder_id: i64) -> rusqlite::Result<usize> {
+        self.conn
+            .execute("DELETE FROM folders WHERE id = ?1", params![folder_id])
+    }
+
+    /// Loads all folders, ordered by name.
+    pub fn load_folders(&self) -> rusqlite::Result<Vec<Folder>> {
+        let mut statement = self
+            .conn
+            .prepare("SELECT id, name, expanded FROM folders ORDER BY name ASC")?;
+        statement
+            .query_map([], |row| {
+                Ok(Folder {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    expanded: row.get::<_, i32>(2)? != 0,
+                })
+            })?
+            .collect()
+    }
+
+    /// Saves an RSS feed and all of its entries. The feed's folder is
+    /// created if it doesn't exist.
     pub fn save_rss_feed(&mut self, rss_feed: &RssFeed) -> rusqlite::Result<()> {
         let transaction = self.conn.transaction()?;
+        let folder_id = match &rss_feed.folder {
+            Some(folder) => Some(Self::folder_id(&transaction, folder)?),
+            None => None,
+        };
+        // An upsert is used rather than a replace, since replacing a feed
+        // would cascade and delete all of its entries.
+        let retention_override = rss_feed.retention_override;
         transaction.execute(
-            "INSERT OR REPLACE INTO rss_feeds (id, title, link, expanded)
-            VALUES(?1, ?2, ?3, ?4)",
+            "INSERT INTO rss_feeds
+            (id, title, link, expanded, etag, last_modified, last_fetched,
+             retention_read_days, retention_unread_days, retention_max_entries,
+             folder_id, html_url, refresh_interval_secs, fetch_full_content)
+            VALUES(?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)
+            ON CONFLICT(id) DO UPDATE SET
+                title = excluded.title,
+                link = excluded.link,
+                expanded = excluded.expanded,
+                etag = excluded.etag,
+                last_modified = excluded.last_modified,
+                last_fetched = excluded.last_fetched,
+                retention_read_days = excluded.retention_read_days,
+                retention_unread_days = excluded.retention_unread_days,
+                retention_max_entries = excluded.retention_max_entries,
+                folder_id = excluded.folder_id,
+                html_url = excluded.html_url,
+                refresh_interval_secs = excluded.refresh_interval_secs,
+                fetch_full_content = excluded.fetch_full_content",
             params![
                 rss_feed.id,
                 rss_feed.title,
                 rss_feed.link,
-                rss_feed.expanded as i32
+                rss_feed.expanded as i32,
+                rss_feed.etag,
+                rss_feed.last_modified,
+                rss_feed.last_fetched.map(|t| t.to_rfc3339()),
+                retention_override.read_ttl.map(|ttl| ttl.num_days()),
+                retention_override.unread_ttl.map(|ttl| ttl.num_days()),
+                retention_override.max_rss_entries.map(|max| max as i64),
+                folder_id,
+                rss_feed.html_url,
+                rss_feed.refresh_interval.map(|i| i.num_seconds()),
+                rss_feed.fetch_full_content as i32,
             ],
         )?;
 
         for rss_entry in &rss_feed.rss_entries {
-            let authors_json =
-                serde_json::to_string(&rss_entry.authors).expect("authors failed to serialize");
-            transaction.execute(
-                "INSERT OR REPLACE INTO rss_entries
-                (id, rss_feed_id, title, authors, content, content_total_lines,
-                 link, published, read)
-                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
-                params![
-                    rss_entry.id,
-                    rss_feed.id,
-                    rss_entry.title,
-                    authors_json,
-                    rss_entry.content,
-                    rss_entry.content_total_lines as i64,
-                    rss_entry.link,
-                    rss_entry.published.to_rfc3339(),
-                    rss_entry.read as i32
-                ],
-            )?;
+            Self::insert_rss_entry(&transaction, &rss_feed.id, rss_entry)?;
         }
 
         transaction.commit()?;
         Ok(())
     }
 
-    /// Saves multiple RSS feeds and all of their entries.
-    pub fn save_rss_feeds(&mut self, rss_feeds: &Vec<RssFeed>) -> rusqlite::Result<()> {
-        for rss_feed in rss_feeds {
-            self.save_rss_feed(rss_feed)?;
-        }
-        Ok(())
-    }
-
     /// Loads all available RSS feeds and translates rows to RssFeeds.
     pub fn load_rss_feeds(&self) -> rusqlite::Result<Vec<RssFeed>> {
-        let mut rss_feed_statement = self
-            .conn
-            .prepare("SELECT id, title, link, expanded FROM rss_feeds ORDER BY title ASC")?;
+        let mut rss_feed_statement = self.conn.prepare(
+            "SELECT rss_feeds.id, title, link, rss_feeds.expanded, etag, last_modified,
+                 last_fetched, retention_read_days, retention_unread_days,
+                 retention_max_entries, folders.name, html_url, refresh_interval_secs,
+                 fetch_full_content
+            FROM rss_feeds LEFT JOIN folders ON folders.id = rss_feeds.folder_id
+            ORDER BY title ASC",
+        )?;
         let rss_feed_rows = rss_feed_statement.query_map([], |row| {
-            Ok((
-                row.get::<_, String>(0)?,
-                row.get::<_, String>(1)?,
-                row.get::<_, String>(2)?,
-                row.get::<_, i32>(3)?,
-            ))
+            let last_fetched: Option<String> = row.get(6)?;
+            let last_fetched = last_fetched
+                .and_then(|t| DateTime::parse_from_rfc3339(&t).ok())
+                .map(|t| t.with_timezone(&Utc));
+            Ok(RssFeed {
+                id: row.get(0)?,
+                title: row.get(1)?,
+                link: row.get(2)?,
+                rss_entries: Vec::new(),
+                expanded: row.get::<_, i32>(3)? != 0,
+                etag: row.get(4)?,
+                last_modified: row.get(5)?,
+                last_fetched,
+                retention_override: Self::retention_policy_from_row(row, 7)?,
+                folder: row.get(10)?,
+                html_url: row.get(11)?,
+                refresh_interval: row.get::<_, Option<i64>>(12)?.map(Duration::seconds),
+                fetch_full_content: row.get::<_, i32>(13)? != 0,
+                ..Default::default()
+            })
         })?;
 
         let mut rss_feeds: Vec<RssFeed> = Vec::new();
 
         for rss_feed_row in rss_feed_rows {
-            let (id, title, link, expanded) = rss_feed_row?;
-            let rss_entries = self.load_rss_entries_for_rss_feed(&id)?;
-            rss_feeds.push(RssFeed {
-                id,
-                title,
-                link,
-                rss_entries,
-                expanded: expanded != 0,
-            });
+            let mut rss_feed = rss_feed_row?;
+            rss_feed.rss_entries = self.load_rss_entries_for_rss_feed(&rss_feed.id)?;
+            rss_feeds.push(rss_feed);
         }
         Ok(rss_feeds)
     }
 
+    /// Reads a retention policy stored as three columns, in days, days,
+    /// and entries, starting at column `start`.
+    fn retention_policy_from_row(
+        row: &rusqlite::Row,
+        start: usize,
+    ) -> rusqlite::Result<RetentionPolicy> {
+        Ok(RetentionPolicy {
+            read_ttl: row.get::<_, Option<i64>>(start)?.map(Duration::days),
+            unread_ttl: row.get::<_, Option<i64>>(start + 1)?.map(Duration::days),
+            max_rss_entries: row
+                .get::<_, Option<i64>>(start + 2)?
+                .map(|max| max as usize),
+        })
+    }
+
     pub fn load_rss_entries_for_rss_feed(
         &self,
         rss_feed_id: &String,
     ) -> rusqlite::Result<Vec<RssEntry>> {
         let mut statement = self.conn.prepare(
             "SELECT id, title, authors, content, content_total_lines, link,
-                 published, read FROM rss_entries WHERE rss_feed_id = ?1 ORDER BY published DESC",
+                 published, read, starred, content_hash, content_html,
+                 full_content_html, full_content_fetched
+                 FROM rss_entries WHERE rss_feed_id = ?1
+                 ORDER BY published DESC",
         )?;
 
         let rows = statement.query_map([rss_feed_id], |row| {
@@ -176,16 +370,29 @@ impl LocalStorage {
                 .ok()
                 .map(|dt| dt.with_timezone(&Utc))
                 .unwrap_or_default();
+            let full_content_html: Option<String> = row.get(11)?;
+            let full_content_fetched: Option<String> = row.get(12)?;
+            let full_content = full_content_html.map(|html| FullContent {
+                html,
+                fetched: full_content_fetched
+                    .and_then(|t| DateTime::parse_from_rfc3339(&t).ok())
+                    .map(|t| t.with_timezone(&Utc))
+                    .unwrap_or_default(),
+            });
 
             Ok(RssEntry {
                 id: row.get(0)?,
                 title: row.get(1)?,
                 authors,
                 content: row.get(3)?,
+                content_html: row.get(10)?,
                 content_total_lines: row.get::<_, i64>(4)? as usize,
                 link: row.get(5)?,
                 published,
                 read: row.get::<_, i32>(7)? != 0,
+                starred: row.get::<_, i32>(8)? != 0,
+                content_hash: row.get(9)?,
+                full_content,
             })
         })?;
 
@@ -197,6 +404,45 @@ impl LocalStorage {
         Ok(rss_entries)
     }
 
+    /// Searches the titles, authors and content of every stored entry,
+    /// returning at most `limit` entries, best matches first.
+    pub fn search_rss_entries(
+        &self,
+        search: &str,
+        limit: usize,
+    ) -> rusqlite::Result<Vec<SearchResult>> {
+        let Some(query) = fts_query(search) else {
+            return Ok(Vec::new());
+        };
+        let mut statement = self.conn.prepare(
+            "SELECT rss_entries.rss_feed_id, rss_entries.id,
+                 highlight(rss_entries_fts, 0, ?2, ?3)
+            FROM rss_entries_fts
+            JOIN rss_entries ON rss_entries.rowid = rss_entries_fts.rowid
+            WHERE rss_entries_fts MATCH ?1
+            ORDER BY rank LIMIT ?4",
+        )?;
+        let rows = statement.query_map(
+            params![
+                query,
+                MATCH_START.to_string(),
+                MATCH_END.to_string(),
+                limit as i64
+            ],
+            |row| {
+                let highlighted_title: String = row.get(2)?;
+                let (title, title_matches) = parse_highlighted_title(&highlighted_title);
+                Ok(SearchResult {
+                    rss_feed_id: row.get(0)?,
+                    rss_entry_id: row.get(1)?,
+                    title,
+                    title_matches,
+                })
+            },
+        )?;
+        rows.collect()
+    }
+
     /// Deletes an RSS feed.
     pub fn delete_rss_feed(&self, rss_feed_id: &String) -> rusqlite::Result<usize> {
         let affected = self
@@ -205,13 +451,319 @@ impl LocalStorage {
         Ok(affected)
     }
 
-    /// Removes old entries if they are unread.
-    pub fn expire_old_entries(&self) -> rusqlite::Result<usize> {
-        let cutoff = Utc::now() - self.max_ttl;
-        let affected = self.conn.execute(
-            "DELETE FROM entries WHERE published < ?1",
-            [cutoff.to_rfc3339()],
-        )?;
+    /// Removes entries that have expired under the retention policy,
+    /// applying each feed's overrides. Starred entries are never removed.
+    /// Returns the number of entries removed.
+    pub fn expire_old_entries(&mut self) -> rusqlite::Result<usize> {
+        let now = Utc::now();
+        let transaction = self.conn.transaction()?;
+        let retention_policies: Vec<(String, RetentionPolicy)> = transaction
+            .prepare(
+                "SELECT id, retention_read_days, retention_unread_days, retention_max_entries
+                FROM rss_feeds",
+            )?
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    Self::retention_policy_from_row(row, 1)?,
+                ))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        let mut affected = 0;
+        for (rss_feed_id, retention_override) in retention_policies {
+            let retention_policy = retention_override.or(self.retention_policy);
+            if let Some(read_ttl) = retention_policy.read_ttl {
+                affected += transaction.execute(
+                    "DELETE FROM rss_entries
+                    WHERE rss_feed_id = ?1 AND starred = 0 AND read = 1 AND published < ?2",
+                    params![rss_feed_id, (now - read_ttl).to_rfc3339()],
+                )?;
+            }
+            if let Some(unread_ttl) = retention_policy.unread_ttl {
+                affected += transaction.execute(
+                    "DELETE FROM rss_entries
+                    WHERE rss_feed_id = ?1 AND starred = 0 AND read = 0 AND published < ?2",
+                    params![rss_feed_id, (now - unread_ttl).to_rfc3339()],
+                )?;
+            }
+            if let Some(max_rss_entries) = retention_policy.max_rss_entries {
+                affected += transaction.execute(
+                    "DELETE FROM rss_entries
+                    WHERE rss_feed_id = ?1 AND starred = 0 AND rowid NOT IN (
+                        SELECT rowid FROM rss_entries
+                        WHERE rss_feed_id = ?1 AND starred = 0
+                        ORDER BY published DESC LIMIT ?2
+                    )",
+                    params![rss_feed_id, max_rss_entries as i64],
+                )?;
+            }
+        }
+        transaction.commit()?;
         Ok(affected)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::{TempDir, tempdir};
+
+    /// Opens storage backed by a database in a new temporary directory.
+    /// The directory is removed when the returned `TempDir` is dropped.
+    fn open_storage(retention_policy: RetentionPolicy) -> (TempDir, LocalStorage) {
+        let temp_dir = tempdir().unwrap();
+        let storage = LocalStorage::new(temp_dir.path().join("rss.db"), retention_policy).unwrap();
+        (temp_dir, storage)
+    }
+
+    /// Creates an RSS entry published a number of days ago.
+    fn test_rss_entry(id: &str, days_old: i64, read: bool, starred: bool) -> RssEntry {
+        RssEntry {
+            id: id.to_string(),
+            title: format!("{} title", id),
+            published: Utc::now() - Duration::days(days_old),
+            read,
+            starred,
+            ..Default::default()
+        }
+    }
+
+    /// Retrieves the IDs of a feed's stored entries, newest first.
+    fn stored_rss_entry_ids(storage: &LocalStorage, rss_feed_id: &str) -> Vec<String> {
+        storage
+            .load_rss_entries_for_rss_feed(&rss_feed_id.to_string())
+            .unwrap()
+            .into_iter()
+            .map(|e| e.id)
+            .collect()
+    }
+
+    /// Tests that read and unread entries expire after their own TTLs.
+    #[test]
+    fn test_expire_read_and_unread_entries() {
+        let (_temp_dir, mut storage) = open_storage(RetentionPolicy {
+            read_ttl: Some(Duration::days(2)),
+            unread_ttl: Some(Duration::days(10)),
+            max_rss_entries: None,
+        });
+        storage
+            .save_rss_feed(&RssFeed {
+                id: "feed".to_string(),
+                rss_entries: vec![
+                    test_rss_entry("new-read", 1, true, false),
+                    test_rss_entry("old-read", 5, true, false),
+                    test_rss_entry("old-unread", 5, false, false),
+                    test_rss_entry("ancient-unread", 20, false, false),
+                ],
+                ..Default::default()
+            })
+            .unwrap();
+
+        let affected = storage.expire_old_entries().unwrap();
+
+        assert!(affected == 2);
+        assert!(stored_rss_entry_ids(&storage, "feed") == vec!["new-read", "old-unread"]);
+    }
+
+    /// Tests that saving a feed creates its folder, that renaming a folder
+    /// keeps its feeds in it, and that deleting a folder keeps its feeds.
+    #[test]
+    fn test_folders() {
+        let (_temp_dir, mut storage) = open_storage(RetentionPolicy::default());
+        storage.create_folder("Empty").unwrap();
+        storage
+            .save_rss_feed(&RssFeed {
+                id: "feed".to_string(),
+                folder: Some("News".to_string()),
+                ..Default::default()
+            })
+            .unwrap();
+        let mut folders = storage.load_folders().unwrap();
+        assert!(folders.iter().map(|f| f.name.as_str()).collect::<Vec<_>>() == ["Empty", "News"]);
+
+        folders[1].name = "Renamed".to_string();
+        folders[1].expanded = true;
+        storage.save_folder(&folders[1]).unwrap();
+        assert!(storage.load_folders().unwrap()[1] == folders[1]);
+        let rss_feeds = storage.load_rss_feeds().unwrap();
+        assert!(rss_feeds[0].folder.as_deref() == Some("Renamed"));
+
+        storage.delete_folder(folders[1].id).unwrap();
+        let rss_feeds = storage.load_rss_feeds().unwrap();
+        assert!(rss_feeds.len() == 1);
+        assert!(rss_feeds[0].folder.is_none());
+    }
+
+    /// Tests that starred entries never expire, however old they are.
+    #[test]
+    fn test_expire_keeps_starred_entries() {
+        let (_temp_dir, mut storage) = open_storage(RetentionPolicy {
+            read_ttl: Some(Duration::days(1)),
+            unread_ttl: Some(Duration::days(1)),
+            max_rss_entries: Some(1),
+        });
+        storage
+            .save_rss_feed(&RssFeed {
+                id: "feed".to_string(),
+                rss_entries: vec![
+                    test_rss_entry("starred-read", 30, true, true),
+                    test_rss_entry("starred-unread", 40, false, true),
+                    test_rss_entry("unstarred", 30, true, false),
+                ],
+                ..Default::default()
+            })
+            .unwrap();
+
+        storage.expire_old_entries().unwrap();
+
+        assert!(stored_rss_entry_ids(&storage, "feed") == vec!["starred-read", "starred-unread"]);
+    }
+
+    /// Tests that only the newest entries are kept when a feed has more
+    /// entries than allowed.
+    #[test]
+    fn test_expire_max_entries_per_feed() {
+        let (_temp_dir, mut storage) = open_storage(RetentionPolicy {
+            read_ttl: None,
+            unread_ttl: None,
+            max_rss_entries: Some(2),
+        });
+        for rss_feed_id in ["first", "second"] {
+            storage
+                .save_rss_feed(&RssFeed {
+                    id: rss_feed_id.to_string(),
+                    rss_entries: (0..4)
+                        .map(|i| test_rss_entry(&format!("{}-{}", rss_feed_id, i), i, false, false))
+                        .collect(),
+                    ..Default::default()
+                })
+                .unwrap();
+        }
+
+        let affected = storage.expire_old_entries().unwrap();
+
+        assert!(affected == 4);
+        assert!(stored_rss_entry_ids(&storage, "first") == vec!["first-0", "first-1"]);
+        assert!(stored_rss_entry_ids(&storage, "second") == vec!["second-0", "second-1"]);
+    }
+
+    /// Tests that a feed's retention overrides take precedence over the
+    /// global policy, and that unset overrides fall back to it.
+    #[test]
+    fn test_expire_per_feed_override() {
+        let (_temp_dir, mut storage) = open_storage(RetentionPolicy {
+            read_ttl: Some(Duration::days(2)),
+            unread_ttl: Some(Duration::days(2)),
+            max_rss_entries: None,
+        });
+        let test_rss_entries = |prefix: &str| {
+            vec![
+                test_rss_entry(&format!("{}-recent", prefix), 1, false, false),
+                test_rss_entry(&format!("{}-old-read", prefix), 5, true, false),
+                test_rss_entry(&format!("{}-old-unread", prefix), 5, false, false),
+            ]
+        };
+        storage
+            .save_rss_feed(&RssFeed {
+                id: "global".to_string(),
+                rss_entries: test_rss_entries("global"),
+                ..Default::default()
+            })
+            .unwrap();
+        storage
+            .save_rss_feed(&RssFeed {
+                id: "override".to_string(),
+                rss_entries: test_rss_entries("override"),
+                retention_override: RetentionPolicy {
+                    unread_ttl: Some(Duration::days(30)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .unwrap();
+
+        storage.expire_old_entries().unwrap();
+
+        assert!(stored_rss_entry_ids(&storage, "global") == vec!["global-recent"]);
+        assert!(
+            stored_rss_entry_ids(&storage, "override")
+                == vec!["override-recent", "override-old-unread"]
+        );
+        let rss_feeds = storage.load_rss_feeds().unwrap();
+        let override_rss_feed = rss_feeds.iter().find(|f| f.id == "override").unwrap();
+        assert!(override_rss_feed.retention_override.unread_ttl == Some(Duration::days(30)));
+        assert!(override_rss_feed.retention_override.read_ttl.is_none());
+    }
+
+    /// Tests that a policy without any rules keeps every entry.
+    #[test]
+    fn test_expire_keep_forever() {
+        let (_temp_dir, mut storage) = open_storage(RetentionPolicy::default());
+        storage
+            .save_rss_feed(&RssFeed {
+                id: "feed".to_string(),
+                rss_entries: vec![
+                    test_rss_entry("old-read", 365, true, false),
+                    test_rss_entry("old-unread", 365, false, false),
+                ],
+                ..Default::default()
+            })
+            .unwrap();
+
+        assert!(storage.expire_old_entries().unwrap() == 0);
+        assert!(stored_rss_entry_ids(&storage, "feed").len() == 2);
+    }
+
+    /// Tests that searches rank entries, highlight matches in titles, and
+    /// stay in sync as entries are updated and deleted.
+    #[test]
+    fn test_search_rss_entries() {
+        let (_temp_dir, mut storage) = open_storage(RetentionPolicy::default());
+        let mut rss_feed = RssFeed {
+            id: "feed".to_string(),
+            rss_entries: vec![
+                RssEntry {
+                    title: "Rust in the kernel".to_string(),
+                    content: "Rust rust rust.".to_string(),
+                    ..test_rss_entry("kernel", 1, false, false)
+                },
+                RssEntry {
+                    title: "Café notes".to_string(),
+                    content: "Mentions Rustaceans once.".to_string(),
+                    ..test_rss_entry("cafe", 2, false, false)
+                },
+                RssEntry {
+                    title: "Unrelated".to_string(),
+                    ..test_rss_entry("unrelated", 3, false, false)
+                },
+            ],
+            ..Default::default()
+        };
+        storage.save_rss_feed(&rss_feed).unwrap();
+
+        let search_results = storage.search_rss_entries("rust", 10).unwrap();
+        assert!(search_results.len() == 2);
+        assert!(search_results[0].rss_entry_id == "kernel");
+        assert!(search_results[0].title == "Rust in the kernel");
+        assert!(search_results[0].title_matches == vec![Range { start: 0, end: 4 }]);
+        assert!(search_results[1].rss_entry_id == "cafe");
+        assert!(search_results[1].title_matches.is_empty());
+
+        let search_results = storage.search_rss_entries("cafe", 10).unwrap();
+        assert!(search_results.len() == 1);
+        assert!(search_results[0].title_matches.first() == Some(&(0.."Café".len())));
+        assert!(storage.search_rss_entries("\"(*", 10).unwrap().is_empty());
+        assert!(storage.search_rss_entries("  ", 10).unwrap().is_empty());
+
+        rss_feed.rss_entries[0].title = "Go in the kernel".to_string();
+        rss_feed.rss_entries[0].content = "Go.".to_string();
+        storage.save_rss_feed(&rss_feed).unwrap();
+        let search_results = storage.search_rss_entries("rust", 10).unwrap();
+        assert!(search_results.len() == 1);
+        assert!(search_results[0].rss_entry_id == "cafe");
+
+        storage.delete_rss_feed(&"feed".to_string()).unwrap();
+        assert!(storage.search_rss_entries("kernel", 10).unwrap().is_empty());
+    }
+}
@@ -12,10 +12,23 @@ use ratatui::Terminal;
 use tokio::sync::mpsc;
 
 mod app;
+mod cli;
+mod config;
+mod discovery;
+mod extract;
+mod keymap;
 mod local_storage;
+mod migrations;
+mod opml;
+mod refresh;
+mod render;
+mod theme;
 mod tui;
 
-use crate::app::{App, AppEvent};
+use crate::app::{App, AppEvent, DEFAULT_DATE_FORMAT, SyncOptions, get_db_path};
+use crate::cli::Command;
+use crate::config::Config;
+use crate::local_storage::{LocalStorage, RetentionPolicy};
 use crate::tui::{get_rows, ui};
 
 /// Runs the application.
@@ -23,8 +36,8 @@ fn run_app<B: ratatui::backend::Backend>(
     terminal: &mut Terminal<B>,
     app: &mut App,
     receiver: &mut mpsc::UnboundedReceiver<AppEvent>,
+    tick_rate: Duration,
 ) -> Result<()> {
-    let tick_rate = Duration::from_millis(100);
     let mut last_tick = Instant::now();
     loop {
         terminal
@@ -33,7 +46,7 @@ fn run_app<B: ratatui::backend::Backend>(
         let rows = get_rows(app);
 
         // Asynchronous operations.
-        if let Ok(app_event) = receiver.try_recv() {
+        while let Ok(app_event) = receiver.try_recv() {
             app.handle_app_event(app_event);
         }
 
@@ -43,44 +56,148 @@ fn run_app<B: ratatui::backend::Backend>(
         }
 
         // Keyboard input.
-        if event::poll(std::time::Duration::from_millis(200))? {
-            if let Event::Key(key) = event::read()? {
-                if app.handle_key(key, &rows)? {
-                    return Ok(());
-                }
-            }
+        if event::poll(std::time::Duration::from_millis(200))?
+            && let Event::Key(key) = event::read()?
+            && app.handle_key(key, &rows)?
+        {
+            return Ok(());
         }
     }
 }
 
+/// How many days entries are kept by default.
+const DEFAULT_TTL_DAYS: usize = 5;
+
+/// How many milliseconds pass between ticks by default.
+const DEFAULT_TICK_RATE_MS: u64 = 100;
+
+/// Converts a number of days to a time-to-live, where 0 days means
+/// forever.
+fn ttl_from_days(days: usize) -> Option<chrono::Duration> {
+    if days == 0 {
+        None
+    } else {
+        Some(chrono::Duration::days(days as i64))
+    }
+}
+
 #[derive(Parser)]
 #[command(version, about, long_about = None)]
 struct Cli {
+    /// Runs a command without starting the TUI.
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Outputs JSON from commands instead of human-readable text.
+    #[arg(long, global = true)]
+    json: bool,
+
+    /// The configuration file to read instead of the default one.
+    #[arg(long)]
+    config: Option<PathBuf>,
+
     #[arg(short, long)]
     db_path: Option<String>,
 
+    /// How many days entries are kept. 0 keeps entries forever.
     #[arg(short, long)]
     max_ttl_days: Option<usize>,
+
+    /// How many days read entries are kept, overriding --max-ttl-days.
+    /// 0 keeps read entries forever.
+    #[arg(long)]
+    read_ttl_days: Option<usize>,
+
+    /// How many days unread entries are kept, overriding --max-ttl-days.
+    /// 0 keeps unread entries forever.
+    #[arg(long)]
+    unread_ttl_days: Option<usize>,
+
+    /// The most entries kept per feed. Starred entries don't count
+    /// towards this limit.
+    #[arg(long)]
+    max_entries_per_feed: Option<usize>,
+
+    /// The maximum number of feeds fetched at once while syncing.
+    #[arg(long)]
+    sync_concurrency: Option<usize>,
+
+    /// The maximum number of feeds fetched at once from a single host
+    /// while syncing.
+    #[arg(long)]
+    sync_host_concurrency: Option<usize>,
+
+    /// How many minutes pass between background syncs of feeds that
+    /// don't say how often to refresh them. 0 disables background syncing.
+    #[arg(long)]
+    refresh_minutes: Option<usize>,
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let cli: Cli = Cli::parse();
+    let config = Config::load(cli.config)?;
+    // Flags override the configuration file.
     let db_path = match cli.db_path {
         Some(db_path) => PathBuf::from_str(db_path.as_str()).ok(),
-        None => None,
+        None => config.db_path,
     };
-    let max_ttl_days = match cli.max_ttl_days {
-        Some(max_ttl_days) => Some(chrono::Duration::days(max_ttl_days as i64)),
-        None => None,
+    let retention = config.retention;
+    let max_ttl_days = cli
+        .max_ttl_days
+        .or(retention.max_ttl_days)
+        .unwrap_or(DEFAULT_TTL_DAYS);
+    let read_ttl_days = cli.read_ttl_days.or(retention.read_ttl_days);
+    let unread_ttl_days = cli.unread_ttl_days.or(retention.unread_ttl_days);
+    let retention_policy = RetentionPolicy {
+        read_ttl: ttl_from_days(read_ttl_days.unwrap_or(max_ttl_days)),
+        unread_ttl: ttl_from_days(unread_ttl_days.unwrap_or(max_ttl_days)),
+        max_rss_entries: cli.max_entries_per_feed.or(retention.max_entries_per_feed),
     };
+    let mut sync_options = SyncOptions::default();
+    if let Some(sync_concurrency) = cli.sync_concurrency.or(config.sync.concurrency) {
+        sync_options.max_concurrent_feeds = sync_concurrency;
+    }
+    if let Some(sync_host_concurrency) = cli.sync_host_concurrency.or(config.sync.host_concurrency)
+    {
+        sync_options.max_concurrent_feeds_per_host = sync_host_concurrency;
+    }
+    if let Some(refresh_minutes) = cli.refresh_minutes.or(config.sync.refresh_minutes) {
+        sync_options.refresh_interval =
+            (refresh_minutes > 0).then(|| chrono::Duration::minutes(refresh_minutes as i64));
+    }
+    sync_options.user_agent = config.sync.user_agent;
+
+    if let Some(command) = cli.command {
+        let mut storage = LocalStorage::new(get_db_path(db_path)?, retention_policy)?;
+        let date_format = config
+            .ui
+            .date_format
+            .as_deref()
+            .unwrap_or(DEFAULT_DATE_FORMAT);
+        let output =
+            cli::run_command(command, &mut storage, sync_options, date_format, cli.json).await?;
+        if !output.is_empty() {
+            println!("{}", output.trim_end());
+        }
+        return Ok(());
+    }
 
     let (sender, mut receiver) = mpsc::unbounded_channel();
     enable_raw_mode()?;
     let mut terminal = ratatui::init();
-    let mut app = App::new(sender, db_path, max_ttl_days)?;
-    let _ = run_app(&mut terminal, &mut app, &mut receiver);
+    let mut app = App::new(sender, db_path, retention_policy, sync_options)?;
+    // A non-empty NO_COLOR turns colors off, as described at
+    // https://no-color.org.
+    let no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+    app.theme = config.theme.theme(no_color);
+    app.keymap = config.keys;
+    if let Some(date_format) = config.ui.date_format {
+        app.date_format = date_format;
+    }
+    let tick_rate = Duration::from_millis(config.ui.tick_rate_ms.unwrap_or(DEFAULT_TICK_RATE_MS));
+    let result = run_app(&mut terminal, &mut app, &mut receiver, tick_rate);
     ratatui::restore();
 
-    Ok(())
+    result
 }
@@ -0,0 +1,642 @@
+//! Schema migrations for the local SQLite database.
+//!
+//! A database's `user_version` records how many migrations have been
+//! applied to it. Migrations are applied in order when the database is
+//! opened, each within its own transaction.
+
+use anyhow::{Result, anyhow};
+use rusqlite::{Connection, Transaction, params};
+
+/// A single, ordered change to the database schema.
+pub struct Migration {
+    pub description: &'static str,
+    pub up: fn(&Transaction) -> rusqlite::Result<()>,
+}
+
+/// Every migration, in the order they are applied. Migrations must never
+/// be reordered or removed once released, only appended.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        description: "create rss_feeds and rss_entries",
+        up: create_tables,
+    },
+    Migration {
+        description: "add HTTP cache validators to rss_feeds",
+        up: add_http_cache_columns,
+    },
+    Migration {
+        description: "add starred entries and per-feed retention overrides",
+        up: add_retention_columns,
+    },
+    Migration {
+        description: "key rss_entries by feed and entry ID and add content hashes",
+        up: key_rss_entries_by_feed,
+    },
+    Migration {
+        description: "add folders and site links to rss_feeds",
+        up: add_opml_columns,
+    },
+    Migration {
+        description: "add a full-text search index of rss_entries",
+        up: create_rss_entries_search_index,
+    },
+    Migration {
+        description: "add the refresh interval advertised by each feed",
+        up: add_refresh_interval_column,
+    },
+    Migration {
+        description: "move feed folders into a folders table",
+        up: create_folders,
+    },
+    Migration {
+        description: "add the HTML content of rss_entries",
+        up: add_content_html_column,
+    },
+    Migration {
+        description: "add the scraped full content of rss_entries and full content fetching",
+        up: add_full_content_columns,
+    },
+];
+
+/// Brings a database's schema up to date.
+pub fn migrate(conn: &mut Connection) -> Result<()> {
+    apply_migrations(conn, MIGRATIONS)
+}
+
+/// Applies every migration that a database hasn't had applied yet. A
+/// migration that fails is rolled back, leaving the database at the
+/// version of the last successful migration.
+fn apply_migrations(conn: &mut Connection, migrations: &[Migration]) -> Result<()> {
+    let version = schema_version(conn)?;
+    if version > migrations.len() {
+        return Err(anyhow!(
+            "database schema version {} is newer than the latest supported version {}",
+            version,
+            migrations.len()
+        ));
+    }
+
+    for (index, migration) in migrations.iter().enumerate().skip(version) {
+        let transaction = conn.transaction()?;
+        (migration.up)(&transaction).map_err(|e| {
+            anyhow!(
+                "migration {} ({}) failed: {}",
+                index + 1,
+                migration.description,
+                e
+            )
+        })?;
+        transaction.pragma_update(None, "user_version", (index + 1) as i64)?;
+        transaction.commit()?;
+    }
+    Ok(())
+}
+
+/// Retrieves the number of migrations applied to a database.
+pub fn schema_version(conn: &Connection) -> rusqlite::Result<usize> {
+    conn.pragma_query_value(None, "user_version", |row| row.get::<_, i64>(0))
+        .map(|version| version as usize)
+}
+
+/// Hashes an entry's title and content to match entries across syncs.
+/// A copy of `app::content_hash` as it was when content hashes were
+/// added, so that the migration keeps producing the same hashes.
+fn content_hash(title: &str, content: &str) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in title.bytes().chain([0]).chain(content.bytes()) {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{:016x}", hash)
+}
+
+/// Converts plain text to HTML, with blank lines separating paragraphs.
+/// A copy of `render::text_to_html` as it was when HTML content was
+/// added, so that the migration keeps producing the same HTML.
+fn text_to_html(text: &str) -> String {
+    text.split("\n\n")
+        .map(str::trim)
+        .filter(|paragraph| !paragraph.is_empty())
+        .map(|paragraph| {
+            let escaped = paragraph
+                .replace('&', "&amp;")
+                .replace('<', "&lt;")
+                .replace('>', "&gt;")
+                .replace('\n', "<br>");
+            format!("<p>{}</p>", escaped)
+        })
+        .collect()
+}
+
+/// Adds a column to a table if the table doesn't already have it. Only
+/// the HTTP cache columns need this, since the reader added them before
+/// migrations existed. Later migrations add columns with `ALTER TABLE`.
+fn add_column_if_missing(
+    transaction: &Transaction,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> rusqlite::Result<()> {
+    let mut statement = transaction.prepare(&format!("PRAGMA table_info({})", table))?;
+    let columns = statement
+        .query_map([], |row| row.get::<_, String>(1))?
+        .collect::<rusqlite::Result<Vec<String>>>()?;
+    if !columns.iter().any(|c| c == column) {
+        transaction.execute_batch(&format!(
+            "ALTER TABLE {} ADD COLUMN {} {}",
+            table, column, definition
+        ))?;
+    }
+    Ok(())
+}
+
+/// Creates the original tables. Databases created before migrations
+/// existed already have these tables, so they are only created if they
+/// don't exist.
+fn create_tables(transaction: &Transaction) -> rusqlite::Result<()> {
+    transaction.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS rss_feeds (
+            id TEXT PRIMARY KEY,
+            title TEXT NOT NULL,
+            link TEXT NOT NULL,
+            expanded INTEGER NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS rss_entries (
+            id TEXT PRIMARY KEY,
+            rss_feed_id TEXT NOT NULL,
+            title TEXT NOT NULL,
+            content TEXT NOT NULL,
+            content_total_lines INTEGER NOT NULL,
+            link TEXT NOT NULL,
+            published TEXT NOT NULL,
+            read INTEGER NOT NULL,
+            authors TEXT,
+            FOREIGN KEY(rss_feed_id) REFERENCES rss_feeds(id) ON DELETE CASCADE
+        )
+        "#,
+    )
+}
+
+/// Adds the columns used for conditional requests while syncing.
+fn add_http_cache_columns(transaction: &Transaction) -> rusqlite::Result<()> {
+    add_column_if_missing(transaction, "rss_feeds", "etag", "TEXT")?;
+    add_column_if_missing(transaction, "rss_feeds", "last_modified", "TEXT")?;
+    add_column_if_missing(transaction, "rss_feeds", "last_fetched", "TEXT")
+}
+
+/// Adds the starred flag that exempts entries from expiry, and each
+/// feed's retention overrides.
+fn add_retention_columns(transaction: &Transaction) -> rusqlite::Result<()> {
+    transaction.execute_batch(
+        r#"
+        ALTER TABLE rss_entries ADD COLUMN starred INTEGER NOT NULL DEFAULT 0;
+        ALTER TABLE rss_feeds ADD COLUMN retention_read_days INTEGER;
+        ALTER TABLE rss_feeds ADD COLUMN retention_unread_days INTEGER;
+        ALTER TABLE rss_feeds ADD COLUMN retention_max_entries INTEGER;
+        "#,
+    )
+}
+
+/// Rebuilds rss_entries with a primary key of feed ID and entry ID, since
+/// entry IDs are only unique within a feed, and adds a content hash used
+/// to match entries across syncs.
+fn key_rss_entries_by_feed(transaction: &Transaction) -> rusqlite::Result<()> {
+    transaction.execute_batch(
+        r#"
+        CREATE TABLE rss_entries_new (
+            id TEXT NOT NULL,
+            rss_feed_id TEXT NOT NULL,
+            title TEXT NOT NULL,
+            content TEXT NOT NULL,
+            content_total_lines INTEGER NOT NULL,
+            link TEXT NOT NULL,
+            published TEXT NOT NULL,
+            read INTEGER NOT NULL,
+            authors TEXT,
+            starred INTEGER NOT NULL DEFAULT 0,
+            content_hash TEXT NOT NULL DEFAULT '',
+            PRIMARY KEY(rss_feed_id, id),
+            FOREIGN KEY(rss_feed_id) REFERENCES rss_feeds(id) ON DELETE CASCADE
+        );
+
+        INSERT INTO rss_entries_new
+            (id, rss_feed_id, title, content, content_total_lines, link, published, read,
+             authors, starred)
+        SELECT id, rss_feed_id, title, content, content_total_lines, link, published, read,
+            authors, starred
+        FROM rss_entries;
+
+        DROP TABLE rss_entries;
+        ALTER TABLE rss_entries_new RENAME TO rss_entries;
+        "#,
+    )?;
+
+    let rows: Vec<(i64, String, String)> = transaction
+        .prepare("SELECT rowid, title, content FROM rss_entries")?
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    for (rowid, title, content) in rows {
+        transaction.execute(
+            "UPDATE rss_entries SET content_hash = ?1 WHERE rowid = ?2",
+            params![content_hash(&title, &content), rowid],
+        )?;
+    }
+    Ok(())
+}
+
+/// Adds the folder and site link of each feed, kept for OPML import
+/// and export.
+fn add_opml_columns(transaction: &Transaction) -> rusqlite::Result<()> {
+    transaction.execute_batch(
+        r#"
+        ALTER TABLE rss_feeds ADD COLUMN folder TEXT;
+        ALTER TABLE rss_feeds ADD COLUMN html_url TEXT;
+        "#,
+    )
+}
+
+/// Creates a full-text search index of entries' titles, authors and
+/// content. The index reads from rss_entries, which triggers keep it in
+/// sync with.
+fn create_rss_entries_search_index(transaction: &Transaction) -> rusqlite::Result<()> {
+    transaction.execute_batch(
+        r#"
+        CREATE VIRTUAL TABLE rss_entries_fts USING fts5(
+            title, authors, content,
+            content = 'rss_entries',
+            content_rowid = 'rowid',
+            tokenize = 'unicode61 remove_diacritics 2'
+        );
+
+        CREATE TRIGGER rss_entries_fts_insert AFTER INSERT ON rss_entries BEGIN
+            INSERT INTO rss_entries_fts (rowid, title, authors, content)
+            VALUES (new.rowid, new.title, new.authors, new.content);
+        END;
+
+        CREATE TRIGGER rss_entries_fts_delete AFTER DELETE ON rss_entries BEGIN
+            INSERT INTO rss_entries_fts (rss_entries_fts, rowid, title, authors, content)
+            VALUES ('delete', old.rowid, old.title, old.authors, old.content);
+        END;
+
+        CREATE TRIGGER rss_entries_fts_update AFTER UPDATE OF title, authors, content
+        ON rss_entries BEGIN
+            INSERT INTO rss_entries_fts (rss_entries_fts, rowid, title, authors, content)
+            VALUES ('delete', old.rowid, old.title, old.authors, old.content);
+            INSERT INTO rss_entries_fts (rowid, title, authors, content)
+            VALUES (new.rowid, new.title, new.authors, new.content);
+        END;
+
+        INSERT INTO rss_entries_fts (rss_entries_fts) VALUES ('rebuild');
+        "#,
+    )
+}
+
+/// Adds how often each feed asks to be refreshed, in seconds.
+fn add_refresh_interval_column(transaction: &Transaction) -> rusqlite::Result<()> {
+    transaction.execute_batch("ALTER TABLE rss_feeds ADD COLUMN refresh_interval_secs INTEGER;")
+}
+
+/// Moves the folder names stored on each feed into a folders table, which
+/// feeds reference by ID. Folders also keep whether they're expanded.
+fn create_folders(transaction: &Transaction) -> rusqlite::Result<()> {
+    transaction.execute_batch(
+        r#"
+        CREATE TABLE folders (
+            id INTEGER PRIMARY KEY,
+            name TEXT NOT NULL UNIQUE,
+            expanded INTEGER NOT NULL DEFAULT 0
+        );
+
+        ALTER TABLE rss_feeds
+        ADD COLUMN folder_id INTEGER REFERENCES folders(id) ON DELETE SET NULL;
+
+        INSERT OR IGNORE INTO folders (name)
+        SELECT folder FROM rss_feeds WHERE folder IS NOT NULL AND folder != ''
+        ORDER BY folder;
+
+        UPDATE rss_feeds
+        SET folder_id = (SELECT id FROM folders WHERE folders.name = rss_feeds.folder);
+
+        ALTER TABLE rss_feeds DROP COLUMN folder;
+        "#,
+    )
+}
+
+/// Adds the HTML content that entries are displayed from. Entries saved
+/// before then only kept plain text, which becomes their HTML.
+fn add_content_html_column(transaction: &Transaction) -> rusqlite::Result<()> {
+    transaction.execute_batch(
+        "ALTER TABLE rss_entries ADD COLUMN content_html TEXT NOT NULL DEFAULT '';",
+    )?;
+
+    let rows: Vec<(i64, String)> = transaction
+        .prepare("SELECT rowid, content FROM rss_entries")?
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    for (rowid, content) in rows {
+        transaction.execute(
+            "UPDATE rss_entries SET content_html = ?1 WHERE rowid = ?2",
+            params![text_to_html(&content), rowid],
+        )?;
+    }
+    Ok(())
+}
+
+/// Adds the full content scraped from each entry's web page, kept apart
+/// from the content its feed provides, and whether each feed's new
+/// entries are scraped while syncing.
+fn add_full_content_columns(transaction: &Transaction) -> rusqlite::Result<()> {
+    transaction.execute_batch(
+        "ALTER TABLE rss_entries ADD COLUMN full_content_html TEXT;
+        ALTER TABLE rss_entries ADD COLUMN full_content_fetched TEXT;
+        ALTER TABLE rss_feeds ADD COLUMN fetch_full_content INTEGER NOT NULL DEFAULT 0;",
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    /// The schema of databases created before migrations existed.
+    const LEGACY_SCHEMA: &str = r#"
+        PRAGMA foreign_keys = ON;
+
+        CREATE TABLE IF NOT EXISTS rss_feeds (
+            id TEXT PRIMARY KEY,
+            title TEXT NOT NULL,
+            link TEXT NOT NULL,
+            expanded INTEGER NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS rss_entries (
+            id TEXT PRIMARY KEY,
+            rss_feed_id TEXT NOT NULL,
+            title TEXT NOT NULL,
+            content TEXT NOT NULL,
+            content_total_lines INTEGER NOT NULL,
+            link TEXT NOT NULL,
+            published TEXT NOT NULL,
+            read INTEGER NOT NULL,
+            authors TEXT,
+            FOREIGN KEY(rss_feed_id) REFERENCES rss_feeds(id) ON DELETE CASCADE
+        )
+    "#;
+
+    /// Retrieves the names of a table's columns.
+    fn column_names(conn: &Connection, table: &str) -> Vec<String> {
+        let mut statement = conn
+            .prepare(&format!("PRAGMA table_info({})", table))
+            .unwrap();
+        statement
+            .query_map([], |row| row.get::<_, String>(1))
+            .unwrap()
+            .collect::<rusqlite::Result<Vec<String>>>()
+            .unwrap()
+    }
+
+    /// Tests migrating a new, empty database.
+    #[test]
+    fn test_migrate_new_database() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        migrate(&mut conn).unwrap();
+        assert!(schema_version(&conn).unwrap() == MIGRATIONS.len());
+        assert!(column_names(&conn, "rss_feeds").contains(&"etag".to_string()));
+        assert!(column_names(&conn, "rss_entries").contains(&"read".to_string()));
+    }
+
+    /// Tests upgrading a database created before migrations existed,
+    /// keeping its feeds and entries.
+    #[test]
+    fn test_migrate_legacy_database() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("rss.db");
+        {
+            let conn = Connection::open(&db_path).unwrap();
+            conn.execute_batch(LEGACY_SCHEMA).unwrap();
+            conn.execute(
+                "INSERT INTO rss_feeds (id, title, link, expanded) VALUES (?1, ?2, ?3, ?4)",
+                params!["feed", "Feed", "https://example.com/rss.xml", 1],
+            )
+            .unwrap();
+            conn.execute(
+                "INSERT INTO rss_entries
+                (id, rss_feed_id, title, content, content_total_lines, link, published, read,
+                 authors)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                params![
+                    "entry",
+                    "feed",
+                    "Entry",
+                    "Content.",
+                    1,
+                    "https://example.com/entry",
+                    "2026-01-05T10:00:00+00:00",
+                    1,
+                    "[]"
+                ],
+            )
+            .unwrap();
+            assert!(schema_version(&conn).unwrap() == 0);
+        }
+
+        let mut conn = Connection::open(&db_path).unwrap();
+        migrate(&mut conn).unwrap();
+
+        assert!(schema_version(&conn).unwrap() == MIGRATIONS.len());
+        let rss_feed_columns = column_names(&conn, "rss_feeds");
+        for column in ["etag", "last_modified", "last_fetched"] {
+            assert!(rss_feed_columns.contains(&column.to_string()));
+        }
+        let (title, etag): (String, Option<String>) = conn
+            .query_row(
+                "SELECT title, etag FROM rss_feeds WHERE id = 'feed'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert!(title == "Feed");
+        assert!(etag.is_none());
+        let (read, hash): (i32, String) = conn
+            .query_row(
+                "SELECT read, content_hash FROM rss_entries WHERE id = 'entry'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert!(read == 1);
+        assert!(hash == crate::app::content_hash("Entry", "Content."));
+
+        // Entry IDs only need to be unique within a feed.
+        conn.execute(
+            "INSERT INTO rss_feeds (id, title, link, expanded) VALUES (?1, ?2, ?3, ?4)",
+            params!["other-feed", "Other feed", "https://example.org/rss.xml", 0],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO rss_entries
+            (id, rss_feed_id, title, content, content_total_lines, link, published, read)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                "entry",
+                "other-feed",
+                "Other entry",
+                "Other content.",
+                1,
+                "https://example.org/entry",
+                "2026-01-05T10:00:00+00:00",
+                0
+            ],
+        )
+        .unwrap();
+        let num_rss_entries: i64 = conn
+            .query_row("SELECT COUNT(*) FROM rss_entries", [], |row| row.get(0))
+            .unwrap();
+        assert!(num_rss_entries == 2);
+
+        // Existing entries are indexed for search.
+        let num_matches: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM rss_entries_fts WHERE rss_entries_fts MATCH 'content'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert!(num_matches == 2);
+    }
+
+    /// Tests that the folder names stored on feeds become folders that the
+    /// feeds reference.
+    #[test]
+    fn test_migrate_folders() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        let create_folders_index = MIGRATIONS
+            .iter()
+            .position(|m| m.description == "move feed folders into a folders table")
+            .unwrap();
+        apply_migrations(&mut conn, &MIGRATIONS[..create_folders_index]).unwrap();
+        for (id, folder) in [("a", Some("News")), ("b", Some("News")), ("c", None)] {
+            conn.execute(
+                "INSERT INTO rss_feeds (id, title, link, expanded, folder)
+                VALUES (?1, ?1, ?1, 0, ?2)",
+                params![id, folder],
+            )
+            .unwrap();
+        }
+
+        migrate(&mut conn).unwrap();
+
+        assert!(!column_names(&conn, "rss_feeds").contains(&"folder".to_string()));
+        let folders: Vec<(String, Option<String>)> = conn
+            .prepare(
+                "SELECT rss_feeds.id, folders.name FROM rss_feeds
+                LEFT JOIN folders ON folders.id = rss_feeds.folder_id
+                ORDER BY rss_feeds.id",
+            )
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert!(
+            folders
+                == vec![
+                    ("a".to_string(), Some("News".to_string())),
+                    ("b".to_string(), Some("News".to_string())),
+                    ("c".to_string(), None),
+                ]
+        );
+    }
+
+    /// Tests that entries saved before HTML content was kept get HTML
+    /// made from their plain text.
+    #[test]
+    fn test_migrate_content_html() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        let add_content_html_index = MIGRATIONS
+            .iter()
+            .position(|m| m.description == "add the HTML content of rss_entries")
+            .unwrap();
+        apply_migrations(&mut conn, &MIGRATIONS[..add_content_html_index]).unwrap();
+        conn.execute(
+            "INSERT INTO rss_feeds (id, title, link, expanded) VALUES ('a', 'a', 'a', 0)",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO rss_entries
+            (id, rss_feed_id, title, content, content_total_lines, link, published, read)
+            VALUES ('1', 'a', 'Title', 'One\n\nTwo & three', 0, 'a', '', 0)",
+            [],
+        )
+        .unwrap();
+
+        migrate(&mut conn).unwrap();
+
+        let content_html: String = conn
+            .query_row("SELECT content_html FROM rss_entries", [], |row| row.get(0))
+            .unwrap();
+        assert!(content_html == "<p>One</p><p>Two &amp; three</p>");
+    }
+
+    /// Tests that migrating an up-to-date database changes nothing.
+    #[test]
+    fn test_migrate_twice() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        migrate(&mut conn).unwrap();
+        migrate(&mut conn).unwrap();
+        assert!(schema_version(&conn).unwrap() == MIGRATIONS.len());
+    }
+
+    /// Tests that a database from a newer version of the reader is
+    /// rejected rather than modified.
+    #[test]
+    fn test_migrate_newer_database() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.pragma_update(None, "user_version", (MIGRATIONS.len() + 1) as i64)
+            .unwrap();
+        assert!(migrate(&mut conn).is_err());
+        assert!(schema_version(&conn).unwrap() == MIGRATIONS.len() + 1);
+    }
+
+    /// Tests that a failing migration is rolled back and stops later
+    /// migrations from being applied.
+    #[test]
+    fn test_failed_migration_rolls_back() {
+        let migrations = [
+            Migration {
+                description: "create a table",
+                up: |transaction| transaction.execute_batch("CREATE TABLE first (id TEXT)"),
+            },
+            Migration {
+                description: "fail partway through",
+                up: |transaction| {
+                    transaction.execute_batch(
+                        "CREATE TABLE second (id TEXT);
+                        INSERT INTO missing_table VALUES (1);",
+                    )
+                },
+            },
+            Migration {
+                description: "create another table",
+                up: |transaction| transaction.execute_batch("CREATE TABLE third (id TEXT)"),
+            },
+        ];
+        let mut conn = Connection::open_in_memory().unwrap();
+
+        let result = apply_migrations(&mut conn, &migrations);
+
+        assert!(result.is_err());
+        assert!(schema_version(&conn).unwrap() == 1);
+        let tables: Vec<String> = conn
+            .prepare("SELECT name FROM sqlite_master WHERE type = 'table' ORDER BY name")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<rusqlite::Result<Vec<String>>>()
+            .unwrap();
+        assert!(tables == vec!["first".to_string()]);
+    }
+}
@@ -0,0 +1,305 @@
+//! OPML import and export of RSS feed subscriptions.
+
+use std::collections::HashMap;
+
+use anyhow::{Result, anyhow};
+use quick_xml::XmlVersion;
+use quick_xml::escape::escape;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::reader::Reader;
+
+use crate::app::RssFeed;
+use crate::local_storage::LocalStorage;
+
+/// A feed listed in an OPML document.
+#[derive(Debug, PartialEq)]
+pub struct OpmlFeed {
+    pub title: String,
+    /// The URL of the feed itself.
+    pub xml_url: String,
+    /// The URL of the website the feed belongs to.
+    pub html_url: Option<String>,
+    /// The categories the feed's outline is nested in, joined with `/`.
+    pub folder: Option<String>,
+}
+
+impl From<OpmlFeed> for RssFeed {
+    fn from(opml_feed: OpmlFeed) -> Self {
+        // Imported feeds haven't been fetched yet, so their URL stands in
+        // for the ID a feed would otherwise provide.
+        RssFeed {
+            id: opml_feed.xml_url.clone(),
+            title: opml_feed.title,
+            link: opml_feed.xml_url,
+            folder: opml_feed.folder,
+            html_url: opml_feed.html_url,
+            ..Default::default()
+        }
+    }
+}
+
+/// Reads an outline's attributes, keyed by lowercase name, since readers
+/// disagree on the case of names like `xmlUrl`.
+fn outline_attributes(outline: &BytesStart) -> Result<HashMap<String, String>> {
+    let mut attributes = HashMap::new();
+    for attribute in outline.attributes() {
+        let attribute = attribute?;
+        let key = attribute.key.as_ref().to_lowercase();
+        let value = attribute.normalized_value(XmlVersion::Implicit1_0)?;
+        attributes.insert(key, value.trim().to_string());
+    }
+    Ok(attributes)
+}
+
+/// Parses the feeds listed in an OPML document. Outlines without an
+/// `xmlUrl` are treated as categories, which become the folders of the
+/// feeds nested within them.
+pub fn parse_opml(opml: &str) -> Result<Vec<OpmlFeed>> {
+    let mut reader = Reader::from_str(opml);
+    let mut is_opml = false;
+    let mut opml_feeds = Vec::new();
+    // The category of each open outline, or `None` for open feed outlines.
+    let mut categories: Vec<Option<String>> = Vec::new();
+    loop {
+        let event = reader
+            .read_event()
+            .map_err(|e| anyhow!("invalid OPML: {}", e))?;
+        let (outline, has_children) = match &event {
+            Event::Start(tag) if tag.local_name().as_ref() == "opml" => {
+                is_opml = true;
+                continue;
+            }
+            Event::Start(tag) if tag.local_name().as_ref() == "outline" => (tag, true),
+            Event::Empty(tag) if tag.local_name().as_ref() == "outline" => (tag, false),
+            Event::End(tag) if tag.local_name().as_ref() == "outline" => {
+                categories.pop();
+                continue;
+            }
+            Event::Eof => break,
+            _ => continue,
+        };
+
+        let mut attributes = outline_attributes(outline)?;
+        let text = attributes
+            .remove("text")
+            .filter(|t| !t.is_empty())
+            .or_else(|| attributes.remove("title"))
+            .unwrap_or_default();
+        let Some(xml_url) = attributes.remove("xmlurl").filter(|u| !u.is_empty()) else {
+            if has_children {
+                categories.push(Some(text));
+            }
+            continue;
+        };
+
+        let html_url = attributes.remove("htmlurl").filter(|u| !u.is_empty());
+        let mut folder: Vec<&str> = categories.iter().flatten().map(|c| c.as_str()).collect();
+        if folder.is_empty()
+            && let Some(category) = attributes.get("category")
+        {
+            // OPML 2.0 categories are comma-separated, slash-delimited paths.
+            let category = category.split(',').next().unwrap_or_default();
+            folder = category.split('/').filter(|c| !c.is_empty()).collect();
+        }
+        opml_feeds.push(OpmlFeed {
+            title: if text.is_empty() {
+                xml_url.clone()
+            } else {
+                text
+            },
+            html_url,
+            folder: (!folder.is_empty()).then(|| folder.join("/")),
+            xml_url,
+        });
+        if has_children {
+            categories.push(None);
+        }
+    }
+
+    if !is_opml {
+        return Err(anyhow!("not an OPML document"));
+    }
+    Ok(opml_feeds)
+}
+
+/// Writes an outline for a single feed.
+fn write_feed_outline(opml: &mut String, rss_feed: &RssFeed, indent: &str) {
+    opml.push_str(&format!(
+        "{}<outline type=\"rss\" text=\"{}\" title=\"{}\" xmlUrl=\"{}\"",
+        indent,
+        escape(rss_feed.title.as_str()),
+        escape(rss_feed.title.as_str()),
+        escape(rss_feed.link.as_str()),
+    ));
+    if let Some(html_url) = &rss_feed.html_url {
+        opml.push_str(&format!(" htmlUrl=\"{}\"", escape(html_url.as_str())));
+    }
+    opml.push_str("/>\n");
+}
+
+/// Writes RSS feeds as an OPML 2.0 document. Feeds in a folder are
+/// nested in an outline named after the folder.
+pub fn write_opml(rss_feeds: &[RssFeed]) -> String {
+    let mut opml = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <opml version=\"2.0\">\n  \
+         <head>\n    \
+         <title>Pequod Reader subscriptions</title>\n  \
+         </head>\n  \
+         <body>\n",
+    );
+    let mut folders: Vec<&str> = Vec::new();
+    for rss_feed in rss_feeds {
+        match rss_feed.folder.as_deref() {
+            Some(folder) => {
+                if !folders.contains(&folder) {
+                    folders.push(folder);
+                }
+            }
+            None => write_feed_outline(&mut opml, rss_feed, "    "),
+        }
+    }
+    for folder in folders {
+        opml.push_str(&format!(
+            "    <outline text=\"{}\" title=\"{}\">\n",
+            escape(folder),
+            escape(folder)
+        ));
+        for rss_feed in rss_feeds
+            .iter()
+            .filter(|f| f.folder.as_deref() == Some(folder))
+        {
+            write_feed_outline(&mut opml, rss_feed, "      ");
+        }
+        opml.push_str("    </outline>\n");
+    }
+    opml.push_str("  </body>\n</opml>\n");
+    opml
+}
+
+/// Saves the feeds listed in an OPML document. Feeds that are already
+/// saved, matched by their URL, are skipped. Returns the saved feeds.
+pub fn import_opml(storage: &mut LocalStorage, opml: &str) -> Result<Vec<RssFeed>> {
+    let mut links: Vec<String> = storage
+        .load_rss_feeds()?
+        .into_iter()
+        .map(|f| f.link)
+        .collect();
+    let mut imported_rss_feeds = Vec::new();
+    for opml_feed in parse_opml(opml)? {
+        if links.contains(&opml_feed.xml_url) {
+            continue;
+        }
+        links.push(opml_feed.xml_url.clone());
+        let rss_feed = RssFeed::from(opml_feed);
+        storage.save_rss_feed(&rss_feed)?;
+        imported_rss_feeds.push(rss_feed);
+    }
+    Ok(imported_rss_feeds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::local_storage::RetentionPolicy;
+    use tempfile::tempdir;
+
+    const TEST_OPML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<opml version="2.0">
+  <head><title>Subscriptions</title></head>
+  <body>
+    <outline text="Unfiled" xmlUrl="https://example.com/unfiled.xml"/>
+    <outline text="News">
+      <outline text="Local">
+        <outline text="Local news" type="rss" xmlUrl="https://example.com/local.xml"
+          htmlUrl="https://example.com/local"/>
+      </outline>
+      <outline title="Titled &amp; escaped" xmlurl="https://example.com/titled.xml"/>
+    </outline>
+    <outline text="Categorized" xmlUrl="https://example.com/categorized.xml"
+      category="/Tech/Rust,/Other"/>
+  </body>
+</opml>"#;
+
+    /// Tests that feeds are parsed with nested categories as folders.
+    #[test]
+    fn test_parse_opml() {
+        let opml_feeds = parse_opml(TEST_OPML).unwrap();
+        assert!(opml_feeds.len() == 4);
+        assert!(opml_feeds[0].title == "Unfiled");
+        assert!(opml_feeds[0].folder.is_none());
+        assert!(opml_feeds[1].title == "Local news");
+        assert!(opml_feeds[1].folder.as_deref() == Some("News/Local"));
+        assert!(opml_feeds[1].html_url.as_deref() == Some("https://example.com/local"));
+        assert!(opml_feeds[2].title == "Titled & escaped");
+        assert!(opml_feeds[2].xml_url == "https://example.com/titled.xml");
+        assert!(opml_feeds[2].folder.as_deref() == Some("News"));
+        assert!(opml_feeds[3].folder.as_deref() == Some("Tech/Rust"));
+    }
+
+    /// Tests that documents other than OPML are rejected.
+    #[test]
+    fn test_parse_opml_not_opml() {
+        assert!(parse_opml("<rss><channel></channel></rss>").is_err());
+        assert!(parse_opml("not xml <").is_err());
+    }
+
+    /// Tests that exported feeds are imported again unchanged.
+    #[test]
+    fn test_write_opml_round_trip() {
+        let rss_feeds = vec![
+            RssFeed {
+                id: "unfiled".to_string(),
+                title: "Quotes \" & <brackets>".to_string(),
+                link: "https://example.com/unfiled.xml?a=1&b=2".to_string(),
+                ..Default::default()
+            },
+            RssFeed {
+                id: "filed".to_string(),
+                title: "Filed".to_string(),
+                link: "https://example.com/filed.xml".to_string(),
+                html_url: Some("https://example.com".to_string()),
+                folder: Some("News/Local".to_string()),
+                ..Default::default()
+            },
+        ];
+        let opml_feeds = parse_opml(&write_opml(&rss_feeds)).unwrap();
+        assert!(opml_feeds.len() == 2);
+        assert!(opml_feeds[0].title == rss_feeds[0].title);
+        assert!(opml_feeds[0].xml_url == rss_feeds[0].link);
+        assert!(opml_feeds[0].html_url.is_none());
+        assert!(opml_feeds[1].xml_url == rss_feeds[1].link);
+        assert!(opml_feeds[1].html_url == rss_feeds[1].html_url);
+        assert!(opml_feeds[1].folder == rss_feeds[1].folder);
+    }
+
+    /// Tests that importing saves new feeds and skips feeds that are
+    /// already saved.
+    #[test]
+    fn test_import_opml_skips_existing_feeds() {
+        let temp_dir = tempdir().unwrap();
+        let mut storage =
+            LocalStorage::new(temp_dir.path().join("rss.db"), RetentionPolicy::default()).unwrap();
+        storage
+            .save_rss_feed(&RssFeed {
+                id: "existing".to_string(),
+                title: "Existing".to_string(),
+                link: "https://example.com/unfiled.xml".to_string(),
+                ..Default::default()
+            })
+            .unwrap();
+
+        let imported_rss_feeds = import_opml(&mut storage, TEST_OPML).unwrap();
+        assert!(imported_rss_feeds.len() == 3);
+        let saved_rss_feeds = storage.load_rss_feeds().unwrap();
+        assert!(saved_rss_feeds.len() == 4);
+        let local_rss_feed = saved_rss_feeds
+            .iter()
+            .find(|f| f.link == "https://example.com/local.xml")
+            .unwrap();
+        assert!(local_rss_feed.folder.as_deref() == Some("News/Local"));
+        assert!(local_rss_feed.html_url.as_deref() == Some("https://example.com/local"));
+
+        assert!(import_opml(&mut storage, TEST_OPML).unwrap().is_empty());
+    }
+}
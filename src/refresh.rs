@@ -0,0 +1,144 @@
+//! How often feeds ask to be refreshed.
+
+use chrono::Duration;
+use quick_xml::XmlVersion;
+use quick_xml::events::Event;
+use quick_xml::reader::Reader;
+
+/// The shortest interval feeds are refreshed at, however often they ask
+/// to be refreshed.
+pub const MIN_REFRESH_INTERVAL: Duration = Duration::minutes(5);
+
+/// Reads the interval advertised with the syndication module's
+/// `sy:updatePeriod` and `sy:updateFrequency` elements. Only the feed's
+/// header is read, stopping at its first entry.
+pub fn syndication_interval(feed_xml: &str) -> Option<Duration> {
+    let mut reader = Reader::from_str(feed_xml);
+    let mut update_period: Option<String> = None;
+    let mut update_frequency: Option<String> = None;
+    let mut current: Option<String> = None;
+    loop {
+        match reader.read_event().ok()? {
+            Event::Start(tag) => {
+                let name = tag.local_name().as_ref().to_string();
+                if name == "item" || name == "entry" {
+                    break;
+                }
+                current = Some(name);
+            }
+            Event::Text(text) => {
+                let text = text.xml_content(XmlVersion::Implicit1_0);
+                match current.as_deref() {
+                    Some("updatePeriod") => update_period = Some(text.trim().to_string()),
+                    Some("updateFrequency") => update_frequency = Some(text.trim().to_string()),
+                    _ => {}
+                }
+            }
+            Event::End(_) => current = None,
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    let period = match update_period?.to_lowercase().as_str() {
+        "hourly" => Duration::hours(1),
+        "daily" => Duration::days(1),
+        "weekly" => Duration::weeks(1),
+        "monthly" => Duration::days(30),
+        "yearly" => Duration::days(365),
+        _ => return None,
+    };
+    // The frequency is how many times the feed updates per period.
+    let frequency = match update_frequency {
+        Some(frequency) => frequency.parse::<i32>().ok().filter(|f| *f > 0)?,
+        None => 1,
+    };
+    Some(period / frequency)
+}
+
+/// Reads the `max-age` directive of a `Cache-Control` header.
+pub fn cache_control_max_age(cache_control: &str) -> Option<Duration> {
+    cache_control.split(',').find_map(|directive| {
+        let (name, value) = directive.split_once('=')?;
+        if !name.trim().eq_ignore_ascii_case("max-age") {
+            return None;
+        }
+        let seconds: i64 = value.trim().trim_matches('"').parse().ok()?;
+        (seconds > 0).then(|| Duration::seconds(seconds))
+    })
+}
+
+/// Combines the intervals a feed advertises with its `<ttl>`, its
+/// syndication module elements and its `Cache-Control` header. The
+/// longest interval wins, so that none of them are exceeded.
+pub fn advertised_refresh_interval(
+    ttl_minutes: Option<u32>,
+    feed_xml: &str,
+    cache_control: Option<&str>,
+) -> Option<Duration> {
+    let ttl = ttl_minutes
+        .filter(|m| *m > 0)
+        .map(|m| Duration::minutes(m as i64));
+    [
+        ttl,
+        syndication_interval(feed_xml),
+        cache_control.and_then(cache_control_max_age),
+    ]
+    .into_iter()
+    .flatten()
+    .max()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tests that the syndication module's elements are read from the
+    /// feed's header only.
+    #[test]
+    fn test_syndication_interval() {
+        let feed_xml = r#"<?xml version="1.0"?>
+<rss version="2.0" xmlns:sy="http://purl.org/rss/1.0/modules/syndication/">
+  <channel>
+    <title>Feed</title>
+    <sy:updatePeriod> daily </sy:updatePeriod>
+    <sy:updateFrequency>4</sy:updateFrequency>
+    <item><sy:updatePeriod>yearly</sy:updatePeriod></item>
+  </channel>
+</rss>"#;
+        assert!(syndication_interval(feed_xml) == Some(Duration::hours(6)));
+        assert!(
+            syndication_interval("<feed><sy:updatePeriod>weekly</sy:updatePeriod></feed>")
+                == Some(Duration::weeks(1))
+        );
+        assert!(syndication_interval("<rss><channel></channel></rss>").is_none());
+        assert!(
+            syndication_interval(
+                "<rss><sy:updatePeriod>hourly</sy:updatePeriod>\
+                 <sy:updateFrequency>0</sy:updateFrequency></rss>"
+            )
+            .is_none()
+        );
+    }
+
+    /// Tests that `max-age` is read among other directives and that a
+    /// zero `max-age` is ignored.
+    #[test]
+    fn test_cache_control_max_age() {
+        assert!(cache_control_max_age("public, MAX-AGE=600") == Some(Duration::minutes(10)));
+        assert!(cache_control_max_age("no-cache").is_none());
+        assert!(cache_control_max_age("max-age=0").is_none());
+    }
+
+    /// Tests that the longest advertised interval wins.
+    #[test]
+    fn test_advertised_refresh_interval() {
+        let feed_xml = "<rss><channel><sy:updatePeriod>hourly</sy:updatePeriod></channel></rss>";
+        assert!(
+            advertised_refresh_interval(Some(90), feed_xml, Some("max-age=300"))
+                == Some(Duration::minutes(90))
+        );
+        assert!(advertised_refresh_interval(Some(0), feed_xml, None) == Some(Duration::hours(1)));
+        assert!(advertised_refresh_interval(None, "<rss/>", None).is_none());
+    }
+}
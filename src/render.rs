@@ -0,0 +1,375 @@
+//! Renders entries' HTML content as styled lines of text.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use html2text::render::{RichAnnotation, RichDecorator, TaggedLine, TextDecorator};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use reqwest::Url;
+
+use crate::theme::Theme;
+
+/// Marks a heading level in rendered text. Markers are control
+/// characters, which readable text doesn't contain, and are a single byte
+/// wide, since html2text measures prefixes in bytes.
+const HEADING_MARKER: char = '\x01';
+/// Marks a level of blockquote in rendered text.
+const QUOTE_MARKER: char = '\x02';
+/// Marks an unordered list item in rendered text.
+const BULLET_MARKER: char = '\x03';
+
+/// Decorates like html2text's rich decorator, but marks headings,
+/// blockquotes and list items so they can be styled, and collects the
+/// targets of links in the order they're numbered.
+#[derive(Clone)]
+struct EntryDecorator(RichDecorator, Rc<RefCell<Vec<String>>>);
+
+impl TextDecorator for EntryDecorator {
+    type Annotation = RichAnnotation;
+
+    fn decorate_link_start(&mut self, url: &str) -> (String, RichAnnotation) {
+        self.0.decorate_link_start(url)
+    }
+
+    fn decorate_link_end(&mut self) -> String {
+        self.0.decorate_link_end()
+    }
+
+    fn decorate_em_start(&self) -> (String, RichAnnotation) {
+        self.0.decorate_em_start()
+    }
+
+    fn decorate_em_end(&self) -> String {
+        self.0.decorate_em_end()
+    }
+
+    fn decorate_strong_start(&self) -> (String, RichAnnotation) {
+        self.0.decorate_strong_start()
+    }
+
+    fn decorate_strong_end(&self) -> String {
+        self.0.decorate_strong_end()
+    }
+
+    fn decorate_strikeout_start(&self) -> (String, RichAnnotation) {
+        self.0.decorate_strikeout_start()
+    }
+
+    fn decorate_strikeout_end(&self) -> String {
+        self.0.decorate_strikeout_end()
+    }
+
+    fn decorate_code_start(&self) -> (String, RichAnnotation) {
+        self.0.decorate_code_start()
+    }
+
+    fn decorate_code_end(&self) -> String {
+        self.0.decorate_code_end()
+    }
+
+    fn decorate_preformat_first(&self) -> RichAnnotation {
+        self.0.decorate_preformat_first()
+    }
+
+    fn decorate_preformat_cont(&self) -> RichAnnotation {
+        self.0.decorate_preformat_cont()
+    }
+
+    fn decorate_image(&mut self, src: &str, title: &str) -> (String, RichAnnotation) {
+        self.0.decorate_image(src, title)
+    }
+
+    fn header_prefix(&self, level: usize) -> String {
+        HEADING_MARKER.to_string().repeat(level) + " "
+    }
+
+    fn quote_prefix(&self) -> String {
+        format!("{} ", QUOTE_MARKER)
+    }
+
+    fn unordered_item_prefix(&self) -> String {
+        format!("{} ", BULLET_MARKER)
+    }
+
+    fn ordered_item_prefix(&self, i: i64) -> String {
+        self.0.ordered_item_prefix(i)
+    }
+
+    fn make_subblock_decorator(&self) -> Self {
+        self.clone()
+    }
+
+    fn finalise(&mut self, links: Vec<String>) -> Vec<TaggedLine<RichAnnotation>> {
+        // Footnotes are added once links are resolved.
+        *self.1.borrow_mut() = links;
+        Vec::new()
+    }
+}
+
+/// An entry's content rendered as lines of text.
+#[derive(Default)]
+pub struct RenderedHtml {
+    pub lines: Vec<Line<'static>>,
+    /// The targets of the content's links, numbered from 1 as footnotes.
+    pub links: Vec<String>,
+}
+
+/// Renders HTML as lines of text wrapped to `width` columns, with
+/// emphasis, headings, blockquotes, list items, code and links styled.
+/// Links are numbered, and listed as footnotes after the text with
+/// relative links resolved against `base_url`.
+pub fn render_html(html: &str, base_url: &str, width: usize, theme: &Theme) -> RenderedHtml {
+    let links = Rc::new(RefCell::new(Vec::new()));
+    let decorator = EntryDecorator(RichDecorator::new(), Rc::clone(&links));
+    // Text too wide for the width, like long preformatted lines, is left
+    // to overflow rather than failing to render.
+    let mut lines: Vec<Line> = html2text::config::with_decorator(decorator)
+        .link_footnotes(true)
+        .allow_width_overflow()
+        .lines_from_read(html.as_bytes(), width.max(1))
+        .unwrap_or_default()
+        .iter()
+        .map(|line| render_line(line, theme))
+        .collect();
+
+    let base_url = Url::parse(base_url).ok();
+    let links: Vec<String> = links
+        .take()
+        .into_iter()
+        .map(|link| resolve_link(&link, base_url.as_ref()))
+        .collect();
+    if !links.is_empty() {
+        lines.push(Line::default());
+    }
+    for (i, link) in links.iter().enumerate() {
+        lines.push(Line::from(vec![
+            Span::styled(format!("[{}] ", i + 1), theme.muted_style()),
+            Span::styled(
+                link.clone(),
+                Style::default()
+                    .fg(theme.link)
+                    .add_modifier(Modifier::UNDERLINED),
+            ),
+        ]));
+    }
+    RenderedHtml { lines, links }
+}
+
+/// Resolves a link against the URL of the page it's on. Links that can't
+/// be resolved are kept as they are.
+fn resolve_link(link: &str, base_url: Option<&Url>) -> String {
+    match base_url.map(|base_url| base_url.join(link)) {
+        Some(Ok(url)) => url.to_string(),
+        _ => link.to_string(),
+    }
+}
+
+/// Converts a line rendered by html2text into a styled line, replacing
+/// markers with the characters they stand for.
+fn render_line(line: &TaggedLine<Vec<RichAnnotation>>, theme: &Theme) -> Line<'static> {
+    let mut spans: Vec<Span> = Vec::new();
+    // Markers come before the text they apply to, and style the rest of
+    // the line.
+    let mut line_style = Style::default();
+    for tagged_string in line.tagged_strings() {
+        let style = annotation_style(&tagged_string.tag, theme);
+        let mut text = String::new();
+        for c in tagged_string.s.chars() {
+            let (marker, marker_style) = match c {
+                HEADING_MARKER => {
+                    line_style = line_style.fg(theme.heading).add_modifier(Modifier::BOLD);
+                    ("#", line_style)
+                }
+                QUOTE_MARKER => {
+                    line_style = line_style.add_modifier(Modifier::ITALIC);
+                    ("│", theme.muted_style())
+                }
+                BULLET_MARKER => ("•", Style::default().fg(theme.accent)),
+                _ => {
+                    text.push(c);
+                    continue;
+                }
+            };
+            if !text.is_empty() {
+                spans.push(Span::styled(
+                    std::mem::take(&mut text),
+                    line_style.patch(style),
+                ));
+            }
+            spans.push(Span::styled(marker, marker_style));
+        }
+        if !text.is_empty() {
+            spans.push(Span::styled(text, line_style.patch(style)));
+        }
+    }
+    Line::from(spans)
+}
+
+/// Retrieves the style of text with html2text's annotations. Annotations
+/// are listed outermost first, so inner annotations take precedence.
+fn annotation_style(annotations: &[RichAnnotation], theme: &Theme) -> Style {
+    annotations
+        .iter()
+        .fold(Style::default(), |style, annotation| match annotation {
+            RichAnnotation::Strong => style.add_modifier(Modifier::BOLD),
+            RichAnnotation::Emphasis => style.add_modifier(Modifier::ITALIC),
+            RichAnnotation::Strikeout => style.add_modifier(Modifier::CROSSED_OUT),
+            RichAnnotation::Code | RichAnnotation::Preformat(_) => style.fg(theme.code),
+            RichAnnotation::Link(_) => style.fg(theme.link).add_modifier(Modifier::UNDERLINED),
+            RichAnnotation::Image(_) => style.patch(theme.muted_style()),
+            // Entries' own colors are left out in favor of the theme.
+            _ => style,
+        })
+}
+
+/// Converts plain text to HTML, with blank lines separating paragraphs.
+pub fn text_to_html(text: &str) -> String {
+    text.split("\n\n")
+        .map(str::trim)
+        .filter(|paragraph| !paragraph.is_empty())
+        .map(|paragraph| {
+            let escaped = paragraph
+                .replace('&', "&amp;")
+                .replace('<', "&lt;")
+                .replace('>', "&gt;")
+                .replace('\n', "<br>");
+            format!("<p>{}</p>", escaped)
+        })
+        .collect()
+}
+
+/// The HTML an entry's content was last rendered from, kept until the
+/// content or width changes so that content isn't rendered every frame.
+#[derive(Default)]
+pub struct RenderedContent {
+    html: String,
+    base_url: String,
+    width: usize,
+    rendered: RenderedHtml,
+}
+
+impl RenderedContent {
+    /// Retrieves HTML rendered to a width, rendering it if it wasn't
+    /// rendered last time.
+    pub fn render(
+        &mut self,
+        html: &str,
+        base_url: &str,
+        width: usize,
+        theme: &Theme,
+    ) -> &RenderedHtml {
+        if self.html != html || self.base_url != base_url || self.width != width {
+            self.html = html.to_string();
+            self.base_url = base_url.to_string();
+            self.width = width;
+            self.rendered = render_html(html, base_url, width, theme);
+        }
+        &self.rendered
+    }
+
+    /// The links of the content last rendered.
+    pub fn links(&self) -> &[String] {
+        &self.rendered.links
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::theme::ThemeName;
+
+    /// Joins the text of rendered lines.
+    fn text(lines: &[Line]) -> Vec<String> {
+        lines.iter().map(|line| line.to_string()).collect()
+    }
+
+    /// Tests that structure is kept and styled.
+    #[test]
+    fn test_render_html() {
+        let theme = Theme::default();
+        let rendered = render_html(
+            r#"<h2>Heading</h2>
+            <p><b>Bold</b>, <em>italic</em> and <a href="https://example.com">a link</a>.</p>
+            <blockquote>Quoted</blockquote>
+            <ul><li>Item</li></ul>
+            <pre>let x = 1;</pre>"#,
+            "",
+            40,
+            &theme,
+        );
+        let lines: Vec<&Line> = rendered.lines.iter().filter(|l| l.width() > 0).collect();
+
+        assert!(lines[0].to_string() == "## Heading");
+        assert!(lines[0].spans.last().unwrap().style.fg == Some(theme.heading));
+        let spans = &lines[1].spans;
+        assert!(spans[0].content == "Bold");
+        assert!(spans[0].style.add_modifier.contains(Modifier::BOLD));
+        assert!(spans[2].content == "italic");
+        assert!(spans[2].style.add_modifier.contains(Modifier::ITALIC));
+        assert!(spans[4].content == "a link");
+        assert!(spans[4].style.fg == Some(theme.link));
+        assert!(lines[2].to_string() == "│ Quoted");
+        assert!(
+            lines[2].spans[1]
+                .style
+                .add_modifier
+                .contains(Modifier::ITALIC)
+        );
+        assert!(lines[3].to_string() == "• Item");
+        assert!(lines[4].to_string() == "let x = 1;");
+        assert!(lines[4].spans[0].style.fg == Some(theme.code));
+    }
+
+    /// Tests that rendering reflows text to the width it's given.
+    #[test]
+    fn test_render_html_reflows() {
+        let theme = Theme::builtin(ThemeName::Monochrome);
+        let html = "<p>The quick brown fox jumps over the lazy dog</p>";
+        assert!(
+            text(&render_html(html, "", 80, &theme).lines)
+                == ["The quick brown fox jumps over the lazy dog"]
+        );
+        let narrow = text(&render_html(html, "", 20, &theme).lines);
+        assert!(narrow == ["The quick brown fox", "jumps over the lazy", "dog"]);
+        let quoted = format!("<blockquote><ul><li>{}</li></ul></blockquote>", html);
+        let quoted = text(&render_html(&quoted, "", 20, &theme).lines);
+        assert!(quoted[0] == "│ • The quick brown");
+        assert!(quoted.iter().all(|line| line.chars().count() <= 20));
+
+        let mut rendered_content = RenderedContent::default();
+        assert!(rendered_content.render(html, "", 20, &theme).lines.len() == 3);
+        assert!(rendered_content.render(html, "", 80, &theme).lines.len() == 1);
+    }
+
+    /// Tests that links are numbered, resolved against the base URL and
+    /// listed as footnotes.
+    #[test]
+    fn test_render_html_links() {
+        let theme = Theme::builtin(ThemeName::Monochrome);
+        let rendered = render_html(
+            r#"<p>See <a href="/about">this</a> and <a href="https://example.org/feed.xml">that</a>.</p>"#,
+            "https://example.com/posts/1",
+            80,
+            &theme,
+        );
+        assert!(rendered.links == ["https://example.com/about", "https://example.org/feed.xml"]);
+        assert!(
+            text(&rendered.lines)
+                == [
+                    "See this[1] and that[2].",
+                    "",
+                    "[1] https://example.com/about",
+                    "[2] https://example.org/feed.xml",
+                ]
+        );
+        assert!(render_html("<p>None</p>", "", 80, &theme).links.is_empty());
+    }
+
+    /// Tests that plain text is escaped and split into paragraphs.
+    #[test]
+    fn test_text_to_html() {
+        assert!(text_to_html("a < b\nc\n\nd") == "<p>a &lt; b<br>c</p><p>d</p>");
+        assert!(text_to_html("").is_empty());
+    }
+}
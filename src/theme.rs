@@ -0,0 +1,174 @@
+//! The colors the TUI is drawn with.
+
+use ratatui::style::{Color, Modifier, Style};
+use serde::Deserialize;
+
+/// The colors of the TUI's styled elements. `Color::Reset` leaves an
+/// element in the terminal's own colors.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Theme {
+    /// Text that isn't styled otherwise.
+    pub text: Color,
+    /// The background of views and popups.
+    pub background: Color,
+    /// Borders and scrollbars.
+    pub border: Color,
+    /// The titles of views and popups.
+    pub title: Color,
+    /// Secondary text, like dates.
+    pub muted: Color,
+    /// The text of the selected row.
+    pub selection_fg: Color,
+    /// The background of the selected row.
+    pub selection_bg: Color,
+    /// Unread counts and markers, and starred markers.
+    pub unread: Color,
+    /// The feed an entry in a virtual feed came from, and the sync
+    /// status line.
+    pub accent: Color,
+    /// Popups that accept user input.
+    pub input: Color,
+    /// Errors.
+    pub error: Color,
+    /// Keys in instructions and help popups.
+    pub key: Color,
+    /// Headings in entries.
+    pub heading: Color,
+    /// Links in entries.
+    pub link: Color,
+    /// Code in entries.
+    pub code: Color,
+}
+
+/// The built-in themes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThemeName {
+    /// Bright colors for dark terminals.
+    Dark,
+    /// The 16 basic colors, picked to stay readable on light terminals.
+    Light,
+    /// No colors at all, only bold, dim and reversed text.
+    Monochrome,
+}
+
+impl Theme {
+    /// Retrieves a built-in theme.
+    pub fn builtin(name: ThemeName) -> Theme {
+        match name {
+            ThemeName::Dark => Theme {
+                unread: Color::Rgb(255, 179, 0),
+                accent: Color::Rgb(255, 239, 0),
+                input: Color::Rgb(255, 161, 0),
+                error: Color::Rgb(255, 0, 0),
+                key: Color::Blue,
+                heading: Color::Rgb(255, 179, 0),
+                link: Color::Rgb(95, 175, 255),
+                code: Color::Rgb(255, 161, 0),
+                ..Theme::builtin(ThemeName::Monochrome)
+            },
+            ThemeName::Light => Theme {
+                muted: Color::DarkGray,
+                unread: Color::Magenta,
+                accent: Color::Green,
+                input: Color::Magenta,
+                error: Color::Red,
+                key: Color::Blue,
+                heading: Color::Magenta,
+                link: Color::Blue,
+                code: Color::Green,
+                ..Theme::builtin(ThemeName::Monochrome)
+            },
+            ThemeName::Monochrome => Theme {
+                text: Color::Reset,
+                background: Color::Reset,
+                border: Color::Reset,
+                title: Color::Reset,
+                muted: Color::Reset,
+                selection_fg: Color::Reset,
+                selection_bg: Color::Reset,
+                unread: Color::Reset,
+                accent: Color::Reset,
+                input: Color::Reset,
+                error: Color::Reset,
+                key: Color::Reset,
+                heading: Color::Reset,
+                link: Color::Reset,
+                code: Color::Reset,
+            },
+        }
+    }
+
+    /// The style of views and popups, which their contents are drawn over.
+    pub fn base(&self) -> Style {
+        let mut style = Style::default();
+        if self.text != Color::Reset {
+            style = style.fg(self.text);
+        }
+        if self.background != Color::Reset {
+            style = style.bg(self.background);
+        }
+        style
+    }
+
+    /// The style of borders and scrollbars.
+    pub fn border_style(&self) -> Style {
+        match self.border {
+            Color::Reset => Style::default(),
+            color => Style::default().fg(color),
+        }
+    }
+
+    /// The style of titles.
+    pub fn title_style(&self) -> Style {
+        match self.title {
+            Color::Reset => Style::default(),
+            color => Style::default().fg(color),
+        }
+    }
+
+    /// The style of secondary text.
+    pub fn muted_style(&self) -> Style {
+        Style::default().fg(self.muted).add_modifier(Modifier::DIM)
+    }
+
+    /// The style of the selected row. Without selection colors, the row
+    /// is reversed.
+    pub fn selection_style(&self) -> Style {
+        if self.selection_fg == Color::Reset && self.selection_bg == Color::Reset {
+            return Style::default().add_modifier(Modifier::REVERSED);
+        }
+        Style::default().fg(self.selection_fg).bg(self.selection_bg)
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::builtin(ThemeName::Dark)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tests that the monochrome theme uses no colors.
+    #[test]
+    fn test_monochrome_theme() {
+        let theme = Theme::builtin(ThemeName::Monochrome);
+        assert!(theme.base() == Style::default());
+        assert!(theme.selection_style() == Style::default().add_modifier(Modifier::REVERSED));
+        let styles = [
+            theme.border_style(),
+            theme.title_style(),
+            theme.muted_style(),
+            Style::default().fg(theme.unread),
+            Style::default().fg(theme.error),
+            Style::default().fg(theme.key),
+        ];
+        for style in styles {
+            assert!(matches!(style.fg, None | Some(Color::Reset)));
+            assert!(style.bg.is_none());
+        }
+    }
+}
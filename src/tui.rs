@@ -1,11 +1,16 @@
 //! The terminal UI.
 
+use std::cmp::Reverse;
+use std::io::Write;
+use std::ops::Range;
+
+use base64::prelude::*;
 use chrono::Local;
-use ratatui::layout::{Constraint, Direction, Flex, Layout, Margin, Position};
-use ratatui::style::{Color, Modifier, Style};
+use ratatui::layout::{Constraint, Direction, Flex, Layout, Margin, Position, Rect};
+use ratatui::style::Style;
 use ratatui::text::Span;
 use ratatui::widgets::{
-    Borders, Clear, List, ListItem, Scrollbar, ScrollbarOrientation, ScrollbarState, Wrap,
+    Clear, List, ListItem, Scrollbar, ScrollbarOrientation, ScrollbarState, Wrap,
 };
 use ratatui::{
     Frame,
@@ -15,15 +20,22 @@ use ratatui::{
 };
 use unicode_width::{self, UnicodeWidthChar, UnicodeWidthStr};
 
-use crate::app::App;
+use crate::app::{App, RssFeed, VirtualFeed};
+use crate::keymap::{Action, Mode};
+use crate::theme::Theme;
 
 pub const SPINNER_CHARS: &[char] = &['/', '-', '\\', '|'];
 
-/// A row in the list view. A row can be either an RSS feed
-/// or an entry belonging to an RSS feed.
+/// A row in the list view. A row can be a virtual feed, an entry listed
+/// in a virtual feed, a folder, an RSS feed or an entry belonging to an
+/// RSS feed.
+#[derive(Debug, PartialEq)]
 pub enum Row {
-    RssFeed(usize),         // Feed index.
-    RssEntry(usize, usize), // Feed index and entry index.
+    VirtualFeed(VirtualFeed),
+    VirtualFeedEntry(VirtualFeed, usize, usize), // Feed index and entry index.
+    Folder(usize),                               // Folder index.
+    RssFeed(usize),                              // Feed index.
+    RssEntry(usize, usize),                      // Feed index and entry index.
 }
 
 /// View states the reader supports.
@@ -32,11 +44,14 @@ pub enum ViewState {
     #[default]
     /// A list of feeds with nested entries.
     RssFeeds,
-    /// An entry, displaying entry content.
+    /// An entry, displaying entry content. The entry is kept by ID, since
+    /// its index changes as feeds are synced, added and deleted.
     RssEntry {
-        rss_feed_index: usize,
-        rss_entry_index: usize,
+        rss_feed_id: String,
+        rss_entry_id: String,
     },
+    /// A list of entries matching a search.
+    SearchResults,
 }
 
 /// The popup state, representing a type of popup that can
@@ -47,55 +62,137 @@ pub enum PopupState {
     /// The popup for adding a new feed. Accepts user input.
     AddRssFeed,
     /// The popup asking the user to confirm the deletion of
-    /// an RSS feed.
+    /// an RSS feed or folder.
     ConfirmDeleteRssFeed,
+    /// The popup for creating a folder. Accepts user input.
+    CreateFolder,
     /// The popup that displays errors.
     Error,
+    /// The popup for exporting feeds to an OPML file. Accepts
+    /// user input.
+    ExportOpml,
+    /// The popup for importing feeds from an OPML file. Accepts
+    /// user input.
+    ImportOpml,
+    /// The popup that displays informational messages.
+    Info,
+    /// The popup for marking entries older than a number of days as
+    /// read. Accepts user input.
+    MarkOlderRead,
+    /// The popup listing the links of the entry being read.
+    Links,
+    /// The popup for picking the folder to move a feed to.
+    MoveRssFeed,
+    /// The popup for picking one of the feeds found on a website.
+    PickDiscoveredRssFeed,
+    /// The popup for renaming a folder. Accepts user input.
+    RenameFolder,
+    /// The popup for searching entries. Accepts user input.
+    Search,
     /// The popup that displays keybinds for navigating
     /// an RSS entry.
     RssEntryHelp,
     /// The popup that displays keybinds for navigating
     /// the list of RSS feeds.
     RssFeedHelp,
-    /// The popup that indicates that syncing is happening.
-    Syncing,
+    /// The popup that summarizes the last sync, listing feeds
+    /// that failed to sync and why.
+    SyncReport,
+}
+
+impl PopupState {
+    /// Whether the popup accepts user input, in which case typed
+    /// characters edit the input rather than performing actions.
+    pub fn accepts_input(&self) -> bool {
+        matches!(
+            self,
+            PopupState::AddRssFeed
+                | PopupState::CreateFolder
+                | PopupState::RenameFolder
+                | PopupState::ImportOpml
+                | PopupState::ExportOpml
+                | PopupState::MarkOlderRead
+                | PopupState::Search
+        )
+    }
 }
 
 /// Draws the UI.
 pub fn ui(app: &mut App, frame: &mut Frame) {
+    // An entry that no longer exists can't be read, so it's closed.
+    if matches!(app.view_state, ViewState::RssEntry { .. })
+        && app.open_rss_entry_indices().is_none()
+    {
+        app.close_rss_entry();
+    }
     match app.view_state {
         ViewState::RssFeeds => draw_list(frame, app),
-        ViewState::RssEntry {
-            rss_feed_index,
-            rss_entry_index,
-        } => draw_rss_entry(frame, app, rss_feed_index, rss_entry_index),
+        ViewState::RssEntry { .. } => {
+            if let Some((rss_feed_index, rss_entry_index)) = app.open_rss_entry_indices() {
+                draw_rss_entry(frame, app, rss_feed_index, rss_entry_index);
+            }
+        }
+        ViewState::SearchResults => draw_search_results(frame, app),
     }
 
     if let PopupState::RssEntryHelp = app.popup {
-        draw_rss_entry_help_popup(frame);
+        draw_help_popup(frame, app, Mode::Entry, "Entry commands");
     }
     if let PopupState::RssFeedHelp = app.popup {
-        draw_rss_feed_help_popup(frame);
+        draw_help_popup(frame, app, Mode::Feeds, "Feed commands");
     }
     if let PopupState::AddRssFeed = app.popup {
-        draw_add_rss_feed_popup(frame, app);
+        draw_input_popup(frame, app, "Add feed");
+    }
+    if let PopupState::ImportOpml = app.popup {
+        draw_input_popup(frame, app, "Import OPML file");
+    }
+    if let PopupState::ExportOpml = app.popup {
+        draw_input_popup(frame, app, "Export OPML file");
+    }
+    if let PopupState::Search = app.popup {
+        draw_input_popup(frame, app, "Search entries");
+    }
+    if let PopupState::MarkOlderRead = app.popup {
+        draw_input_popup(frame, app, "Mark read older than (days)");
+    }
+    if let PopupState::CreateFolder = app.popup {
+        draw_input_popup(frame, app, "New folder");
+    }
+    if let PopupState::RenameFolder = app.popup {
+        draw_input_popup(frame, app, "Rename folder");
+    }
+    if let PopupState::MoveRssFeed = app.popup {
+        draw_move_rss_feed_popup(frame, app);
+    }
+    if let PopupState::PickDiscoveredRssFeed = app.popup {
+        draw_pick_discovered_rss_feed_popup(frame, app);
+    }
+    if let PopupState::Links = app.popup {
+        draw_links_popup(frame, app);
     }
     if let PopupState::ConfirmDeleteRssFeed = app.popup {
         draw_confirm_delete_rss_feed_popup(frame, app);
     }
-    if let PopupState::Syncing = app.popup {
-        draw_syncing_popup(frame, app);
+    if let PopupState::SyncReport = app.popup {
+        draw_sync_report_popup(frame, app);
     }
-    if let PopupState::Error = app.popup {
-        if let Some(error_message) = app.error_message.clone() {
-            draw_error_popup(frame, &error_message);
-        }
+    if let PopupState::Error = app.popup
+        && let Some(error_message) = app.error_message.clone()
+    {
+        draw_error_popup(frame, app, &error_message);
+    }
+    if let PopupState::Info = app.popup
+        && let Some(info_message) = app.info_message.clone()
+    {
+        draw_info_popup(frame, app, &info_message);
     }
 }
 
 /// Draws the list of RSS feeds and their entries.
 fn draw_list(frame: &mut ratatui::Frame, app: &mut App) {
-    let area = frame.area();
+    let theme = app.theme;
+    let (area, sync_status_area) = split_sync_status_area(frame.area(), app);
     app.last_frame_area = area;
     let chunks = Layout::default()
         .direction(Direction::Vertical)
@@ -103,10 +200,11 @@ fn draw_list(frame: &mut ratatui::Frame, app: &mut App) {
         .split(area);
 
     let rows = get_rows(app);
+    // Rows can disappear from under the cursor, like read entries from
+    // the All Unread virtual feed.
+    app.cursor = app.cursor.min(rows.len().saturating_sub(1));
     let visible_height = area.height as usize - 2;
-    let start = if rows.len() <= visible_height {
-        0
-    } else if app.cursor < visible_height / 2 {
+    let start = if rows.len() <= visible_height || app.cursor < visible_height / 2 {
         0
     } else if app.cursor + visible_height / 2 >= rows.len() {
         rows.len() - visible_height
@@ -118,9 +216,36 @@ fn draw_list(frame: &mut ratatui::Frame, app: &mut App) {
     let items: Vec<ListItem> = visible_rows
         .iter()
         .map(|row| match row {
+            Row::Folder(folder_index) => {
+                let folder = &app.folders[*folder_index];
+                let mut spans: Vec<Span> = Vec::new();
+                let prefix = if folder.expanded {
+                    Span::raw("▼ ")
+                } else {
+                    Span::raw("▶ ")
+                };
+                spans.push(prefix);
+                let truncated_name = truncate_str(&folder.name, area.width as usize);
+                spans.push(Span::raw(truncated_name).bold());
+                let num_unread_rss_entries: usize = app
+                    .rss_feeds
+                    .iter()
+                    .filter(|f| f.folder.as_ref() == Some(&folder.name))
+                    .map(|f| f.rss_entries.iter().filter(|a| !a.read).count())
+                    .sum();
+                if num_unread_rss_entries > 0 {
+                    spans.push(Span::raw(format!(" {}*", num_unread_rss_entries)).fg(theme.unread));
+                }
+
+                ListItem::new(Line::from(spans))
+            }
             Row::RssFeed(rss_feed_index) => {
                 let rss_feed = &app.rss_feeds[*rss_feed_index];
                 let mut spans: Vec<Span> = Vec::new();
+                // Feeds in a folder are indented beneath it.
+                if app.folder_index(rss_feed).is_some() {
+                    spans.push(Span::raw("  "));
+                }
                 let prefix = if rss_feed.expanded {
                     Span::raw("▼ ")
                 } else {
@@ -129,13 +254,10 @@ fn draw_list(frame: &mut ratatui::Frame, app: &mut App) {
                 spans.push(prefix);
                 let truncated_title = truncate_str(&rss_feed.title, area.width as usize);
                 spans.push(Span::raw(truncated_title));
-                let num_unread_rss_entries = rss_feed
-                    .rss_entries
-                    .iter()
-                    .filter(|a| a.read == false)
-                    .count();
+                let num_unread_rss_entries =
+                    rss_feed.rss_entries.iter().filter(|a| !a.read).count();
                 let num_unread_rss_entries_formatted =
-                    Span::raw(format!(" {}*", num_unread_rss_entries)).fg(Color::Rgb(255, 179, 0));
+                    Span::raw(format!(" {}*", num_unread_rss_entries)).fg(theme.unread);
                 let postfix = if num_unread_rss_entries == 0 {
                     Span::default()
                 } else {
@@ -146,68 +268,64 @@ fn draw_list(frame: &mut ratatui::Frame, app: &mut App) {
                 ListItem::new(Line::from(spans))
             }
             Row::RssEntry(rss_feed_index, rss_entry_index) => {
-                let rss_entry = &app.rss_feeds[*rss_feed_index].rss_entries[*rss_entry_index];
-                let wrapped_width = if area.width.saturating_sub(24) > 0 {
-                    area.width.saturating_sub(24)
+                let indent = if app.folder_index(&app.rss_feeds[*rss_feed_index]).is_some() {
+                    "      "
                 } else {
-                    area.width
+                    "    "
                 };
-                let wrapped_title = textwrap::wrap(&rss_entry.title, (wrapped_width) as usize);
-                let date = rss_entry
-                    .published
-                    .with_timezone(&Local)
-                    .format("%Y-%m-%d %I:%M%P")
-                    .to_string();
-
-                let mut lines: Vec<Line> = Vec::new();
-
-                for (i, wrapped_line) in wrapped_title.iter().enumerate() {
-                    let mut spans: Vec<Span> = Vec::new();
-                    spans.push(Span::raw("    "));
-                    spans.push(Span::raw(wrapped_line.to_string()));
-                    if i == wrapped_title.len() - 1 {
-                        if !rss_entry.read {
-                            spans.push(Span::styled(
-                                "*",
-                                Style::default().fg(Color::Rgb(255, 179, 0)),
-                            ));
-                        }
-                        spans.push(Span::styled(format!(" {}", date), Style::default().dim()));
-                    }
-                    lines.push(Line::from(spans));
+                rss_entry_list_item(app, *rss_feed_index, *rss_entry_index, indent, false, area)
+            }
+            Row::VirtualFeed(virtual_feed) => {
+                let mut spans: Vec<Span> = Vec::new();
+                let prefix = if app.expanded_virtual_feeds.contains(virtual_feed) {
+                    Span::raw("▼ ")
+                } else {
+                    Span::raw("▶ ")
+                };
+                spans.push(prefix);
+                spans.push(Span::raw(virtual_feed.title()).italic());
+                let num_unread_rss_entries = app
+                    .rss_feeds
+                    .iter()
+                    .flat_map(|f| f.rss_entries.iter())
+                    .filter(|e| !e.read && virtual_feed.contains(e))
+                    .count();
+                if num_unread_rss_entries > 0 {
+                    spans.push(Span::raw(format!(" {}*", num_unread_rss_entries)).fg(theme.unread));
                 }
 
-                ListItem::from(lines)
+                ListItem::new(Line::from(spans))
+            }
+            Row::VirtualFeedEntry(_, rss_feed_index, rss_entry_index) => {
+                rss_entry_list_item(app, *rss_feed_index, *rss_entry_index, "    ", true, area)
             }
         })
         .collect();
 
-    let instructions = Line::from(vec![
-        " ↓".into(),
-        "<j> ".blue().bold().into(),
-        "↑".into(),
-        "<k> ".blue().bold().into(),
-        "Add".into(),
-        "<a> ".blue().bold().into(),
-        "Help".into(),
-        "<h> ".blue().bold().into(),
-        "Quit".into(),
-        "<q> ".blue().bold().into(),
-    ]);
+    let instructions = instructions(
+        app,
+        Mode::Feeds,
+        &[
+            Action::ScrollDown,
+            Action::ScrollUp,
+            Action::AddFeed,
+            Action::Help,
+            Action::Quit,
+        ],
+    );
 
     let list = List::new(items)
         .block(
-            Block::default()
+            bordered_block(&theme)
                 .title("Feeds".bold())
-                .borders(Borders::ALL)
                 .title_bottom(instructions.centered()),
         )
-        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        .highlight_style(theme.selection_style());
 
     let mut state = ratatui::widgets::ListState::default();
     state.select(Some(app.cursor.saturating_sub(start)));
 
-    let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight);
+    let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight).style(theme.border_style());
     frame.render_stateful_widget(
         scrollbar,
         chunks[0].inner(Margin {
@@ -217,6 +335,155 @@ fn draw_list(frame: &mut ratatui::Frame, app: &mut App) {
         &mut ScrollbarState::default(),
     );
     frame.render_stateful_widget(list, area, &mut state);
+    if let Some(sync_status_area) = sync_status_area {
+        draw_sync_status(frame, app, sync_status_area);
+    }
+}
+
+/// Creates the list item of an entry, with its title wrapped and followed
+/// by its publication date. Entries listed away from their feed show the
+/// feed's title too.
+fn rss_entry_list_item<'a>(
+    app: &'a App,
+    rss_feed_index: usize,
+    rss_entry_index: usize,
+    indent: &'a str,
+    show_rss_feed: bool,
+    area: Rect,
+) -> ListItem<'a> {
+    let theme = app.theme;
+    let rss_feed = &app.rss_feeds[rss_feed_index];
+    let rss_entry = &rss_feed.rss_entries[rss_entry_index];
+    let wrapped_width = if area.width.saturating_sub(24) > 0 {
+        area.width.saturating_sub(24)
+    } else {
+        area.width
+    };
+    let wrapped_title = textwrap::wrap(&rss_entry.title, (wrapped_width) as usize);
+    let date = rss_entry
+        .published
+        .with_timezone(&Local)
+        .format(&app.date_format)
+        .to_string();
+
+    let mut lines: Vec<Line> = Vec::new();
+
+    for (i, wrapped_line) in wrapped_title.iter().enumerate() {
+        let mut spans: Vec<Span> = Vec::new();
+        spans.push(Span::raw(indent));
+        spans.push(Span::raw(wrapped_line.to_string()));
+        if i == wrapped_title.len() - 1 {
+            if !rss_entry.read {
+                spans.push(Span::styled("*", Style::default().fg(theme.unread)));
+            }
+            if rss_entry.starred {
+                spans.push(Span::styled(" ★", Style::default().fg(theme.unread)));
+            }
+            if show_rss_feed {
+                spans.push(Span::styled(
+                    format!(" {}", truncate_str(&rss_feed.title, 24)),
+                    Style::default().fg(theme.accent),
+                ));
+            }
+            spans.push(Span::styled(format!(" {}", date), theme.muted_style()));
+        }
+        lines.push(Line::from(spans));
+    }
+
+    ListItem::from(lines)
+}
+
+/// Draws the entries matching a search, with matches highlighted in
+/// their titles.
+fn draw_search_results(frame: &mut ratatui::Frame, app: &mut App) {
+    let theme = app.theme;
+    let area = frame.area();
+    app.last_frame_area = area;
+    let items: Vec<ListItem> = app
+        .search_results
+        .iter()
+        .map(|search_result| {
+            let mut title_spans = vec![Span::raw(" ")];
+            title_spans.extend(highlight_matches(
+                &search_result.title,
+                &search_result.title_matches,
+                &theme,
+            ));
+            let mut lines = vec![Line::from(title_spans)];
+            if let Some(rss_feed) = app
+                .rss_feeds
+                .iter()
+                .find(|f| f.id == search_result.rss_feed_id)
+            {
+                let mut details = format!("    {}", rss_feed.title);
+                if let Some(rss_entry) = rss_feed
+                    .rss_entries
+                    .iter()
+                    .find(|e| e.id == search_result.rss_entry_id)
+                {
+                    let date = rss_entry
+                        .published
+                        .with_timezone(&Local)
+                        .format(&app.date_format);
+                    details.push_str(&format!(" {}", date));
+                }
+                lines.push(Line::from(Span::styled(
+                    truncate_str(&details, area.width.saturating_sub(2) as usize),
+                    theme.muted_style(),
+                )));
+            }
+            ListItem::from(lines)
+        })
+        .collect();
+
+    let instructions = labelled_instructions(
+        app,
+        Mode::SearchResults,
+        &[
+            ("Open", Action::Select),
+            ("Search", Action::Search),
+            ("Back", Action::Back),
+        ],
+    );
+    let title = format!(
+        "Search: {} ({} results)",
+        app.search_query.as_deref().unwrap_or_default(),
+        app.search_results.len()
+    );
+    let list = List::new(items)
+        .block(
+            bordered_block(&theme)
+                .title(truncate_str(&title, area.width.saturating_sub(2) as usize).bold())
+                .title_bottom(instructions.centered()),
+        )
+        .highlight_style(theme.selection_style());
+
+    let mut state = ratatui::widgets::ListState::default();
+    state.select(Some(app.search_cursor));
+    frame.render_stateful_widget(list, area, &mut state);
+}
+
+/// Splits text into spans, highlighting the given byte ranges.
+fn highlight_matches<'a>(text: &'a str, matches: &[Range<usize>], theme: &Theme) -> Vec<Span<'a>> {
+    let mut spans = Vec::new();
+    let mut position = 0;
+    for range in matches {
+        if range.start < position || range.end > text.len() {
+            continue;
+        }
+        if range.start > position {
+            spans.push(Span::raw(&text[position..range.start]));
+        }
+        spans.push(Span::styled(
+            &text[range.clone()],
+            Style::default().fg(theme.unread).bold(),
+        ));
+        position = range.end;
+    }
+    if position < text.len() {
+        spans.push(Span::raw(&text[position..]));
+    }
+    spans
 }
 
 /// Truncates a string to a specific width.
@@ -249,43 +516,106 @@ fn draw_rss_entry(
     rss_feed_index: usize,
     rss_entry_index: usize,
 ) {
-    let size = frame.area();
+    let theme = app.theme;
+    let (size, sync_status_area) = split_sync_status_area(frame.area(), app);
     app.last_frame_area = size;
+    let instructions = instructions(
+        app,
+        Mode::Entry,
+        &[
+            Action::ScrollDown,
+            Action::ScrollUp,
+            Action::Help,
+            Action::Back,
+        ],
+    );
     let rss_entry = &mut app.rss_feeds[rss_feed_index].rss_entries[rss_entry_index];
-    let instructions = Line::from(vec![
-        " ↓".into(),
-        "<j> ".blue().bold().into(),
-        "↑".into(),
-        "<k> ".blue().bold().into(),
-        "Help".into(),
-        "<h> ".blue().bold().into(),
-        "Back".into(),
-        "<q> ".blue().bold().into(),
-    ]);
-    let wrapped_lines = wrap_str(&rss_entry.content, (frame.area().width - 2) as usize);
-    rss_entry.content_total_lines = wrapped_lines.len();
-    let visible_lines = wrapped_lines
+    // Content is rendered to the width of the pane, so it reflows when the
+    // pane is resized.
+    let lines = &app
+        .rendered_content
+        .render(
+            rss_entry.displayed_html(app.show_full_content),
+            &rss_entry.link,
+            (frame.area().width - 2) as usize,
+            &theme,
+        )
+        .lines;
+    rss_entry.content_total_lines = lines.len();
+    let text = lines
         .iter()
         .skip(app.rss_entry_scroll as usize)
-        .take(size.height as usize);
-    let text = visible_lines
-        .map(|l| Line::from(l.clone()))
+        .take(size.height as usize)
+        .cloned()
         .collect::<Vec<_>>();
     let truncated_title = truncate_str(&rss_entry.title, (frame.area().width - 2) as usize);
+    let mut title = vec![truncated_title.bold()];
+    if rss_entry.starred {
+        title.push(" ★".fg(theme.unread));
+    }
+    if let Some(full_content) = &rss_entry.full_content
+        && app.show_full_content
+    {
+        let fetched = full_content.fetched.with_timezone(&Local);
+        title.push(Span::styled(
+            format!(" · full text, fetched {}", fetched.format(&app.date_format)),
+            theme.muted_style(),
+        ));
+    }
     let paragraph = Paragraph::new(text).block(
-        Block::default()
-            .title(truncated_title.clone().bold())
-            .title_bottom(instructions.centered())
-            .borders(Borders::ALL),
+        bordered_block(&theme)
+            .title(Line::from(title))
+            .title_bottom(instructions.centered()),
     );
     frame.render_widget(paragraph, size);
+    if let Some(sync_status_area) = sync_status_area {
+        draw_sync_status(frame, app, sync_status_area);
+    }
 }
 
 /// Retrieves all current rows.
+/// Virtual feeds come first, then folders, each followed by its feeds if
+/// it's expanded, then the feeds that aren't in a folder.
 pub fn get_rows(app: &App) -> Vec<Row> {
     let mut rows: Vec<Row> = Vec::new();
 
+    for virtual_feed in VirtualFeed::ALL {
+        rows.push(Row::VirtualFeed(virtual_feed));
+        if app.expanded_virtual_feeds.contains(&virtual_feed) {
+            let mut rss_entries: Vec<(usize, usize)> = Vec::new();
+            for (rss_feed_index, rss_feed) in app.rss_feeds.iter().enumerate() {
+                for (rss_entry_index, rss_entry) in rss_feed.rss_entries.iter().enumerate() {
+                    if virtual_feed.contains(rss_entry) {
+                        rss_entries.push((rss_feed_index, rss_entry_index));
+                    }
+                }
+            }
+            rss_entries.sort_by_key(|(f, e)| Reverse(app.rss_feeds[*f].rss_entries[*e].published));
+            rows.extend(
+                rss_entries
+                    .into_iter()
+                    .map(|(f, e)| Row::VirtualFeedEntry(virtual_feed, f, e)),
+            );
+        }
+    }
+
+    for (folder_index, folder) in app.folders.iter().enumerate() {
+        rows.push(Row::Folder(folder_index));
+        if folder.expanded {
+            push_rss_feed_rows(app, &mut rows, |f| f.folder.as_ref() == Some(&folder.name));
+        }
+    }
+    push_rss_feed_rows(app, &mut rows, |f| app.folder_index(f).is_none());
+    rows
+}
+
+/// Adds the rows of the feeds matching `filter`, and their entries if
+/// they're expanded.
+fn push_rss_feed_rows(app: &App, rows: &mut Vec<Row>, filter: impl Fn(&RssFeed) -> bool) {
     for (rss_feed_index, rss_feed) in app.rss_feeds.iter().enumerate() {
+        if !filter(rss_feed) {
+            continue;
+        }
         rows.push(Row::RssFeed(rss_feed_index));
         if rss_feed.expanded {
             for (rss_entry_index, _) in rss_feed.rss_entries.iter().enumerate() {
@@ -293,196 +623,286 @@ pub fn get_rows(app: &App) -> Vec<Row> {
             }
         }
     }
-    rows
 }
 
-/// Draws the RSS entry help popup, which displays keybinds used
-/// for navigating an RSS entry.
-fn draw_rss_entry_help_popup(frame: &mut ratatui::Frame) {
+/// Draws a help popup listing the keys of every action in a mode, as
+/// many to a line as fit.
+fn draw_help_popup(frame: &mut ratatui::Frame, app: &App, mode: Mode, title: &str) {
+    let theme = app.theme;
     let area = frame.area();
-    let lines: Vec<Line> = vec![
-        Line::from(vec![
-            "↓".into(),
-            "<j> ".blue().bold(),
-            "↑".into(),
-            "<k> ".blue().bold().into(),
-            "Help".into(),
-            "<h> ".blue().bold().into(),
-            "Back".into(),
-            "<q>".blue().bold().into(),
-        ]),
-        Line::from(vec![
-            "Fetch".into(),
-            "<f> ".blue().bold().into(),
-            "Open".into(),
-            "<o> ".blue().bold().into(),
-        ]),
-        Line::from(vec![
-            "Bottom".into(),
-            "<G> ".blue().bold().into(),
-            "Top".into(),
-            "<gg> ".blue().bold().into(),
-        ]),
-        Line::from(vec![
-            "Half page up".into(),
-            "<ctrl + u>".blue().bold().into(),
-        ]),
-        Line::from(vec![
-            "Half page down".into(),
-            "<ctrl + d>".blue().bold().into(),
-        ]),
-    ];
-
-    let instructions = Line::from(vec![" Back".into(), "<q> ".blue().bold().into()]);
-    let paragraph = Paragraph::new(lines)
-        .centered()
-        .style(Style::default())
+    let horizontal = Layout::horizontal([Constraint::Percentage(85)]).flex(Flex::Center);
+    let [popup_area] = horizontal.areas(area);
+    let max_width = popup_area.width.saturating_sub(2) as usize;
+
+    let mut lines: Vec<Line> = Vec::new();
+    let mut spans: Vec<Span> = Vec::new();
+    let mut width = 0;
+    for (action, keys) in app.keymap.bindings(mode) {
+        let Some(key) = keys.first() else {
+            continue;
+        };
+        let label = action.label();
+        let key = format!("<{}>", key);
+        let item_width = UnicodeWidthStr::width(label) + UnicodeWidthStr::width(key.as_str());
+        if width > 0 && width + 1 + item_width > max_width {
+            lines.push(Line::from(std::mem::take(&mut spans)));
+            width = 0;
+        }
+        if width > 0 {
+            spans.push(" ".into());
+            width += 1;
+        }
+        spans.push(label.into());
+        spans.push(key.fg(theme.key).bold());
+        width += item_width;
+    }
+    if !spans.is_empty() {
+        lines.push(Line::from(spans));
+    }
+
+    let instructions = labelled_instructions(app, Mode::Popup, &[("Back", Action::Back)]);
+    let height = lines.len() as u16 + 2;
+    let paragraph = Paragraph::new(lines).centered().block(
+        bordered_block(&theme)
+            .title(title.to_string())
+            .title_bottom(instructions.centered()),
+    );
+    let vertical = Layout::vertical([Constraint::Length(height)]).flex(Flex::Center);
+    let [popup_area] = vertical.areas(popup_area);
+
+    frame.render_widget(Clear, popup_area);
+    frame.render_widget(paragraph, popup_area);
+}
+
+/// Builds instructions listing the keys of actions, each shown with its
+/// label. Actions without keys are left out.
+fn instructions(app: &App, mode: Mode, actions: &[Action]) -> Line<'static> {
+    let actions: Vec<(&str, Action)> = actions.iter().map(|a| (a.label(), *a)).collect();
+    labelled_instructions(app, mode, &actions)
+}
+
+/// Builds instructions listing the keys of actions, each shown with the
+/// label given for it, like " Add<Enter> Back<q> ".
+fn labelled_instructions(app: &App, mode: Mode, actions: &[(&str, Action)]) -> Line<'static> {
+    let mut spans: Vec<Span> = vec![" ".into()];
+    for (label, action) in actions {
+        let key = app.keymap.key(mode, *action);
+        if key.is_empty() {
+            continue;
+        }
+        spans.push(label.to_string().into());
+        spans.push(format!("<{}> ", key).fg(app.theme.key).bold());
+    }
+    Line::from(spans)
+}
+
+/// Draws a popup that accepts user input, like the popup for adding a
+/// new RSS feed.
+fn draw_input_popup(frame: &mut ratatui::Frame, app: &mut App, title: &str) {
+    let theme = app.theme;
+    let area = frame.area();
+    let instructions = labelled_instructions(
+        app,
+        Mode::Input,
+        &[("Submit", Action::Select), ("Back", Action::Back)],
+    );
+    let input_paragraph = Paragraph::new(app.input.as_str())
+        .style(Style::default().fg(theme.input))
         .block(
-            Block::bordered()
-                .title("Entry commands")
+            bordered_block(&theme)
+                .style(theme.base().fg(theme.input))
+                .border_style(Style::default().fg(theme.input))
+                .title(title)
                 .title_bottom(instructions.centered()),
         );
-    let vertical = Layout::vertical([Constraint::Length(7)]).flex(Flex::Center);
+    let vertical = Layout::vertical([Constraint::Length(3)]).flex(Flex::Center);
     let horizontal = Layout::horizontal([Constraint::Percentage(85)]).flex(Flex::Center);
     let popup_area = area;
     let [popup_area] = vertical.areas(popup_area);
     let [popup_area] = horizontal.areas(popup_area);
+    let [input_area] = vertical.areas(popup_area);
+
+    #[allow(clippy::cast_possible_truncation)]
+    frame.set_cursor_position(Position::new(
+        input_area.x + app.character_index as u16 + 1,
+        input_area.y + 1,
+    ));
 
     frame.render_widget(Clear, popup_area);
-    frame.render_widget(paragraph, popup_area);
+    frame.render_widget(input_paragraph, popup_area);
 }
 
-/// Draws the RSS feed popup, which shows keybinds used for
-/// navigating the list of RSS feeds.
-fn draw_rss_feed_help_popup(frame: &mut ratatui::Frame) {
+/// Draws the popup for picking one of the feeds found on a website.
+fn draw_pick_discovered_rss_feed_popup(frame: &mut ratatui::Frame, app: &mut App) {
+    let theme = app.theme;
     let area = frame.area();
-    let lines: Vec<Line> = vec![
-        Line::from(vec![
-            "↓".into(),
-            "<j> ".blue().bold(),
-            "↑".into(),
-            "<k> ".blue().bold().into(),
-            "Add".into(),
-            "<a> ".blue().bold().into(),
-            "Help".into(),
-            "<h> ".blue().bold().into(),
-            "Quit".into(),
-            "<q> ".blue().bold().into(),
-        ]),
-        Line::from(vec![
-            "Select".into(),
-            "<Enter> ".blue().bold().into(),
-            "Delete".into(),
-            "<d> ".blue().bold().into(),
-            "Sync".into(),
-            "<s> ".blue().bold().into(),
-        ]),
-        Line::from(vec![
-            "Bottom".into(),
-            "<G> ".blue().bold().into(),
-            "Top".into(),
-            "<gg> ".blue().bold().into(),
-            "Collapse".into(),
-            "<c>".blue().bold().into(),
-        ]),
-        Line::from(vec![
-            "Half page up".into(),
-            "<ctrl + u> ".blue().bold().into(),
-        ]),
-        Line::from(vec![
-            "Half page down".into(),
-            "<ctrl + d>".blue().bold().into(),
-        ]),
-    ];
-
-    let instructions = Line::from(vec![" Back".into(), "<q> ".blue().bold().into()]);
-    let paragraph = Paragraph::new(lines)
-        .centered()
-        .style(Style::default())
+    let instructions = labelled_instructions(
+        app,
+        Mode::Popup,
+        &[("Add", Action::Select), ("Back", Action::Back)],
+    );
+    let text_width = (area.width as usize * 85 / 100).saturating_sub(4);
+    let items: Vec<ListItem> = app
+        .discovered_rss_feeds
+        .iter()
+        .map(|discovered_rss_feed| {
+            ListItem::new(truncate_str(&discovered_rss_feed.title, text_width))
+        })
+        .collect();
+    let list = List::new(items)
         .block(
-            Block::bordered()
-                .title("Feed commands")
+            bordered_block(&theme)
+                .title("Pick a feed")
                 .title_bottom(instructions.centered()),
-        );
-    let vertical = Layout::vertical([Constraint::Length(7)]).flex(Flex::Center);
+        )
+        .highlight_style(theme.selection_style());
+    let mut state = ratatui::widgets::ListState::default();
+    state.select(Some(app.discovered_rss_feed_cursor));
+    let height = (app.discovered_rss_feeds.len() as u16 + 2).min(area.height);
+    let vertical = Layout::vertical([Constraint::Length(height)]).flex(Flex::Center);
     let horizontal = Layout::horizontal([Constraint::Percentage(85)]).flex(Flex::Center);
     let popup_area = area;
     let [popup_area] = vertical.areas(popup_area);
     let [popup_area] = horizontal.areas(popup_area);
 
     frame.render_widget(Clear, popup_area);
-    frame.render_widget(paragraph, popup_area);
+    frame.render_stateful_widget(list, popup_area, &mut state);
 }
 
-/// Draws the popup for adding a new RSS feed.
-fn draw_add_rss_feed_popup(frame: &mut ratatui::Frame, app: &mut App) {
+/// Draws the popup listing the links of the entry being read, numbered
+/// like their footnotes.
+fn draw_links_popup(frame: &mut ratatui::Frame, app: &mut App) {
+    let theme = app.theme;
     let area = frame.area();
-    let instructions = Line::from(vec![
-        " Submit".into(),
-        "<Enter> ".blue().bold().into(),
-        "Back".into(),
-        "<q> ".blue().bold().into(),
-    ]);
-    let input_paragraph = Paragraph::new(app.input.as_str())
-        .style(Style::default().fg(Color::Rgb(255, 161, 0)))
+    let instructions = labelled_instructions(
+        app,
+        Mode::Popup,
+        &[
+            ("Open", Action::Select),
+            ("Copy", Action::CopyLink),
+            ("Add feed", Action::AddFeed),
+            ("Back", Action::Back),
+        ],
+    );
+    let text_width = (area.width as usize * 85 / 100).saturating_sub(4);
+    let items: Vec<ListItem> = app
+        .rendered_content
+        .links()
+        .iter()
+        .enumerate()
+        .map(|(i, link)| {
+            let number = format!("[{}] ", i + 1);
+            let link = truncate_str(link, text_width.saturating_sub(number.len()));
+            ListItem::new(Line::from(vec![
+                Span::styled(number, theme.muted_style()),
+                Span::raw(link),
+            ]))
+        })
+        .collect();
+    let height = (items.len() as u16 + 2).min(area.height);
+    let list = List::new(items)
         .block(
-            Block::bordered()
-                .title("Add feed")
+            bordered_block(&theme)
+                .title("Links")
                 .title_bottom(instructions.centered()),
-        );
-    let vertical = Layout::vertical([Constraint::Length(3)]).flex(Flex::Center);
+        )
+        .highlight_style(theme.selection_style());
+    let mut state = ratatui::widgets::ListState::default();
+    state.select(Some(app.link_cursor));
+    let vertical = Layout::vertical([Constraint::Length(height)]).flex(Flex::Center);
     let horizontal = Layout::horizontal([Constraint::Percentage(85)]).flex(Flex::Center);
     let popup_area = area;
     let [popup_area] = vertical.areas(popup_area);
     let [popup_area] = horizontal.areas(popup_area);
-    let [input_area] = vertical.areas(popup_area);
 
-    #[allow(clippy::cast_possible_truncation)]
-    frame.set_cursor_position(Position::new(
-        input_area.x + app.character_index as u16 + 1,
-        input_area.y + 1,
-    ));
+    frame.render_widget(Clear, popup_area);
+    frame.render_stateful_widget(list, popup_area, &mut state);
+}
+
+/// Draws the popup for picking the folder to move a feed to. The first
+/// option moves the feed out of its folder.
+fn draw_move_rss_feed_popup(frame: &mut ratatui::Frame, app: &mut App) {
+    let theme = app.theme;
+    let area = frame.area();
+    let instructions = labelled_instructions(
+        app,
+        Mode::Popup,
+        &[("Move", Action::Select), ("Back", Action::Back)],
+    );
+    let text_width = (area.width as usize * 85 / 100).saturating_sub(4);
+    let mut items: Vec<ListItem> = vec![ListItem::new("No folder".italic())];
+    items.extend(
+        app.folders
+            .iter()
+            .map(|folder| ListItem::new(truncate_str(&folder.name, text_width))),
+    );
+    let height = (items.len() as u16 + 2).min(area.height);
+    let list = List::new(items)
+        .block(
+            bordered_block(&theme)
+                .title("Move to folder")
+                .title_bottom(instructions.centered()),
+        )
+        .highlight_style(theme.selection_style());
+    let mut state = ratatui::widgets::ListState::default();
+    state.select(Some(app.folder_cursor));
+    let vertical = Layout::vertical([Constraint::Length(height)]).flex(Flex::Center);
+    let horizontal = Layout::horizontal([Constraint::Percentage(85)]).flex(Flex::Center);
+    let popup_area = area;
+    let [popup_area] = vertical.areas(popup_area);
+    let [popup_area] = horizontal.areas(popup_area);
 
     frame.render_widget(Clear, popup_area);
-    frame.render_widget(input_paragraph, popup_area);
+    frame.render_stateful_widget(list, popup_area, &mut state);
 }
 
 /// Draws the popup that confirms whether the users wants to delete an
-/// RSS feed.
+/// RSS feed or folder.
 fn draw_confirm_delete_rss_feed_popup(frame: &mut ratatui::Frame, app: &mut App) {
+    let theme = app.theme;
     let rows = get_rows(app);
     let row = &rows[app.cursor];
-    let rss_feed_name = match row {
-        Row::RssFeed(rss_feed_index) | Row::RssEntry(rss_feed_index, _) => {
-            app.rss_feeds[*rss_feed_index].title.as_str()
-        }
+    let (title, text) = match row {
+        Row::Folder(folder_index) => (
+            "Delete folder",
+            format!(
+                "Are you sure that you want to delete folder \"{}\"? Its feeds are kept",
+                app.folders[*folder_index].name
+            ),
+        ),
+        Row::RssFeed(rss_feed_index) | Row::RssEntry(rss_feed_index, _) => (
+            "Delete feed",
+            format!(
+                "Are you sure that you want to delete feed \"{}\"",
+                app.rss_feeds[*rss_feed_index].title
+            ),
+        ),
+        Row::VirtualFeed(_) | Row::VirtualFeedEntry(..) => return,
     };
     let area = frame.area();
-    let instructions = Line::from(vec![
-        " Yes".into(),
-        "<y> ".blue().bold().into(),
-        "No".into(),
-        "<n> ".blue().bold().into(),
-        "Cancel".into(),
-        "<q> ".blue().bold().into(),
-    ]);
+    let instructions = labelled_instructions(
+        app,
+        Mode::Popup,
+        &[
+            ("Yes", Action::Confirm),
+            ("No", Action::Deny),
+            ("Cancel", Action::Back),
+        ],
+    );
 
     let horizontal = Layout::horizontal([Constraint::Percentage(85)]).flex(Flex::Center);
     let [popup_area] = horizontal.areas(area);
     let text_width = popup_area.width;
-    let text = format!(
-        "Are you sure that you want to delete feed \"{}\"",
-        rss_feed_name
-    );
     let wrapped_text = wrap_str(&text, text_width as usize);
     let height = wrapped_text.len() + 2;
 
     let paragraph = Paragraph::new(text)
-        .style(Style::default().fg(Color::Rgb(255, 0, 0)))
+        .style(Style::default().fg(theme.error))
         .block(
-            Block::bordered()
-                .fg(Color::Rgb(255, 0, 0))
-                .title("Delete feed")
+            bordered_block(&theme)
+                .style(theme.base().fg(theme.error))
+                .border_style(Style::default().fg(theme.error))
+                .title(title)
                 .title_bottom(instructions.centered()),
         )
         .wrap(Wrap { trim: true });
@@ -494,34 +914,116 @@ fn draw_confirm_delete_rss_feed_popup(frame: &mut ratatui::Frame, app: &mut App)
     frame.render_widget(paragraph, popup_area);
 }
 
-/// Draws the popup that indicates that syncing is happening.
-fn draw_syncing_popup(frame: &mut ratatui::Frame, app: &mut App) {
+/// Splits a line off the bottom of an area for the sync status line,
+/// if syncing.
+fn split_sync_status_area(area: Rect, app: &App) -> (Rect, Option<Rect>) {
+    if !app.is_syncing() {
+        return (area, None);
+    }
+    let [main_area, status_area] =
+        Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(area);
+    (main_area, Some(status_area))
+}
+
+/// Draws the status line that shows how far along syncing is and which
+/// feed is being fetched.
+fn draw_sync_status(frame: &mut ratatui::Frame, app: &App, area: Rect) {
+    let theme = app.theme;
+    let style = Style::default().fg(theme.accent);
+    let mut spans = vec![Span::styled(
+        format!(
+            " {} {}/{} feeds",
+            SPINNER_CHARS[app.spinner_index],
+            app.sync_results.len(),
+            app.sync_total
+        ),
+        style,
+    )];
+    if app.sync_cancellation.is_cancelled() {
+        spans.push(Span::styled(" Cancelling…", style));
+    } else {
+        if let Some(rss_feed) = app
+            .fetching_rss_feed_ids
+            .last()
+            .and_then(|id| app.rss_feeds.iter().find(|f| f.id == *id))
+        {
+            let title_width = (area.width as usize).saturating_sub(32);
+            spans.push(Span::styled(
+                format!(" {}", truncate_str(&rss_feed.title, title_width)),
+                style,
+            ));
+        }
+        let cancel_key = app.keymap.key(app.mode(), Action::CancelSync);
+        if !cancel_key.is_empty() {
+            spans.push(" Cancel".into());
+            spans.push(format!("<{}>", cancel_key).fg(theme.key).bold());
+        }
+    }
+    frame.render_widget(Paragraph::new(Line::from(spans)).style(theme.base()), area);
+}
+
+/// Draws the popup that summarizes the last sync.
+fn draw_sync_report_popup(frame: &mut ratatui::Frame, app: &mut App) {
+    let theme = app.theme;
     let area = frame.area();
-    let spinner_char = SPINNER_CHARS[app.spinner_index];
-    let syncing_text = format!("Syncing {}", spinner_char);
-    let paragraph = Paragraph::new(syncing_text)
-        .style(Style::default().fg(Color::Rgb(255, 239, 0)))
-        .centered()
-        .block(Block::bordered().fg(Color::Rgb(255, 239, 0)));
-    let vertical = Layout::vertical([Constraint::Length(3)]).flex(Flex::Center);
+    let instructions = labelled_instructions(app, Mode::Popup, &[("Ok", Action::Select)]);
     let horizontal = Layout::horizontal([Constraint::Percentage(85)]).flex(Flex::Center);
-    let popup_area = area;
+    let [popup_area] = horizontal.areas(area);
+    let text_width = popup_area.width.saturating_sub(2) as usize;
+
+    let num_failed = app
+        .sync_results
+        .iter()
+        .filter(|r| r.error.is_some())
+        .count();
+    let num_new_rss_entries: usize = app.sync_results.iter().map(|r| r.new_rss_entries).sum();
+    let num_updated_rss_entries: usize =
+        app.sync_results.iter().map(|r| r.updated_rss_entries).sum();
+    let summary = format!(
+        "Synced {} of {} feeds, {} new entries, {} updated",
+        app.sync_results.len() - num_failed,
+        app.sync_results.len(),
+        num_new_rss_entries,
+        num_updated_rss_entries
+    );
+
+    let mut lines: Vec<Line> = wrap_str(&summary, text_width)
+        .into_iter()
+        .map(Line::from)
+        .collect();
+    for sync_result in app.sync_results.iter() {
+        if let Some(error) = &sync_result.error {
+            let failure = format!("{}: {}", sync_result.rss_feed_title, error);
+            for wrapped_line in wrap_str(&failure, text_width) {
+                lines.push(Line::from(wrapped_line).fg(theme.error));
+            }
+        }
+    }
+
+    let height = (lines.len() + 2).min(area.height as usize);
+    let paragraph = Paragraph::new(lines).block(
+        bordered_block(&theme)
+            .title("Sync report")
+            .title_bottom(instructions.centered()),
+    );
+    let vertical = Layout::vertical([Constraint::Length(height as u16)]).flex(Flex::Center);
     let [popup_area] = vertical.areas(popup_area);
-    let [popup_area] = horizontal.areas(popup_area);
 
     frame.render_widget(Clear, popup_area);
     frame.render_widget(paragraph, popup_area);
 }
 
 /// Draws the error popup, which an error message.
-fn draw_error_popup(frame: &mut ratatui::Frame, error_message: &str) {
+fn draw_error_popup(frame: &mut ratatui::Frame, app: &mut App, error_message: &str) {
+    let theme = &app.theme;
     let area = frame.area();
-    let instructions = Line::from(vec![" Ok".into(), "<Enter> ".blue().bold().into()]);
+    let instructions = labelled_instructions(app, Mode::Popup, &[("Ok", Action::Select)]);
     let paragraph = Paragraph::new(format!("Error: {}", error_message))
-        .style(Style::default().fg(Color::Rgb(255, 0, 0)))
+        .style(Style::default().fg(theme.error))
         .block(
-            Block::bordered()
-                .fg(Color::Rgb(255, 0, 0))
+            bordered_block(theme)
+                .style(theme.base().fg(theme.error))
+                .border_style(Style::default().fg(theme.error))
                 .title("Error")
                 .title_bottom(instructions.centered()),
         );
@@ -535,6 +1037,50 @@ fn draw_error_popup(frame: &mut ratatui::Frame, error_message: &str) {
     frame.render_widget(paragraph, popup_area);
 }
 
+/// Draws the info popup, which displays an informational message.
+fn draw_info_popup(frame: &mut ratatui::Frame, app: &mut App, info_message: &str) {
+    let theme = &app.theme;
+    let area = frame.area();
+    let instructions = labelled_instructions(app, Mode::Popup, &[("Ok", Action::Select)]);
+    let paragraph = Paragraph::new(info_message)
+        .wrap(Wrap { trim: true })
+        .block(
+            bordered_block(theme)
+                .title("Info")
+                .title_bottom(instructions.centered()),
+        );
+    let vertical = Layout::vertical([Constraint::Length(3)]).flex(Flex::Center);
+    let horizontal = Layout::horizontal([Constraint::Percentage(85)]).flex(Flex::Center);
+    let popup_area = area;
+    let [popup_area] = vertical.areas(popup_area);
+    let [popup_area] = horizontal.areas(popup_area);
+
+    frame.render_widget(Clear, popup_area);
+    frame.render_widget(paragraph, popup_area);
+}
+
+/// Copies text to the clipboard of the terminal, which may be on
+/// another machine, with an OSC 52 escape sequence. Terminals that don't
+/// support the sequence ignore it.
+pub fn copy_to_clipboard(text: &str) -> std::io::Result<()> {
+    let mut stdout = std::io::stdout();
+    stdout.write_all(osc52_sequence(text).as_bytes())?;
+    stdout.flush()
+}
+
+/// Builds the OSC 52 escape sequence that copies text to the clipboard.
+fn osc52_sequence(text: &str) -> String {
+    format!("\x1b]52;c;{}\x07", BASE64_STANDARD.encode(text))
+}
+
+/// Creates a bordered block drawn in a theme's colors.
+fn bordered_block<'a>(theme: &Theme) -> Block<'a> {
+    Block::bordered()
+        .style(theme.base())
+        .border_style(theme.border_style())
+        .title_style(theme.title_style())
+}
+
 /// Wraps a string to a particular width.
 fn wrap_str(text: &str, width: usize) -> Vec<String> {
     let options = textwrap::Options::new(width).break_words(false);
@@ -547,6 +1093,63 @@ fn wrap_str(text: &str, width: usize) -> Vec<String> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::app::{Folder, RssEntry};
+    use ratatui::style::Color;
+
+    /// Tests that folders come first, with the feeds of expanded folders
+    /// beneath them, followed by feeds without a folder.
+    #[tokio::test]
+    async fn test_get_rows_with_folders() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let (sender, _) = tokio::sync::mpsc::unbounded_channel();
+        let mut app = App::new(
+            sender,
+            Some(temp_dir.path().to_path_buf()),
+            crate::local_storage::RetentionPolicy::default(),
+            crate::app::SyncOptions::default(),
+        )
+        .unwrap();
+        app.folders = vec![
+            Folder {
+                id: 1,
+                name: "Collapsed".to_string(),
+                expanded: false,
+            },
+            Folder {
+                id: 2,
+                name: "Expanded".to_string(),
+                expanded: true,
+            },
+        ];
+        let rss_feed = |folder: Option<&str>, expanded: bool| RssFeed {
+            folder: folder.map(|f| f.to_string()),
+            expanded,
+            rss_entries: vec![RssEntry::default()],
+            ..Default::default()
+        };
+        app.rss_feeds = vec![
+            rss_feed(None, true),
+            rss_feed(Some("Collapsed"), true),
+            rss_feed(Some("Expanded"), true),
+            rss_feed(Some("Missing"), false),
+        ];
+
+        assert!(
+            get_rows(&app)
+                == vec![
+                    Row::VirtualFeed(VirtualFeed::AllUnread),
+                    Row::VirtualFeed(VirtualFeed::Today),
+                    Row::VirtualFeed(VirtualFeed::Starred),
+                    Row::Folder(0),
+                    Row::Folder(1),
+                    Row::RssFeed(2),
+                    Row::RssEntry(2, 0),
+                    Row::RssFeed(0),
+                    Row::RssEntry(0, 0),
+                    Row::RssFeed(3),
+                ]
+        );
+    }
 
     /// Tests wrapping a string containing two words.
     #[test]
@@ -579,7 +1182,7 @@ mod tests {
     #[test]
     fn test_truncate_str_simple() {
         let test_title = "test_title";
-        let truncated_title = truncate_str(&test_title, 7);
+        let truncated_title = truncate_str(test_title, 7);
         assert!(truncated_title == "test...");
     }
 
@@ -588,6 +1191,33 @@ mod tests {
     fn test_truncate_str_empty() {
         let test_title = String::new();
         let truncated_title = truncate_str(&test_title, 7);
-        assert!(truncated_title == "");
+        assert!(truncated_title.is_empty());
+    }
+
+    /// Tests highlighting matches within text.
+    #[test]
+    fn test_highlight_matches() {
+        let spans = highlight_matches("Rust and rusty", &[0..4, 9..13], &Theme::default());
+        let contents: Vec<&str> = spans.iter().map(|s| s.content.as_ref()).collect();
+        assert!(contents == vec!["Rust", " and ", "rust", "y"]);
+        assert!(spans[0].style.fg == Some(Color::Rgb(255, 179, 0)));
+        assert!(spans[1].style.fg.is_none());
+    }
+
+    /// Tests that copied text is base64 encoded within the OSC 52
+    /// sequence.
+    #[test]
+    fn test_osc52_sequence() {
+        assert!(
+            osc52_sequence("https://example.com") == "\x1b]52;c;aHR0cHM6Ly9leGFtcGxlLmNvbQ==\x07"
+        );
+    }
+
+    /// Tests that text without matches is a single plain span.
+    #[test]
+    fn test_highlight_matches_none() {
+        let spans = highlight_matches("Plain", &[], &Theme::default());
+        assert!(spans.len() == 1);
+        assert!(spans[0].content == "Plain");
     }
 }